@@ -0,0 +1,667 @@
+//! Validation of barcode payloads before they are sent to the printer, which
+//! silently prints nothing for data it can't encode.
+
+use crate::{
+    databar,
+    error::{BarcodeError, Error},
+    gs1::is_gs1_char,
+    printer::{BarcodeType, DataMatrixSize},
+    render::{ean_check_digit, upc_e_to_upc_a},
+};
+
+/// `GS k` and `ESC b` take the data length as a single byte.
+const MAX_LENGTH: usize = 255;
+
+/// Characters of Code 39, besides the `*` start and stop character.
+const CODE39_CHARS: &str = "0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZ -.$/+%";
+
+/// Characters between the CODABAR start and stop characters.
+const CODABAR_CHARS: &str = "0123456789-$:/.+";
+
+/// Function character 1 in CODE128 data, used to mark GS1 data and to
+/// separate variable length fields.
+pub const FNC1: char = '\u{e001}';
+/// Function character 2 in CODE128 data, asking the scanner to buffer the
+/// message until the next symbol.
+pub const FNC2: char = '\u{e002}';
+/// Function character 3 in CODE128 data, used for reader programming.
+pub const FNC3: char = '\u{e003}';
+/// Function character 4 in CODE128 data, used for extended ASCII.
+pub const FNC4: char = '\u{e004}';
+
+/// Checks `data` against the rules of `r#type` and returns the payload to
+/// send to the printer.
+///
+/// The check digit of UPC-A, UPC-E, JAN-13 and JAN-8 is appended when it is
+/// omitted, and verified otherwise. GS1 DataBar payloads are given as a GTIN,
+/// with or without its check digit, and sent without it as the printer
+/// computes it.
+pub(crate) fn validate(data: &str, r#type: BarcodeType) -> Result<String, Error> {
+    let invalid = |reason| Error::InvalidBarcode { r#type, reason };
+
+    match r#type {
+        BarcodeType::UPCA => with_check_digit(data, 12).map_err(invalid),
+        BarcodeType::UPCE => upc_e(data).map_err(invalid),
+        BarcodeType::JAN13 => with_check_digit(data, 13).map_err(invalid),
+        BarcodeType::JAN8 => with_check_digit(data, 8).map_err(invalid),
+        BarcodeType::CODE39 => code39(data).map_err(invalid),
+        BarcodeType::ITF => itf(data).map_err(invalid),
+        BarcodeType::CODABAR => codabar(data).map_err(invalid),
+        BarcodeType::CODE93 => {
+            check_length(data, 1, MAX_LENGTH)
+                .and_then(|_| check_chars(data, |c| c.is_ascii()))
+                .map_err(invalid)?;
+            Ok(data.to_string())
+        }
+        BarcodeType::CODE128 => {
+            check_chars(data, |c| c.is_ascii() || matches!(c, FNC1..=FNC4)).map_err(invalid)?;
+            check_length(data, 1, usize::MAX).map_err(invalid)?;
+
+            // The limit applies to the data after code set selection.
+            let length = code128(data).len();
+            if length > MAX_LENGTH {
+                return Err(invalid(BarcodeError::InvalidLength {
+                    min: 1,
+                    max: MAX_LENGTH,
+                    length,
+                }));
+            }
+            Ok(data.to_string())
+        }
+        BarcodeType::GS1128 => {
+            check_length(data, 2, MAX_LENGTH)
+                .and_then(|_| check_chars(data, |c| c.is_ascii() || c == FNC1))
+                .map_err(invalid)?;
+
+            // Each FNC1 is sent as two bytes.
+            let length = encode(data, r#type).len();
+            if length > MAX_LENGTH {
+                return Err(invalid(BarcodeError::InvalidLength {
+                    min: 2,
+                    max: MAX_LENGTH,
+                    length,
+                }));
+            }
+            Ok(data.to_string())
+        }
+        BarcodeType::GS1DataBarOmnidirectional | BarcodeType::GS1DataBarTruncated => {
+            gtin(data).map_err(invalid)
+        }
+        BarcodeType::GS1DataBarLimited => {
+            let gtin = gtin(data).map_err(invalid)?;
+            // Limited symbols encode GTINs starting with 0 or 1 only.
+            if !gtin.starts_with(['0', '1']) {
+                return Err(invalid(BarcodeError::InvalidCharacter {
+                    character: gtin.chars().next().unwrap_or_default(),
+                    position: 0,
+                }));
+            }
+            Ok(gtin)
+        }
+        BarcodeType::GS1DataBarExpanded => {
+            check_length(data, 2, MAX_LENGTH)
+                .and_then(|_| check_chars(data, |c| is_gs1_char(c) || c == ' ' || c == FNC1))
+                .map_err(invalid)?;
+            if databar::expanded_bits(data).is_none() {
+                return Err(invalid(BarcodeError::TooLong));
+            }
+            Ok(data.to_string())
+        }
+    }
+}
+
+pub(crate) fn check_length(data: &str, min: usize, max: usize) -> Result<(), BarcodeError> {
+    let length = data.chars().count();
+    if length < min || length > max {
+        return Err(BarcodeError::InvalidLength { min, max, length });
+    }
+
+    Ok(())
+}
+
+pub(crate) fn check_chars(data: &str, allowed: impl Fn(char) -> bool) -> Result<(), BarcodeError> {
+    match data.chars().enumerate().find(|(_, c)| !allowed(*c)) {
+        Some((position, character)) => Err(BarcodeError::InvalidCharacter {
+            character,
+            position,
+        }),
+        None => Ok(()),
+    }
+}
+
+/// The most data a 2D symbol holds, by the compaction its data allows.
+pub(crate) struct Capacity {
+    /// Digits only.
+    pub digits: usize,
+    /// ASCII text.
+    pub text: usize,
+    /// Any bytes.
+    pub bytes: usize,
+}
+
+/// PDF417 holds 2710 digits, 1850 characters or 1108 bytes.
+pub(crate) const PDF417_CAPACITY: Capacity = Capacity {
+    digits: 2710,
+    text: 1850,
+    bytes: 1108,
+};
+
+/// DataMatrix holds 3116 digits, 2335 characters or 1556 bytes.
+pub(crate) const DATA_MATRIX_CAPACITY: Capacity = Capacity {
+    digits: 3116,
+    text: 2335,
+    bytes: 1556,
+};
+
+/// Aztec Code holds 3832 digits, 3067 characters or 1914 bytes.
+pub(crate) const AZTEC_CAPACITY: Capacity = Capacity {
+    digits: 3832,
+    text: 3067,
+    bytes: 1914,
+};
+
+/// MaxiCode holds 138 digits or 93 characters.
+pub(crate) const MAXI_CODE_CAPACITY: Capacity = Capacity {
+    digits: 138,
+    text: 93,
+    bytes: 93,
+};
+
+/// Sizes of square DataMatrix ECC 200 symbols.
+const DATA_MATRIX_SQUARES: [u8; 24] = [
+    10, 12, 14, 16, 18, 20, 22, 24, 26, 32, 36, 40, 44, 48, 52, 64, 72, 80, 88, 96, 104, 120, 132,
+    144,
+];
+
+/// Columns and rows of rectangular DataMatrix ECC 200 symbols.
+const DATA_MATRIX_RECTANGLES: [(u8, u8); 6] =
+    [(18, 8), (32, 8), (26, 12), (36, 12), (36, 16), (48, 16)];
+
+/// Checks that a DataMatrix symbol of `size` exists, 0 letting the printer
+/// fit the data.
+pub(crate) fn check_data_matrix_size(size: &DataMatrixSize) -> Result<(), BarcodeError> {
+    let exists = match *size {
+        DataMatrixSize::Auto | DataMatrixSize::Square(0) => true,
+        DataMatrixSize::Square(size) => DATA_MATRIX_SQUARES.contains(&size),
+        DataMatrixSize::Rectangle(columns, rows) => DATA_MATRIX_RECTANGLES
+            .iter()
+            .any(|&(c, r)| (columns == 0 || columns == c) && (rows == 0 || rows == r)),
+    };
+
+    match exists {
+        true => Ok(()),
+        false => Err(BarcodeError::InvalidSize),
+    }
+}
+
+/// Checks that `data` isn't empty and fits in a symbol of `capacity`, which
+/// also keeps its length within the 16 bits of the store commands.
+pub(crate) fn check_capacity(data: &str, capacity: &Capacity) -> Result<(), BarcodeError> {
+    let max = if data.bytes().all(|byte| byte.is_ascii_digit()) {
+        capacity.digits
+    } else if data.is_ascii() {
+        capacity.text
+    } else {
+        capacity.bytes
+    };
+
+    let length = data.len();
+    if length == 0 || length > max {
+        return Err(BarcodeError::InvalidLength {
+            min: 1,
+            max,
+            length,
+        });
+    }
+
+    Ok(())
+}
+
+fn digits(data: &str) -> Result<Vec<u8>, BarcodeError> {
+    check_chars(data, |c| c.is_ascii_digit())?;
+    Ok(data.bytes().map(|c| c - b'0').collect())
+}
+
+fn to_string(digits: &[u8]) -> String {
+    digits.iter().map(|d| char::from(b'0' + d)).collect()
+}
+
+/// Appends the check digit of an EAN/UPC number of `length` digits if only
+/// the data digits were given, or verifies it otherwise.
+pub(crate) fn with_check_digit(data: &str, length: usize) -> Result<String, BarcodeError> {
+    check_length(data, length - 1, length)?;
+    let mut digits = digits(data)?;
+
+    let expected = ean_check_digit(&digits[..length - 1]);
+    match digits.get(length - 1) {
+        Some(&found) if found != expected => {
+            return Err(BarcodeError::InvalidCheckDigit { expected, found })
+        }
+        Some(_) => {}
+        None => digits.push(expected),
+    }
+
+    Ok(to_string(&digits))
+}
+
+/// Normalizes a UPC-E number given as 6 digits (number system 0), 7 digits
+/// or 8 digits with the check digit to the 8 digit form.
+fn upc_e(data: &str) -> Result<String, BarcodeError> {
+    check_length(data, 6, 8)?;
+    let digits = digits(data)?;
+
+    let (number_system, body, check) = match digits.len() {
+        6 => (0, &digits[..], None),
+        7 => (digits[0], &digits[1..], None),
+        _ => (digits[0], &digits[1..7], Some(digits[7])),
+    };
+
+    if number_system > 1 {
+        return Err(BarcodeError::InvalidNumberSystem(number_system));
+    }
+
+    let expected = ean_check_digit(&upc_e_to_upc_a(number_system, body));
+    if let Some(found) = check.filter(|&found| found != expected) {
+        return Err(BarcodeError::InvalidCheckDigit { expected, found });
+    }
+
+    let mut normalized = vec![number_system];
+    normalized.extend_from_slice(body);
+    normalized.push(expected);
+    Ok(to_string(&normalized))
+}
+
+fn code39(data: &str) -> Result<String, BarcodeError> {
+    check_length(data, 1, MAX_LENGTH)?;
+
+    // The start and stop characters may be given, but only together.
+    let last = data.chars().count() - 1;
+    let enclosed = last > 0 && data.starts_with('*') && data.ends_with('*');
+    for (position, character) in data.chars().enumerate() {
+        let start_stop = enclosed && (position == 0 || position == last);
+        if !start_stop && !CODE39_CHARS.contains(character) {
+            return Err(BarcodeError::InvalidCharacter {
+                character,
+                position,
+            });
+        }
+    }
+
+    Ok(data.to_string())
+}
+
+fn itf(data: &str) -> Result<String, BarcodeError> {
+    check_length(data, 2, MAX_LENGTH)?;
+    digits(data)?;
+
+    if !data.len().is_multiple_of(2) {
+        return Err(BarcodeError::OddLength { length: data.len() });
+    }
+
+    Ok(data.to_string())
+}
+
+fn codabar(data: &str) -> Result<String, BarcodeError> {
+    check_length(data, 2, MAX_LENGTH)?;
+    check_chars(data, |c| c.is_ascii())?;
+
+    let is_start_stop = |c: u8| matches!(c.to_ascii_uppercase(), b'A'..=b'D');
+    let bytes = data.as_bytes();
+    if !is_start_stop(bytes[0]) || !is_start_stop(bytes[bytes.len() - 1]) {
+        return Err(BarcodeError::MissingStartStop);
+    }
+
+    let inner = &data[1..data.len() - 1];
+    check_chars(inner, |c| CODABAR_CHARS.contains(c)).map_err(|error| match error {
+        BarcodeError::InvalidCharacter {
+            character,
+            position,
+        } => BarcodeError::InvalidCharacter {
+            character,
+            position: position + 1,
+        },
+        error => error,
+    })?;
+
+    Ok(data.to_string())
+}
+
+/// Returns the 13 data digits of a GTIN given with or without its check
+/// digit.
+fn gtin(data: &str) -> Result<String, BarcodeError> {
+    let mut gtin = with_check_digit(data, 14)?;
+    gtin.pop();
+    Ok(gtin)
+}
+
+/// Converts validated data into the format of `GS k`, where the function
+/// characters are written `{1`..`{4`.
+pub(crate) fn encode(data: &str, r#type: BarcodeType) -> Vec<u8> {
+    match r#type {
+        BarcodeType::CODE128 => code128(data),
+        BarcodeType::GS1128 | BarcodeType::GS1DataBarExpanded => {
+            data.replace(FNC1, "{1").into_bytes()
+        }
+        _ => data.as_bytes().to_vec(),
+    }
+}
+
+/// Converts `GS k` data back into the validated data [`encode`] made it
+/// from.
+pub(crate) fn decode(encoded: &[u8], r#type: BarcodeType) -> String {
+    match r#type {
+        BarcodeType::CODE128 => {
+            let mut data = String::new();
+            let mut set_c = false;
+
+            let mut i = 0;
+            while i < encoded.len() {
+                match (encoded[i], encoded.get(i + 1)) {
+                    (b'{', Some(b'A' | b'B')) => set_c = false,
+                    (b'{', Some(b'C')) => set_c = true,
+                    (b'{', Some(b'S')) => {}
+                    (b'{', Some(&n @ b'1'..=b'4')) => {
+                        data.push(char::from_u32(FNC1 as u32 + (n - b'1') as u32).unwrap())
+                    }
+                    (b'{', Some(b'{')) => data.push('{'),
+                    (pair, _) if set_c => {
+                        data.push_str(&format!("{:02}", pair));
+                        i += 1;
+                        continue;
+                    }
+                    (c, _) => {
+                        data.push(c as char);
+                        i += 1;
+                        continue;
+                    }
+                }
+                i += 2;
+            }
+
+            data
+        }
+        BarcodeType::GS1128 | BarcodeType::GS1DataBarExpanded => {
+            String::from_utf8_lossy(encoded).replace("{1", &FNC1.to_string())
+        }
+        _ => String::from_utf8_lossy(encoded).into_owned(),
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum CodeSet {
+    A,
+    B,
+    C,
+}
+
+/// Number of consecutive digits in `data` from `start`.
+fn digit_run(data: &[char], start: usize) -> usize {
+    data[start..]
+        .iter()
+        .take_while(|c| c.is_ascii_digit())
+        .count()
+}
+
+/// Picks code set A or B for the characters from `start`, depending on
+/// whether a control character or a lowercase letter comes first.
+fn code_set_for(data: &[char], start: usize) -> CodeSet {
+    data[start..]
+        .iter()
+        .find_map(|&c| match c {
+            '\0'..='\x1f' => Some(CodeSet::A),
+            '`'..='\x7f' => Some(CodeSet::B),
+            _ => None,
+        })
+        .unwrap_or(CodeSet::B)
+}
+
+fn can_encode(set: CodeSet, c: char) -> bool {
+    match set {
+        CodeSet::A => c < '`',
+        CodeSet::B => c >= ' ',
+        CodeSet::C => false,
+    }
+}
+
+/// Converts validated CODE128 data into the `GS k` format, choosing the code
+/// sets that give the shortest symbol: `{A`, `{B` and `{C` select a code set,
+/// `{S` shifts a single character, `{1`..`{4` are the function characters
+/// and `{{` a literal brace. In code set C each byte holds two digits.
+pub(crate) fn code128(data: &str) -> Vec<u8> {
+    let data: Vec<char> = data.chars().collect();
+    let mut encoded = Vec::with_capacity(data.len() + 2);
+    let mut set = None;
+
+    let mut i = 0;
+    while i < data.len() {
+        let c = data[i];
+        let run = digit_run(&data, i);
+
+        if set.is_none() {
+            // The function characters say nothing about the best start.
+            let first = data[i..]
+                .iter()
+                .position(|c| !matches!(c, FNC1..=FNC4))
+                .map_or(data.len(), |position| i + position);
+            let run = digit_run(&data, first);
+            let start = if run >= 4 || (run == 2 && first + 2 == data.len()) {
+                CodeSet::C
+            } else {
+                code_set_for(&data, first)
+            };
+
+            encoded.extend_from_slice(match start {
+                CodeSet::A => b"{A",
+                CodeSet::B => b"{B",
+                CodeSet::C => b"{C",
+            });
+            set = Some(start);
+        }
+
+        let current = set.unwrap_or(CodeSet::B);
+
+        // Code set C only pays off for runs of at least 4 digits at the
+        // end of the data and 6 digits elsewhere.
+        let use_c = match current {
+            CodeSet::C => run >= 2,
+            _ => run >= 6 || (run >= 4 && i + run == data.len()),
+        };
+
+        if use_c {
+            if current != CodeSet::C {
+                if run % 2 == 1 {
+                    // The odd digit goes in the current code set.
+                    encoded.push(c as u8);
+                    i += 1;
+                    continue;
+                }
+                encoded.extend_from_slice(b"{C");
+                set = Some(CodeSet::C);
+            }
+
+            let pair = (data[i] as u8 - b'0') * 10 + data[i + 1] as u8 - b'0';
+            encoded.push(pair);
+            i += 2;
+            continue;
+        }
+
+        if let FNC1..=FNC4 = c {
+            // Code set C has FNC1 only.
+            if current == CodeSet::C && c != FNC1 {
+                encoded.extend_from_slice(b"{B");
+                set = Some(CodeSet::B);
+            }
+
+            encoded.push(b'{');
+            encoded.push(b'1' + (c as u32 - FNC1 as u32) as u8);
+            i += 1;
+            continue;
+        }
+
+        if current == CodeSet::C {
+            let next = code_set_for(&data, i);
+            encoded.extend_from_slice(if next == CodeSet::A { b"{A" } else { b"{B" });
+            set = Some(next);
+        } else if !can_encode(current, c) {
+            let other = if current == CodeSet::A {
+                CodeSet::B
+            } else {
+                CodeSet::A
+            };
+
+            // A single character of the other set is shifted rather than
+            // switched to.
+            let single = data
+                .get(i + 1)
+                .is_some_and(|&next| !can_encode(other, next) || can_encode(current, next));
+            if single {
+                encoded.extend_from_slice(b"{S");
+            } else {
+                encoded.extend_from_slice(if other == CodeSet::A { b"{A" } else { b"{B" });
+                set = Some(other);
+            }
+        }
+
+        if c == '{' {
+            encoded.push(b'{');
+        }
+        encoded.push(c as u8);
+        i += 1;
+    }
+
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn measures_gs1_128_once_encoded() {
+        let data = "10A\u{e001}".repeat(60);
+        assert_eq!(encode(&data, BarcodeType::GS1128).len(), 300);
+        assert!(matches!(
+            validate(&data, BarcodeType::GS1128),
+            Err(Error::InvalidBarcode {
+                reason: BarcodeError::InvalidLength { length: 300, .. },
+                ..
+            })
+        ));
+
+        let data = "10A\u{e001}".repeat(50);
+        assert_eq!(validate(&data, BarcodeType::GS1128).unwrap(), data);
+    }
+
+    #[test]
+    fn adds_and_checks_ean_check_digits() {
+        assert_eq!(
+            validate("03600029145", BarcodeType::UPCA).unwrap(),
+            "036000291452"
+        );
+        assert_eq!(
+            validate("036000291452", BarcodeType::UPCA).unwrap(),
+            "036000291452"
+        );
+        assert!(matches!(
+            validate("036000291453", BarcodeType::UPCA),
+            Err(Error::InvalidBarcode {
+                reason: BarcodeError::InvalidCheckDigit {
+                    expected: 2,
+                    found: 3
+                },
+                ..
+            })
+        ));
+        assert_eq!(
+            validate("400638133393", BarcodeType::JAN13).unwrap(),
+            "4006381333931"
+        );
+        assert_eq!(validate("9638507", BarcodeType::JAN8).unwrap(), "96385074");
+    }
+
+    #[test]
+    fn normalizes_upc_e() {
+        assert_eq!(validate("425261", BarcodeType::UPCE).unwrap(), "04252614");
+        assert_eq!(validate("0425261", BarcodeType::UPCE).unwrap(), "04252614");
+        assert!(matches!(
+            validate("2425261", BarcodeType::UPCE),
+            Err(Error::InvalidBarcode {
+                reason: BarcodeError::InvalidNumberSystem(2),
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn checks_characters() {
+        assert!(validate("*CODE 39*", BarcodeType::CODE39).is_ok());
+        assert!(matches!(
+            validate("CODE*39", BarcodeType::CODE39),
+            Err(Error::InvalidBarcode {
+                reason: BarcodeError::InvalidCharacter {
+                    character: '*',
+                    position: 4
+                },
+                ..
+            })
+        ));
+        assert!(matches!(
+            validate("12345", BarcodeType::ITF),
+            Err(Error::InvalidBarcode {
+                reason: BarcodeError::OddLength { length: 5 },
+                ..
+            })
+        ));
+        assert!(validate("a40156b", BarcodeType::CODABAR).is_ok());
+        assert!(matches!(
+            validate("40156", BarcodeType::CODABAR),
+            Err(Error::InvalidBarcode {
+                reason: BarcodeError::MissingStartStop,
+                ..
+            })
+        ));
+        assert!(matches!(
+            validate("A40E56B", BarcodeType::CODABAR),
+            Err(Error::InvalidBarcode {
+                reason: BarcodeError::InvalidCharacter {
+                    character: 'E',
+                    position: 3
+                },
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn sends_gtins_without_check_digit() {
+        let gtin = "09501101530003";
+        assert_eq!(
+            validate(gtin, BarcodeType::GS1DataBarOmnidirectional).unwrap(),
+            "0950110153000"
+        );
+        assert_eq!(
+            validate(&gtin[..13], BarcodeType::GS1DataBarLimited).unwrap(),
+            "0950110153000"
+        );
+        assert!(validate("2950110153000", BarcodeType::GS1DataBarLimited).is_err());
+    }
+
+    #[test]
+    fn picks_code128_code_sets() {
+        let encode = |data| String::from_utf8(code128(data)).unwrap();
+        assert_eq!(encode("ABC"), "{BABC");
+        assert_eq!(encode("12"), "{C\x0c");
+        assert_eq!(encode("123456"), "{C\x0c\x22\x38");
+        assert_eq!(encode("AB1234"), "{BAB{C\x0c\x22");
+        assert_eq!(encode("AB12345"), "{BAB1{C\x17\x2d");
+        assert_eq!(encode("\u{e001}0101"), "{C{1\x01\x01");
+        assert_eq!(encode("a{b"), "{Ba{{b");
+    }
+
+    #[test]
+    fn decodes_code128() {
+        for data in ["ABC", "123456", "AB1234", "\u{e001}0101", "a{b", "A\tb"] {
+            assert_eq!(decode(&code128(data), BarcodeType::CODE128), data);
+        }
+    }
+}
@@ -0,0 +1,154 @@
+//! A CUPS filter printing raster pages on ESC/POS receipt printers, so that
+//! any application can print on them.
+//!
+//! CUPS runs it as `rastertoescpos JOB USER TITLE COPIES OPTIONS [FILE]`.
+//! It reads CUPS raster from `FILE` or standard input and writes ESC/POS to
+//! standard output. The options it reads are:
+//!
+//! - `profile=ID`, the [`PrinterProfile`] of the printer, `generic` by
+//!   default.
+//! - `cut=partial`, `cut=full` or `cut=none`, how to cut the paper,
+//!   `partial` by default.
+//!
+//! Pages are scaled down to the print area if they are wider, dithered, and
+//! printed without the blank paper at their end. The paper is cut when the
+//! `cupsCutMedia` of the pages says, set by the `CutMedia` option of the
+//! PPD: after each page for 4, after the last page for 1 to 3, the job being
+//! a single document and copy, and never for 0.
+
+use std::{
+    fs::File,
+    io::{self, BufReader, Read, Write},
+    process::ExitCode,
+};
+
+use thermal_printer::{BasePrinter, CupsRaster, Image, Printer, PrinterProfile};
+
+/// Rows of dots sent in each raster image, small enough for the receive
+/// buffer of most printers.
+const BAND_HEIGHT: usize = 128;
+
+/// Values of `cupsCutMedia`.
+const CUT_AFTER_PAGE: u32 = 4;
+const CUT_NEVER: u32 = 0;
+
+#[derive(Clone, Copy)]
+enum Cut {
+    Partial,
+    Full,
+    None,
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().collect();
+    if !(6..=7).contains(&args.len()) {
+        eprintln!("Usage: rastertoescpos JOB USER TITLE COPIES OPTIONS [FILE]");
+        return ExitCode::FAILURE;
+    }
+
+    match run(&args[5], args.get(6)) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(message) => {
+            eprintln!("ERROR: {}", message);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(options: &str, file: Option<&String>) -> Result<(), String> {
+    let mut profile = PrinterProfile::default();
+    let mut cut = Cut::Partial;
+    for option in options.split_whitespace() {
+        match option.split_once('=') {
+            Some(("profile", id)) => {
+                profile = PrinterProfile::find(id).ok_or(format!("unknown profile {:?}", id))?
+            }
+            Some(("cut", "partial")) => cut = Cut::Partial,
+            Some(("cut", "full")) => cut = Cut::Full,
+            Some(("cut", "none")) => cut = Cut::None,
+            Some(("cut", value)) => return Err(format!("unknown cut {:?}", value)),
+            _ => {}
+        }
+    }
+
+    let input: Box<dyn Read> = match file {
+        Some(path) => {
+            Box::new(File::open(path).map_err(|error| format!("can't read {}: {}", path, error))?)
+        }
+        None => Box::new(io::stdin()),
+    };
+    let mut raster = CupsRaster::new(BufReader::new(input))
+        .map_err(|error| format!("can't read the raster: {}", error))?;
+
+    let mut printer = BasePrinter::new(profile);
+    printer.initialize();
+    let mut output = io::stdout().lock();
+
+    let mut number = 0;
+    let mut cut_at_end = false;
+    while let Some(page) = raster
+        .next_page()
+        .map_err(|error| format!("can't read page {}: {}", number + 1, error))?
+    {
+        number += 1;
+        eprintln!("INFO: Printing page {}", number);
+
+        let image = page.to_image(printer.print_area_width()).dither();
+        for band in bands(&image) {
+            printer.image(band);
+        }
+        cut_at_end = match page.cut_media {
+            CUT_AFTER_PAGE => {
+                cut_paper(&mut printer, cut);
+                false
+            }
+            CUT_NEVER => false,
+            _ => true,
+        };
+
+        send(&mut output, &mut printer)?;
+        eprintln!("PAGE: {} 1", number);
+    }
+
+    if cut_at_end {
+        cut_paper(&mut printer, cut);
+        send(&mut output, &mut printer)?;
+    }
+
+    Ok(())
+}
+
+fn cut_paper(printer: &mut BasePrinter, cut: Cut) {
+    match cut {
+        Cut::Partial => printer.cut(true),
+        Cut::Full => printer.cut(false),
+        Cut::None => printer,
+    };
+}
+
+/// Writes the commands of `printer` to `output` and clears them.
+fn send(output: &mut impl Write, printer: &mut BasePrinter) -> Result<(), String> {
+    output
+        .write_all(&printer.get_data())
+        .and_then(|()| output.flush())
+        .map_err(|error| format!("can't write to the printer: {}", error))?;
+    printer.clear();
+    Ok(())
+}
+
+/// Splits `image` in bands of [`BAND_HEIGHT`] rows, leaving out the blank
+/// rows at its end.
+fn bands(image: &Image) -> Vec<Image> {
+    let rows = image.get_pixels();
+    let Some(last) = rows
+        .iter()
+        .rposition(|row| row.iter().any(|pixel| pixel.is_gray()))
+    else {
+        return vec![];
+    };
+
+    rows[..=last]
+        .chunks(BAND_HEIGHT)
+        .map(|band| Image::new(band.concat(), image.width(), band.len() as u16))
+        .collect()
+}
@@ -0,0 +1,259 @@
+//! Prints files on receipt printers, and converts, decodes and previews
+//! ESC/POS data.
+//!
+//! The kind of an input file is read from its extension: `.txt` is printed
+//! line by line, `.json` is a serialized [`Receipt`], `.md` is
+//! [`Markdown`], `.html` is [`Html`], `.png`, `.jpg`, `.gif` and `.bmp` are
+//! images scaled down to the print area, and `.bin` and `.prn` are ESC/POS
+//! data sent as is.
+
+use std::{fs, path::Path, process::ExitCode, time::Duration};
+
+use thermal_printer::{
+    decode, preview, BasePrinter, Destination, Html, Image, Markdown, PrintServer, Printer,
+    PrinterProfile, Receipt,
+};
+
+const USAGE: &str = "\
+Usage:
+  thermal-printer print <FILE> --to <DESTINATION> [--profile <ID>] [--no-cut]
+  thermal-printer convert <FILE> -o <OUTPUT> [--profile <ID>] [--no-cut]
+  thermal-printer decode <FILE>
+  thermal-printer preview <FILE> -o <PNG> [--profile <ID>] [--no-cut]
+  thermal-printer serve --printer <NAME>=<DESTINATION>... [--listen <ADDRESS>]
+                        [--allow-origin <ORIGIN>...] [--profile <ID>]
+  thermal-printer profiles
+
+Files are read as text (.txt), receipts (.json), Markdown (.md), HTML
+(.html), images (.png, .jpg, .gif, .bmp) or ESC/POS data (.bin, .prn).
+
+Destinations are a device path such as /dev/usb/lp0, tcp:HOST[:PORT],
+serial:PATH[@BAUD] or file:PATH. The server listens on 127.0.0.1:8080 by
+default and only answers web pages of the origins allowed with
+--allow-origin.";
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+struct Options {
+    command: String,
+    file: Option<String>,
+    to: Option<String>,
+    output: Option<String>,
+    listen: Option<String>,
+    printers: Vec<(String, Destination)>,
+    origins: Vec<String>,
+    profile: PrinterProfile,
+    cut: bool,
+}
+
+fn main() -> ExitCode {
+    let options = match parse(std::env::args().skip(1)) {
+        Ok(options) => options,
+        Err(message) => {
+            eprintln!("error: {}\n\n{}", message, USAGE);
+            return ExitCode::from(2);
+        }
+    };
+
+    match run(&options) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(message) => {
+            eprintln!("error: {}", message);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn parse(mut args: impl Iterator<Item = String>) -> Result<Options, String> {
+    let mut options = Options {
+        command: args.next().ok_or("missing command")?,
+        file: None,
+        to: None,
+        output: None,
+        listen: None,
+        printers: vec![],
+        origins: vec![],
+        profile: PrinterProfile::default(),
+        cut: true,
+    };
+
+    if matches!(options.command.as_str(), "-h" | "--help") {
+        options.command = "help".to_string();
+    }
+
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| args.next().ok_or(format!("missing value for {}", name));
+        match arg.as_str() {
+            "--to" => options.to = Some(value("--to")?),
+            "-o" | "--output" => options.output = Some(value("--output")?),
+            "--listen" => options.listen = Some(value("--listen")?),
+            "--printer" => {
+                let printer = value("--printer")?;
+                let (name, destination) = printer
+                    .split_once('=')
+                    .ok_or(format!("expected NAME=DESTINATION, got {:?}", printer))?;
+                options
+                    .printers
+                    .push((name.to_string(), destination.parse()?));
+            }
+            "--allow-origin" => options.origins.push(value("--allow-origin")?),
+            "--profile" => {
+                let id = value("--profile")?;
+                options.profile = PrinterProfile::find(&id)
+                    .ok_or(format!("unknown profile {:?}, see `profiles`", id))?;
+            }
+            "--no-cut" => options.cut = false,
+            "-h" | "--help" => options.command = "help".to_string(),
+            _ if arg.starts_with('-') && arg.len() > 1 => {
+                return Err(format!("unknown option {}", arg))
+            }
+            _ if options.file.is_none() => options.file = Some(arg),
+            _ => return Err(format!("unexpected argument {:?}", arg)),
+        }
+    }
+
+    Ok(options)
+}
+
+fn run(options: &Options) -> Result<(), String> {
+    let file = || options.file.as_deref().ok_or("missing input file");
+    let output = || {
+        options
+            .output
+            .as_deref()
+            .ok_or("missing output file, use -o")
+    };
+
+    match options.command.as_str() {
+        "print" => {
+            let destination: Destination = options
+                .to
+                .as_deref()
+                .ok_or("missing destination, use --to")?
+                .parse()?;
+            let data = convert(file()?, options)?;
+            destination
+                .send(&data, TIMEOUT)
+                .map_err(|error| format!("can't print to {}: {}", destination, error))
+        }
+        "convert" => {
+            let data = convert(file()?, options)?;
+            write(output()?, &data)
+        }
+        "decode" => {
+            let data = read(file()?)?;
+            for instruction in decode(&data) {
+                println!("{}", instruction);
+            }
+            Ok(())
+        }
+        "preview" => {
+            let (file, output) = (file()?, output()?);
+            let data = convert(file, options)?;
+            let image = preview(&data, &options.profile);
+            let pixels = image
+                .get_pixels()
+                .into_iter()
+                .flatten()
+                .flat_map(|pixel| [pixel.red(), pixel.green(), pixel.blue(), pixel.alpha()])
+                .collect();
+            let png =
+                image::RgbaImage::from_raw(image.width().into(), image.height().into(), pixels)
+                    .ok_or("the preview is empty")?;
+            png.save(output)
+                .map_err(|error| format!("can't write {}: {}", output, error))
+        }
+        "serve" => {
+            if options.printers.is_empty() {
+                return Err("no printer to serve, use --printer".to_string());
+            }
+
+            let mut server = PrintServer::new();
+            server.set_timeout(TIMEOUT);
+            for (name, destination) in &options.printers {
+                server.add_printer(name, destination.clone(), options.profile);
+            }
+            for origin in &options.origins {
+                server.allow_origin(origin);
+            }
+
+            let listen = options.listen.as_deref().unwrap_or("127.0.0.1:8080");
+            let running = server
+                .start(listen)
+                .map_err(|error| format!("can't listen on {}: {}", listen, error))?;
+            eprintln!("Listening on http://{}", running.address());
+            running.wait();
+            Ok(())
+        }
+        "profiles" => {
+            for profile in PrinterProfile::ALL {
+                println!("{:<16} {}", profile.id, profile.name);
+            }
+            Ok(())
+        }
+        "help" => {
+            println!("{}", USAGE);
+            Ok(())
+        }
+        command => Err(format!("unknown command {:?}, see `help`", command)),
+    }
+}
+
+fn read(path: &str) -> Result<Vec<u8>, String> {
+    fs::read(path).map_err(|error| format!("can't read {}: {}", path, error))
+}
+
+fn write(path: &str, data: &[u8]) -> Result<(), String> {
+    fs::write(path, data).map_err(|error| format!("can't write {}: {}", path, error))
+}
+
+/// Reads `path` as the kind of file its extension says and returns the
+/// ESC/POS data to print it.
+fn convert(path: &str, options: &Options) -> Result<Vec<u8>, String> {
+    let extension = Path::new(path)
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .unwrap_or("")
+        .to_ascii_lowercase();
+    let text = || String::from_utf8(read(path)?).map_err(|_| format!("{} isn't UTF-8", path));
+
+    let mut printer = BasePrinter::new(options.profile);
+    printer.initialize();
+
+    match extension.as_str() {
+        "bin" | "prn" => return read(path),
+        "txt" | "" => {
+            for line in text()?.lines() {
+                printer.text(line).new_line();
+            }
+        }
+        "json" => {
+            let receipt: Receipt = serde_json::from_str(&text()?)
+                .map_err(|error| format!("invalid receipt in {}: {}", path, error))?;
+            receipt
+                .print(&mut printer)
+                .map_err(|error| format!("can't print {}: {}", path, error))?;
+        }
+        "md" | "markdown" => Markdown::parse(&text()?).print(&mut printer),
+        "html" | "htm" => Html::parse(&text()?).print(&mut printer),
+        "png" | "jpg" | "jpeg" | "gif" | "bmp" => {
+            let image =
+                image::open(path).map_err(|error| format!("can't read {}: {}", path, error))?;
+            let width = printer.print_area_width() as u32;
+            let image = if image.width() > width {
+                let height = (image.height() as u64 * width as u64 / image.width() as u64) as u32;
+                image.resize_exact(width, height.max(1), image::imageops::FilterType::Triangle)
+            } else {
+                image
+            };
+            printer.image(Image::from(image));
+        }
+        _ => return Err(format!("unknown kind of file {}", path)),
+    }
+
+    if options.cut {
+        printer.cut(false);
+    }
+
+    Ok(printer.get_data())
+}
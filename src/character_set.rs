@@ -1,6 +1,7 @@
 /// Character sets
-
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
 pub enum CharacterSet {
+    #[default]
     Pc437Usa,
     Pc850Multilingual,
     Pc860Portuguese,
@@ -39,15 +40,9 @@ pub enum CharacterSet {
     HkTw,
 }
 
-impl Default for CharacterSet {
-    fn default() -> Self {
-        CharacterSet::Pc437Usa
-    }
-}
-
-impl Into<&str> for &CharacterSet {
-    fn into(self) -> &'static str {
-        match self {
+impl From<&CharacterSet> for &'static str {
+    fn from(set: &CharacterSet) -> Self {
+        match set {
             CharacterSet::Pc437Usa => "CP437",
             CharacterSet::Pc850Multilingual => "CP850",
             CharacterSet::Pc860Portuguese => "CP860",
@@ -87,3 +82,24 @@ impl Into<&str> for &CharacterSet {
         }
     }
 }
+
+impl CharacterSet {
+    /// Encodes `text` in the character set, with `?` in place of the
+    /// characters it lacks.
+    pub(crate) fn encode(&self, text: &str) -> Vec<u8> {
+        let encoding: &str = self.into();
+        if let Ok(bytes) = iconv::encode(text, encoding) {
+            return bytes;
+        }
+
+        let mut bytes = Vec::with_capacity(text.len());
+        let mut buffer = [0; 4];
+        for c in text.chars() {
+            match iconv::encode(c.encode_utf8(&mut buffer), encoding) {
+                Ok(encoded) => bytes.extend(encoded),
+                Err(_) => bytes.push(b'?'),
+            }
+        }
+        bytes
+    }
+}
@@ -1,7 +1,28 @@
-use crate::{
-    character_set::CharacterSet,
-    constants::{ESC, GS},
-};
+use crate::constants::{ESC, GS};
+
+const EOT: u8 = 0x04;
+const DLE: u8 = 0x10;
+const DC4: u8 = 0x14;
+
+/// Set absolute print position
+///
+/// # Arguments
+///
+/// * `nl` - Position from the start of the line (LSB)
+/// * `nh` - Position from the start of the line (MSB)
+///
+/// # Returns
+///
+/// An array representing the command bytes
+///
+/// # Example
+///
+/// ```ignore
+/// let command = absolute_position(0x40, 0x00);
+/// ```
+pub fn absolute_position(nl: u8, nh: u8) -> [u8; 4] {
+    [ESC, 0x24, nl, nh]
+}
 
 /// Select justification
 ///
@@ -22,6 +43,103 @@ pub fn alignment(n: u8) -> [u8; 3] {
     [ESC, 0x61, n]
 }
 
+/// Aztec Code: Set the error correction level
+///
+/// # Arguments
+///
+/// * `n` - Share of error correction codewords in percent (5 to 95)
+///
+/// # Returns
+///
+/// An array containing the command bytes
+///
+/// # Example
+///
+/// ```ignore
+/// let command = aztec_correction_level(23);
+/// ```
+pub fn aztec_correction_level(n: u8) -> [u8; 8] {
+    [GS, 0x28, 0x6b, 0x03, 0x00, 0x35, 0x32, n]
+}
+
+/// Aztec Code: Select the mode type and the number of data layers
+///
+/// # Arguments
+///
+/// * `n1` - Mode type (0 for full-range, 1 for compact)
+/// * `n2` - Number of data layers (0 for automatic, 1 to 32)
+///
+/// # Returns
+///
+/// An array containing the command bytes
+///
+/// # Example
+///
+/// ```ignore
+/// let command = aztec_mode(0, 0);
+/// ```
+pub fn aztec_mode(n1: u8, n2: u8) -> [u8; 9] {
+    [GS, 0x28, 0x6b, 0x04, 0x00, 0x35, 0x30, n1, n2]
+}
+
+/// Aztec Code: Set the size of module
+///
+/// # Arguments
+///
+/// * `n` - Size of the module in dots (2 to 16)
+///
+/// # Returns
+///
+/// An array containing the command bytes
+///
+/// # Example
+///
+/// ```ignore
+/// let command = aztec_module_size(3);
+/// ```
+pub fn aztec_module_size(n: u8) -> [u8; 8] {
+    [GS, 0x28, 0x6b, 0x03, 0x00, 0x35, 0x31, n]
+}
+
+/// Aztec Code: Print the symbol data in the symbol storage area
+///
+/// # Returns
+///
+/// An array containing the command bytes
+///
+/// # Example
+///
+/// ```ignore
+/// let command = aztec_print();
+/// ```
+pub fn aztec_print() -> [u8; 8] {
+    [GS, 0x28, 0x6b, 0x03, 0x00, 0x35, 0x51, 0x30]
+}
+
+/// Aztec Code: Store the data in the symbol storage area
+///
+/// # Arguments
+///
+/// * `pl` - Length of data (LSB)
+/// * `ph` - Length of data (MSB)
+/// * `data` - Data to be stored
+///
+/// # Returns
+///
+/// A vector containing the command bytes
+///
+/// # Example
+///
+/// ```ignore
+/// let data: Vec<u8> = vec![0x41, 0x42, 0x43]; // Example data
+/// let command = aztec_store(0x06, 0x00, &data);
+/// ```
+pub fn aztec_store(pl: u8, ph: u8, data: &[u8]) -> Vec<u8> {
+    let mut command = vec![GS, 0x28, 0x6b, pl, ph, 0x35, 0x50, 0x30];
+    command.extend_from_slice(data);
+    command
+}
+
 /// Set barcode height
 ///
 /// # Arguments
@@ -121,6 +239,47 @@ pub fn barcode_width(n: u8) -> [u8; 3] {
     [GS, 0x77, n]
 }
 
+/// Sound the buzzer of common Epson clones
+///
+/// # Arguments
+///
+/// * `n` - Number of beeps (1 to 9)
+/// * `t` - Duration of each beep, in units of 50 ms (1 to 9)
+///
+/// # Returns
+///
+/// An array representing the command bytes
+///
+/// # Example
+///
+/// ```ignore
+/// let command = beep(3, 2);
+/// ```
+pub fn beep(n: u8, t: u8) -> [u8; 4] {
+    [ESC, 0x42, n, t]
+}
+
+/// Sound the beeper of Epson printers
+///
+/// # Arguments
+///
+/// * `m` - Sound pattern (49 to 55)
+/// * `n` - Number of repetitions (1 to 63)
+/// * `t` - Duration of each repetition, in units of 100 ms
+///
+/// # Returns
+///
+/// An array representing the command bytes
+///
+/// # Example
+///
+/// ```ignore
+/// let command = beep_pattern(49, 3, 2);
+/// ```
+pub fn beep_pattern(m: u8, n: u8, t: u8) -> [u8; 9] {
+    [ESC, 0x28, 0x41, 0x04, 0x00, 0x30, m, n, t]
+}
+
 /// Generate pulse
 ///
 /// # Arguments
@@ -142,6 +301,26 @@ pub fn cash_draw(m: u8, t1: u8, t2: u8) -> [u8; 5] {
     [ESC, 0x70, m, t1, t2]
 }
 
+/// Generate a real-time pulse on a drawer kick-out connector pin
+///
+/// # Arguments
+///
+/// * `m` - Connector pin (0 for pin 2, 1 for pin 5)
+/// * `t` - Pulse on time and off time, in units of 100 ms (1 to 8)
+///
+/// # Returns
+///
+/// An array representing the command bytes
+///
+/// # Example
+///
+/// ```ignore
+/// let command = cash_draw_realtime(0, 2);
+/// ```
+pub fn cash_draw_realtime(m: u8, t: u8) -> [u8; 5] {
+    [DLE, DC4, 0x01, m, t]
+}
+
 /// Select character code table
 ///
 /// # Arguments
@@ -165,8 +344,10 @@ pub fn character_code_table(n: u8) -> [u8; 3] {
 ///
 /// # Arguments
 ///
-/// * `m` - Cut mode
-/// * `n` - Cut type (optional, only for Function B, C, D)
+/// * `m` - Cut mode (48/49 for function A, 65/66 for function B, 97/98 for
+///   function C, 103/104 for function D)
+/// * `n` - Feed past the cutter position in motion units (functions B, C
+///   and D only)
 ///
 /// # Returns
 ///
@@ -175,8 +356,8 @@ pub fn character_code_table(n: u8) -> [u8; 3] {
 /// # Example
 ///
 /// ```ignore
-/// let command_a = cut(0);
-/// let command_b = cut(0, 1);
+/// let command_a = cut(48, None);
+/// let command_b = cut(65, Some(0));
 /// ```
 pub fn cut(m: u8, n: Option<u8>) -> Vec<u8> {
     let mut cmd = vec![GS, 0x56, m];
@@ -186,6 +367,85 @@ pub fn cut(m: u8, n: Option<u8>) -> Vec<u8> {
     cmd
 }
 
+/// DataMatrix: Set the size of module
+///
+/// # Arguments
+///
+/// * `n` - Size of the module in dots (2 to 16)
+///
+/// # Returns
+///
+/// An array containing the command bytes
+///
+/// # Example
+///
+/// ```ignore
+/// let command = data_matrix_module_size(3);
+/// ```
+pub fn data_matrix_module_size(n: u8) -> [u8; 8] {
+    [GS, 0x28, 0x6b, 0x03, 0x00, 0x36, 0x43, n]
+}
+
+/// DataMatrix: Print the symbol data in the symbol storage area
+///
+/// # Returns
+///
+/// An array containing the command bytes
+///
+/// # Example
+///
+/// ```ignore
+/// let command = data_matrix_print();
+/// ```
+pub fn data_matrix_print() -> [u8; 8] {
+    [GS, 0x28, 0x6b, 0x03, 0x00, 0x36, 0x51, 0x30]
+}
+
+/// DataMatrix: Store the data in the symbol storage area
+///
+/// # Arguments
+///
+/// * `pl` - Length of data (LSB)
+/// * `ph` - Length of data (MSB)
+/// * `data` - Data to be stored
+///
+/// # Returns
+///
+/// A vector containing the command bytes
+///
+/// # Example
+///
+/// ```ignore
+/// let data: Vec<u8> = vec![0x41, 0x42, 0x43]; // Example data
+/// let command = data_matrix_store(0x06, 0x00, &data);
+/// ```
+pub fn data_matrix_store(pl: u8, ph: u8, data: &[u8]) -> Vec<u8> {
+    let mut command = vec![GS, 0x28, 0x6b, pl, ph, 0x36, 0x50, 0x30];
+    command.extend_from_slice(data);
+    command
+}
+
+/// DataMatrix: Select the symbol type and the number of columns and rows
+///
+/// # Arguments
+///
+/// * `m` - Symbol type (0 for square, 1 for rectangle)
+/// * `d1` - Number of columns (0 for automatic)
+/// * `d2` - Number of rows (0 for automatic)
+///
+/// # Returns
+///
+/// An array containing the command bytes
+///
+/// # Example
+///
+/// ```ignore
+/// let command = data_matrix_type(0, 0, 0);
+/// ```
+pub fn data_matrix_type(m: u8, d1: u8, d2: u8) -> [u8; 10] {
+    [GS, 0x28, 0x6b, 0x05, 0x00, 0x36, 0x42, m, d1, d2]
+}
+
 /// Print raster bit image
 ///
 /// # Arguments
@@ -266,6 +526,258 @@ pub fn invert(n: u8) -> [u8; 3] {
     [GS, 0x42, n]
 }
 
+/// Set left margin
+///
+/// # Arguments
+///
+/// * `nl` - Left margin (LSB)
+/// * `nh` - Left margin (MSB)
+///
+/// # Returns
+///
+/// An array representing the command bytes
+///
+/// # Example
+///
+/// ```ignore
+/// let command = left_margin(0x20, 0x00);
+/// ```
+pub fn left_margin(nl: u8, nh: u8) -> [u8; 4] {
+    [GS, 0x4c, nl, nh]
+}
+
+/// MaxiCode: Select the mode
+///
+/// # Arguments
+///
+/// * `n` - Mode (50 to 54 for modes 2 to 6)
+///
+/// # Returns
+///
+/// An array containing the command bytes
+///
+/// # Example
+///
+/// ```ignore
+/// let command = maxi_code_mode(50);
+/// ```
+pub fn maxi_code_mode(n: u8) -> [u8; 8] {
+    [GS, 0x28, 0x6b, 0x03, 0x00, 0x32, 0x41, n]
+}
+
+/// MaxiCode: Print the symbol data in the symbol storage area
+///
+/// # Returns
+///
+/// An array containing the command bytes
+///
+/// # Example
+///
+/// ```ignore
+/// let command = maxi_code_print();
+/// ```
+pub fn maxi_code_print() -> [u8; 8] {
+    [GS, 0x28, 0x6b, 0x03, 0x00, 0x32, 0x51, 0x30]
+}
+
+/// MaxiCode: Store the data in the symbol storage area
+///
+/// # Arguments
+///
+/// * `pl` - Length of data (LSB)
+/// * `ph` - Length of data (MSB)
+/// * `data` - Data to be stored
+///
+/// # Returns
+///
+/// A vector containing the command bytes
+///
+/// # Example
+///
+/// ```ignore
+/// let data: Vec<u8> = vec![0x41, 0x42, 0x43]; // Example data
+/// let command = maxi_code_store(0x06, 0x00, &data);
+/// ```
+pub fn maxi_code_store(pl: u8, ph: u8, data: &[u8]) -> Vec<u8> {
+    let mut command = vec![GS, 0x28, 0x6b, pl, ph, 0x32, 0x50, 0x30];
+    command.extend_from_slice(data);
+    command
+}
+
+/// PDF417: Set the number of columns in the data region
+///
+/// # Arguments
+///
+/// * `n` - Number of columns (0 for automatic, 1 to 30)
+///
+/// # Returns
+///
+/// An array containing the command bytes
+///
+/// # Example
+///
+/// ```ignore
+/// let command = pdf417_columns(0);
+/// ```
+pub fn pdf417_columns(n: u8) -> [u8; 8] {
+    [GS, 0x28, 0x6b, 0x03, 0x00, 0x30, 0x41, n]
+}
+
+/// PDF417: Set the error correction level
+///
+/// # Arguments
+///
+/// * `m` - 48 to select a level, 49 to select a ratio
+/// * `n` - Level (48 to 56) or ratio in tens of percent (1 to 40)
+///
+/// # Returns
+///
+/// An array containing the command bytes
+///
+/// # Example
+///
+/// ```ignore
+/// let command = pdf417_correction_level(49, 1);
+/// ```
+pub fn pdf417_correction_level(m: u8, n: u8) -> [u8; 9] {
+    [GS, 0x28, 0x6b, 0x04, 0x00, 0x30, 0x45, m, n]
+}
+
+/// PDF417: Set the width of the module
+///
+/// # Arguments
+///
+/// * `n` - Width of the module in dots (2 to 8)
+///
+/// # Returns
+///
+/// An array containing the command bytes
+///
+/// # Example
+///
+/// ```ignore
+/// let command = pdf417_module_width(3);
+/// ```
+pub fn pdf417_module_width(n: u8) -> [u8; 8] {
+    [GS, 0x28, 0x6b, 0x03, 0x00, 0x30, 0x43, n]
+}
+
+/// PDF417: Select the options
+///
+/// # Arguments
+///
+/// * `m` - 0 for standard PDF417, 1 for truncated PDF417
+///
+/// # Returns
+///
+/// An array containing the command bytes
+///
+/// # Example
+///
+/// ```ignore
+/// let command = pdf417_options(0);
+/// ```
+pub fn pdf417_options(m: u8) -> [u8; 8] {
+    [GS, 0x28, 0x6b, 0x03, 0x00, 0x30, 0x46, m]
+}
+
+/// PDF417: Print the symbol data in the symbol storage area
+///
+/// # Returns
+///
+/// An array containing the command bytes
+///
+/// # Example
+///
+/// ```ignore
+/// let command = pdf417_print();
+/// ```
+pub fn pdf417_print() -> [u8; 8] {
+    [GS, 0x28, 0x6b, 0x03, 0x00, 0x30, 0x51, 0x30]
+}
+
+/// PDF417: Set the row height
+///
+/// # Arguments
+///
+/// * `n` - Row height as a multiple of the module width (2 to 8)
+///
+/// # Returns
+///
+/// An array containing the command bytes
+///
+/// # Example
+///
+/// ```ignore
+/// let command = pdf417_row_height(3);
+/// ```
+pub fn pdf417_row_height(n: u8) -> [u8; 8] {
+    [GS, 0x28, 0x6b, 0x03, 0x00, 0x30, 0x44, n]
+}
+
+/// PDF417: Set the number of rows
+///
+/// # Arguments
+///
+/// * `n` - Number of rows (0 for automatic, 3 to 90)
+///
+/// # Returns
+///
+/// An array containing the command bytes
+///
+/// # Example
+///
+/// ```ignore
+/// let command = pdf417_rows(0);
+/// ```
+pub fn pdf417_rows(n: u8) -> [u8; 8] {
+    [GS, 0x28, 0x6b, 0x03, 0x00, 0x30, 0x42, n]
+}
+
+/// PDF417: Store the data in the symbol storage area
+///
+/// # Arguments
+///
+/// * `pl` - Length of data (LSB)
+/// * `ph` - Length of data (MSB)
+/// * `data` - Data to be stored
+///
+/// # Returns
+///
+/// A vector containing the command bytes
+///
+/// # Example
+///
+/// ```ignore
+/// let data: Vec<u8> = vec![0x41, 0x42, 0x43]; // Example data
+/// let command = pdf417_store(0x06, 0x00, &data);
+/// ```
+pub fn pdf417_store(pl: u8, ph: u8, data: &[u8]) -> Vec<u8> {
+    let mut command = vec![GS, 0x28, 0x6b, pl, ph, 0x30, 0x50, 0x30];
+    command.extend_from_slice(data);
+    command
+}
+
+/// Set print area width
+///
+/// # Arguments
+///
+/// * `nl` - Print area width (LSB)
+/// * `nh` - Print area width (MSB)
+///
+/// # Returns
+///
+/// An array representing the command bytes
+///
+/// # Example
+///
+/// ```ignore
+/// let command = print_area_width(0x40, 0x02);
+/// ```
+pub fn print_area_width(nl: u8, nh: u8) -> [u8; 4] {
+    [GS, 0x57, nl, nh]
+}
+
 /// QR Code: Set the size of module
 ///
 /// # Arguments
@@ -363,6 +875,26 @@ pub fn qr_code_store(pl: u8, ph: u8, data: &[u8]) -> Vec<u8> {
     command
 }
 
+/// Set relative print position
+///
+/// # Arguments
+///
+/// * `nl` - Offset from the current position (LSB)
+/// * `nh` - Offset from the current position (MSB)
+///
+/// # Returns
+///
+/// An array representing the command bytes
+///
+/// # Example
+///
+/// ```ignore
+/// let command = relative_position(0x10, 0x00);
+/// ```
+pub fn relative_position(nl: u8, nh: u8) -> [u8; 4] {
+    [ESC, 0x5c, nl, nh]
+}
+
 /// Turn emphasized mode on/off
 ///
 /// # Arguments
@@ -382,6 +914,44 @@ pub fn text_bold(n: u8) -> [u8; 3] {
     [ESC, 0x45, n]
 }
 
+/// Select print color
+///
+/// # Arguments
+///
+/// * `n` - Color (0: black, 1: red)
+///
+/// # Returns
+///
+/// An array representing the command bytes
+///
+/// # Example
+///
+/// ```ignore
+/// let command = text_color(1);
+/// ```
+pub fn text_color(n: u8) -> [u8; 3] {
+    [ESC, 0x72, n]
+}
+
+/// Turn double-strike mode on/off
+///
+/// # Arguments
+///
+/// * `n` - Mode (0: off, 1: on)
+///
+/// # Returns
+///
+/// An array representing the command bytes
+///
+/// # Example
+///
+/// ```ignore
+/// let command = text_double_strike(1);
+/// ```
+pub fn text_double_strike(n: u8) -> [u8; 3] {
+    [ESC, 0x47, n]
+}
+
 /// Select character font
 ///
 /// # Arguments
@@ -420,6 +990,25 @@ pub fn text_mode(n: u8) -> [u8; 3] {
     [ESC, 0x21, n]
 }
 
+/// Turn 90° clockwise rotation mode on/off
+///
+/// # Arguments
+///
+/// * `n` - Mode (0: off, 1: on with 1-dot spacing, 2: on with 1.5-dot spacing)
+///
+/// # Returns
+///
+/// An array representing the command bytes
+///
+/// # Example
+///
+/// ```ignore
+/// let command = text_rotation(1);
+/// ```
+pub fn text_rotation(n: u8) -> [u8; 3] {
+    [ESC, 0x56, n]
+}
+
 /// Select character size
 ///
 /// # Arguments
@@ -439,6 +1028,44 @@ pub fn text_size(n: u8) -> [u8; 3] {
     [GS, 0x21, n]
 }
 
+/// Turn smoothing mode on/off
+///
+/// # Arguments
+///
+/// * `n` - Mode (0: off, 1: on)
+///
+/// # Returns
+///
+/// An array representing the command bytes
+///
+/// # Example
+///
+/// ```ignore
+/// let command = text_smoothing(1);
+/// ```
+pub fn text_smoothing(n: u8) -> [u8; 3] {
+    [GS, 0x62, n]
+}
+
+/// Set right-side character spacing
+///
+/// # Arguments
+///
+/// * `n` - Spacing in horizontal motion units
+///
+/// # Returns
+///
+/// An array representing the command bytes
+///
+/// # Example
+///
+/// ```ignore
+/// let command = text_spacing(2);
+/// ```
+pub fn text_spacing(n: u8) -> [u8; 3] {
+    [ESC, 0x20, n]
+}
+
 /// Turn underline mode on/off
 ///
 /// # Arguments
@@ -458,58 +1085,41 @@ pub fn text_underline(n: u8) -> [u8; 3] {
     [ESC, 0x2d, n]
 }
 
-/// Select character set
+/// Turn upside-down print mode on/off
 ///
 /// # Arguments
 ///
-/// * `set` - Character set to select
+/// * `n` - Mode (0: off, 1: on)
 ///
 /// # Returns
 ///
-/// A slice containing the command bytes
+/// An array representing the command bytes
 ///
 /// # Example
 ///
 /// ```ignore
-/// let command = character_set(CharacterSet::Japan);
+/// let command = text_upside_down(1);
 /// ```
-pub fn character_set(set: &CharacterSet) -> [u8; 3] {
-    match set {
-        CharacterSet::Pc437Usa => character_code_table(0),
-        CharacterSet::Pc850Multilingual => character_code_table(2),
-        CharacterSet::Pc860Portuguese => character_code_table(3),
-        CharacterSet::Pc863CanadianFrench => character_code_table(4),
-        CharacterSet::Pc865Nordic => character_code_table(5),
-        CharacterSet::Pc851Greek => character_code_table(11),
-        CharacterSet::Pc857Turkish => character_code_table(12),
-        CharacterSet::Pc737Greek => character_code_table(14),
-        CharacterSet::Iso8859_7Greek => character_code_table(15),
-        CharacterSet::Wpc1252 => character_code_table(16),
-        CharacterSet::Pc866Cyrillic2 => character_code_table(17),
-        CharacterSet::Pc852Latin2 | CharacterSet::Slovenia => character_code_table(18),
-        CharacterSet::Pc858Euro => character_code_table(19),
-        CharacterSet::Wpc775BalticRim => character_code_table(33),
-        CharacterSet::Pc855Cyrillic => character_code_table(34),
-        CharacterSet::Pc861Icelandic => character_code_table(35),
-        CharacterSet::Pc862Hebrew => character_code_table(36),
-        CharacterSet::Pc864Arabic => character_code_table(37),
-        CharacterSet::Pc869Greek => character_code_table(38),
-        CharacterSet::Iso8859_2Latin2 => character_code_table(39),
-        CharacterSet::Iso8859_15Latin9 => character_code_table(40),
-        CharacterSet::Pc1125Ukranian => character_code_table(44),
-        CharacterSet::Wpc1250Latin2 => character_code_table(45),
-        CharacterSet::Wpc1251Cyrillic => character_code_table(46),
-        CharacterSet::Wpc1253Greek => character_code_table(47),
-        CharacterSet::Wpc1254Turkish => character_code_table(48),
-        CharacterSet::Wpc1255Hebrew => character_code_table(49),
-        CharacterSet::Wpc1256Arabic => character_code_table(50),
-        CharacterSet::Wpc1257BalticRim => character_code_table(51),
-        CharacterSet::Wpc1258Vietnamese => character_code_table(52),
-        CharacterSet::Kz1048Kazakhstan => character_code_table(53),
+pub fn text_upside_down(n: u8) -> [u8; 3] {
+    [ESC, 0x7b, n]
+}
 
-        CharacterSet::Japan => international_character_set(0x08),
-        CharacterSet::Korea => international_character_set(0x0d),
-        CharacterSet::China => international_character_set(0x0f),
-        CharacterSet::HkTw => international_character_set(0x00),
-    }
+/// Transmit status in real time
+///
+/// # Arguments
+///
+/// * `n` - Status to transmit (1 for the printer status, which includes
+///   the drawer kick-out connector)
+///
+/// # Returns
+///
+/// An array representing the command bytes
+///
+/// # Example
+///
+/// ```ignore
+/// let command = transmit_status(1);
+/// ```
+pub fn transmit_status(n: u8) -> [u8; 3] {
+    [DLE, EOT, n]
 }
@@ -0,0 +1,324 @@
+//! Reading the CUPS raster format, the pages that the CUPS filters before a
+//! printer driver render, for the `rastertoescpos` filter.
+//!
+//! Version 1, 2 and 3 streams of either byte order are read, including PWG
+//! raster, in gray, black or RGB, chunked colors only.
+
+use std::{
+    io::{self, Read},
+    ops::Range,
+};
+
+use crate::{pixel::Pixel, Image};
+
+/// Size of `cups_page_header2_t`.
+const HEADER_SIZE: usize = 1796;
+
+const HW_RESOLUTION: usize = 276;
+const CUT_MEDIA: usize = 268;
+const WIDTH: usize = 372;
+const HEIGHT: usize = 376;
+const BITS_PER_COLOR: usize = 384;
+const BITS_PER_PIXEL: usize = 388;
+const BYTES_PER_LINE: usize = 392;
+const COLOR_ORDER: usize = 396;
+const COLOR_SPACE: usize = 400;
+
+/// Color spaces of `cupsColorSpace`.
+const W: u32 = 0;
+const RGB: u32 = 1;
+const K: u32 = 3;
+const SW: u32 = 18;
+const SRGB: u32 = 19;
+const ADOBE_RGB: u32 = 20;
+
+/// A stream of CUPS raster pages.
+///
+/// # Example
+///
+/// ```ignore
+/// let mut raster = CupsRaster::new(std::io::stdin().lock())?;
+/// while let Some(page) = raster.next_page()? {
+///     printer.image(page.to_image(printer.print_area_width()).dither());
+/// }
+/// ```
+pub struct CupsRaster<R> {
+    reader: R,
+    big_endian: bool,
+    compressed: bool,
+}
+
+/// A page, in shades of gray.
+#[derive(Clone, Debug)]
+pub struct RasterPage {
+    /// Width and height in dots.
+    pub width: u32,
+    pub height: u32,
+    /// Horizontal and vertical resolution in dots per inch.
+    pub resolution: (u32, u32),
+    /// When to cut the paper, as the `cupsCutMedia` of the PPD: 0 never, 1
+    /// after the document, 2 after the job, 3 after the copies and 4 after
+    /// each page.
+    pub cut_media: u32,
+    /// One byte per dot, from 0 for black to 255 for white.
+    luminance: Vec<u8>,
+}
+
+/// The format of the dots of a page.
+struct Format {
+    big_endian: bool,
+    color_space: u32,
+    bits_per_color: usize,
+    bytes_per_line: usize,
+    /// Bytes repeated together by the compression.
+    unit: usize,
+}
+
+impl<R: Read> CupsRaster<R> {
+    /// Reads the synchronization word at the start of a stream.
+    pub fn new(mut reader: R) -> io::Result<Self> {
+        let mut sync = [0; 4];
+        reader.read_exact(&mut sync)?;
+
+        let (big_endian, compressed) = match &sync {
+            b"RaSt" | b"RaS3" => (true, false),
+            b"tSaR" | b"3SaR" => (false, false),
+            b"RaS2" => (true, true),
+            b"2SaR" => (false, true),
+            _ => return Err(invalid("not a CUPS raster stream")),
+        };
+
+        Ok(Self {
+            reader,
+            big_endian,
+            compressed,
+        })
+    }
+
+    /// Reads the next page, or returns `None` at the end of the stream.
+    pub fn next_page(&mut self) -> io::Result<Option<RasterPage>> {
+        let mut header = vec![0; HEADER_SIZE];
+        let read = read_full(&mut self.reader, &mut header)?;
+        if read == 0 {
+            return Ok(None);
+        }
+        if read < HEADER_SIZE {
+            return Err(invalid("truncated page header"));
+        }
+
+        let big_endian = self.big_endian;
+        let field = |offset: usize| {
+            let bytes = [
+                header[offset],
+                header[offset + 1],
+                header[offset + 2],
+                header[offset + 3],
+            ];
+            if big_endian {
+                u32::from_be_bytes(bytes)
+            } else {
+                u32::from_le_bytes(bytes)
+            }
+        };
+
+        let (width, height) = (field(WIDTH), field(HEIGHT));
+        let bits_per_color = field(BITS_PER_COLOR) as usize;
+        let bits_per_pixel = field(BITS_PER_PIXEL) as usize;
+        let color_space = field(COLOR_SPACE);
+        let colors = match color_space {
+            W | K | SW => 1,
+            RGB | SRGB | ADOBE_RGB => 3,
+            _ => return Err(invalid(&format!("unsupported color space {}", color_space))),
+        };
+        if colors > 1 && field(COLOR_ORDER) != 0 {
+            return Err(invalid("only chunked colors are supported"));
+        }
+        if !matches!(
+            (colors, bits_per_color),
+            (1, 1 | 2 | 4 | 8 | 16) | (3, 8 | 16)
+        ) || bits_per_pixel != colors * bits_per_color
+        {
+            return Err(invalid(&format!(
+                "unsupported {} bits per color",
+                bits_per_color
+            )));
+        }
+
+        if width > u16::MAX as u32 || height > u16::MAX as u32 {
+            return Err(invalid(&format!(
+                "page of {}x{} dots is too large",
+                width, height
+            )));
+        }
+
+        let format = Format {
+            big_endian,
+            color_space,
+            bits_per_color,
+            bytes_per_line: field(BYTES_PER_LINE) as usize,
+            unit: bits_per_pixel.div_ceil(8),
+        };
+        if format.bytes_per_line != (width as usize * bits_per_pixel).div_ceil(8) {
+            return Err(invalid("the bytes per line don't match the page width"));
+        }
+
+        let mut luminance = vec![];
+        let mut line = vec![0; format.bytes_per_line];
+        let mut row = 0;
+        while row < height {
+            let repeat = if self.compressed {
+                let mut count = [0];
+                self.reader.read_exact(&mut count)?;
+                self.read_compressed_line(&format, &mut line)?;
+                count[0] as u32 + 1
+            } else {
+                self.reader.read_exact(&mut line)?;
+                1
+            };
+
+            for _ in 0..repeat.min(height - row) {
+                luminance.extend((0..width as usize).map(|x| format.luminance(&line, x)));
+                row += 1;
+            }
+        }
+
+        Ok(Some(RasterPage {
+            width,
+            height,
+            resolution: (field(HW_RESOLUTION), field(HW_RESOLUTION + 4)),
+            cut_media: field(CUT_MEDIA),
+            luminance,
+        }))
+    }
+
+    /// Reads a line compressed with PackBits on units of a pixel.
+    fn read_compressed_line(&mut self, format: &Format, line: &mut [u8]) -> io::Result<()> {
+        let unit = format.unit;
+        let mut filled = 0;
+
+        while filled < line.len() {
+            let mut count = [0];
+            self.reader.read_exact(&mut count)?;
+
+            match count[0] {
+                // The rest of the line is blank.
+                128 => {
+                    let blank = match format.color_space {
+                        K => 0x00,
+                        _ => 0xff,
+                    };
+                    line[filled..].fill(blank);
+                    filled = line.len();
+                }
+                count @ 0..=127 => {
+                    let length = ((count as usize + 1) * unit).min(line.len() - filled);
+                    let mut pixel = vec![0; unit];
+                    self.reader.read_exact(&mut pixel)?;
+                    for (i, byte) in line[filled..filled + length].iter_mut().enumerate() {
+                        *byte = pixel[i % unit];
+                    }
+                    filled += length;
+                }
+                count => {
+                    let length = (257 - count as usize) * unit;
+                    if length > line.len() - filled {
+                        return Err(invalid("compressed line overflows"));
+                    }
+                    self.reader.read_exact(&mut line[filled..filled + length])?;
+                    filled += length;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Format {
+    /// Luminance of the dot `x` of `line`, from 0 for black to 255 for white.
+    fn luminance(&self, line: &[u8], x: usize) -> u8 {
+        let sample = |index: usize| -> u8 {
+            match self.bits_per_color {
+                // The most significant byte, in the byte order of the stream.
+                16 if self.big_endian => line[index * 2],
+                16 => line[index * 2 + 1],
+                8 => line[index],
+                bits => {
+                    let per_byte = 8 / bits;
+                    let shift = 8 - bits * (index % per_byte + 1);
+                    let value = line[index / per_byte] >> shift & ((1 << bits) - 1);
+                    (value as u32 * 255 / ((1 << bits) - 1)) as u8
+                }
+            }
+        };
+
+        match self.color_space {
+            K => 255 - sample(x),
+            RGB | SRGB | ADOBE_RGB => {
+                let (r, g, b) = (sample(x * 3), sample(x * 3 + 1), sample(x * 3 + 2));
+                ((r as u32 * 299 + g as u32 * 587 + b as u32 * 114) / 1000) as u8
+            }
+            _ => sample(x),
+        }
+    }
+}
+
+impl RasterPage {
+    /// The page as an image at most `max_width` dots wide, scaled down by
+    /// averaging the dots if the page is wider.
+    pub fn to_image(&self, max_width: u16) -> Image {
+        let (width, height) = (self.width as usize, self.height as usize);
+        let (scaled_width, scaled_height) = if width > max_width as usize {
+            let scaled_width = (max_width as usize).max(1);
+            (scaled_width, (height * scaled_width / width).max(1))
+        } else {
+            (width, height)
+        };
+
+        let mut pixels = Vec::with_capacity(scaled_width * scaled_height);
+        for y in 0..scaled_height {
+            let rows = span(y, height, scaled_height);
+            for x in 0..scaled_width {
+                let columns = span(x, width, scaled_width);
+                let mut sum = 0;
+                for row in rows.clone() {
+                    sum += self.luminance[row * width + columns.start..row * width + columns.end]
+                        .iter()
+                        .map(|&level| level as usize)
+                        .sum::<usize>();
+                }
+                let level = (sum / (rows.len() * columns.len())) as u8;
+                pixels.push(Pixel::new(level, level, level, 255));
+            }
+        }
+
+        Image::new(pixels, scaled_width as u16, scaled_height as u16)
+    }
+}
+
+/// The dots of a length of `from` dots averaged into the dot `i` of a
+/// length of `to` dots.
+fn span(i: usize, from: usize, to: usize) -> Range<usize> {
+    let start = i * from / to;
+    start..((i + 1) * from / to).max(start + 1)
+}
+
+/// Reads until `buffer` is full or the stream ends, returning the bytes
+/// read.
+fn read_full(reader: &mut impl Read, buffer: &mut [u8]) -> io::Result<usize> {
+    let mut read = 0;
+    while read < buffer.len() {
+        match reader.read(&mut buffer[read..]) {
+            Ok(0) => break,
+            Ok(n) => read += n,
+            Err(error) if error.kind() == io::ErrorKind::Interrupted => {}
+            Err(error) => return Err(error),
+        }
+    }
+
+    Ok(read)
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.to_string())
+}
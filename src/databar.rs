@@ -0,0 +1,603 @@
+//! Encoders of the GS1 DataBar symbologies, which build each symbol
+//! character from a value using the combinatorial method of ISO/IEC 24724.
+
+use crate::barcode::FNC1;
+
+/// Number of combinations of `r` elements out of `n`.
+fn combins(n: i64, r: i64) -> i64 {
+    let (max_denom, min_denom) = if n - r > r { (n - r, r) } else { (r, n - r) };
+
+    let mut value = 1;
+    let mut j = 1;
+    let mut i = n;
+    while i > max_denom {
+        value *= i;
+        if j <= min_denom {
+            value /= j;
+            j += 1;
+        }
+        i -= 1;
+    }
+    while j <= min_denom {
+        value /= j;
+        j += 1;
+    }
+
+    value
+}
+
+/// Returns the widths of `elements` elements spanning `modules` modules,
+/// none wider than `max_width`, that stand for `value`. With `no_narrow`,
+/// at least one element is a single module wide.
+fn widths(value: u32, modules: u32, elements: usize, max_width: u32, no_narrow: bool) -> Vec<u8> {
+    let mut value = value as i64;
+    let mut n = modules as i64;
+    let elements = elements as i64;
+    let max_width = max_width as i64;
+
+    let mut widths = Vec::with_capacity(elements as usize);
+    let mut narrow_mask = 0u32;
+    for bar in 0..elements - 1 {
+        let mut width = 1;
+        narrow_mask |= 1 << bar;
+        loop {
+            let mut sub_value = combins(n - width - 1, elements - bar - 2);
+            if no_narrow
+                && narrow_mask == 0
+                && n - width - (elements - bar - 1) >= elements - bar - 1
+            {
+                sub_value -= combins(n - width - (elements - bar), elements - bar - 2);
+            }
+            if elements - bar - 1 > 1 {
+                let mut less_value = 0;
+                let mut widest = n - width - (elements - bar - 2);
+                while widest > max_width {
+                    less_value += combins(n - width - widest - 1, elements - bar - 3);
+                    widest -= 1;
+                }
+                sub_value -= less_value * (elements - 1 - bar);
+            } else if n - width > max_width {
+                sub_value -= 1;
+            }
+
+            if value < sub_value {
+                break;
+            }
+            value -= sub_value;
+            width += 1;
+            narrow_mask &= !(1 << bar);
+        }
+        widths.push(width as u8);
+        n -= width;
+    }
+    widths.push(n as u8);
+
+    widths
+}
+
+/// Interleaves the odd and even elements of a character, the first element
+/// being odd.
+fn interleave(odd: &[u8], even: &[u8]) -> Vec<u8> {
+    odd.iter()
+        .zip(even)
+        .flat_map(|(&odd, &even)| [odd, even])
+        .collect()
+}
+
+/// Parameters of a group of character values.
+struct Group {
+    /// The first value of the group.
+    start: u32,
+    /// Modules of the odd elements.
+    odd_modules: u32,
+    /// Widest odd element, the widest even element being `9 - odd_widest`.
+    odd_widest: u32,
+    /// Number of even element combinations, or odd ones when the odd
+    /// elements hold the low part of the value.
+    combinations: u32,
+}
+
+const fn group(start: u32, odd_modules: u32, odd_widest: u32, combinations: u32) -> Group {
+    Group {
+        start,
+        odd_modules,
+        odd_widest,
+        combinations,
+    }
+}
+
+/// Encodes `value` with the group it belongs to, as the odd and even element
+/// widths. `odd_low` tells whether the odd elements hold the remainder of
+/// the value rather than the quotient, and `odd_no_narrow` which elements
+/// must have a narrow one.
+fn character(
+    value: u32,
+    groups: &[Group],
+    modules: u32,
+    elements: usize,
+    odd_low: bool,
+    odd_no_narrow: bool,
+) -> (Vec<u8>, Vec<u8>) {
+    let group = groups
+        .iter()
+        .rev()
+        .find(|group| value >= group.start)
+        .unwrap_or(&groups[0]);
+
+    let value = value - group.start;
+    let (odd_value, even_value) = if odd_low {
+        (value % group.combinations, value / group.combinations)
+    } else {
+        (value / group.combinations, value % group.combinations)
+    };
+
+    let odd = widths(
+        odd_value,
+        group.odd_modules,
+        elements,
+        group.odd_widest,
+        odd_no_narrow,
+    );
+    let even = widths(
+        even_value,
+        modules - group.odd_modules,
+        elements,
+        9 - group.odd_widest,
+        !odd_no_narrow,
+    );
+
+    (odd, even)
+}
+
+/// Weighted sum of the element widths, the weight of each element being
+/// `base` times the one of the previous element.
+fn weighted_sum(widths: &[u8], base: u64) -> u64 {
+    widths
+        .iter()
+        .rev()
+        .fold(0, |sum, &width| sum * base + width as u64)
+}
+
+/// Appends widths in reverse order.
+fn push_reversed(bars: &mut Vec<u8>, widths: &[u8]) {
+    bars.extend(widths.iter().rev());
+}
+
+/// Appends the right guard, a narrow space and a narrow bar, so that the
+/// symbol ends with a bar.
+fn push_guard(bars: &mut Vec<u8>) {
+    if bars.len() % 2 == 1 {
+        bars.push(1);
+    }
+    bars.push(1);
+}
+
+/// Groups of the outside characters of DataBar Omnidirectional, 16 modules
+/// wide.
+const OMNI_OUTSIDE: [Group; 5] = [
+    group(0, 12, 8, 1),
+    group(161, 10, 6, 10),
+    group(961, 8, 4, 34),
+    group(2015, 6, 3, 70),
+    group(2715, 4, 1, 126),
+];
+
+/// Groups of the inside characters of DataBar Omnidirectional, 15 modules
+/// wide.
+const OMNI_INSIDE: [Group; 4] = [
+    group(0, 5, 2, 4),
+    group(336, 7, 4, 20),
+    group(1036, 9, 6, 48),
+    group(1516, 11, 8, 81),
+];
+
+/// Finder patterns of DataBar Omnidirectional, without their last element
+/// which is a single module.
+const OMNI_FINDERS: [[u8; 4]; 9] = [
+    [3, 8, 2, 1],
+    [3, 5, 5, 1],
+    [3, 3, 7, 1],
+    [3, 1, 9, 1],
+    [2, 7, 4, 1],
+    [2, 5, 6, 1],
+    [2, 3, 8, 1],
+    [1, 5, 7, 1],
+    [1, 3, 9, 1],
+];
+
+/// Returns the element widths of a DataBar Omnidirectional symbol, starting
+/// with a bar, for a GTIN of 13 digits without its check digit.
+///
+/// DataBar Truncated has the same elements and differs by its height only.
+pub(crate) fn omnidirectional(gtin: &[u8]) -> Vec<u8> {
+    let value = gtin.iter().fold(0u64, |value, &d| value * 10 + d as u64);
+    let (left, right) = ((value / 4_537_077) as u32, (value % 4_537_077) as u32);
+
+    // The elements of each character are listed starting from the side away
+    // from its finder pattern.
+    let characters = [
+        (left / 1597, true),
+        (left % 1597, false),
+        (right / 1597, true),
+        (right % 1597, false),
+    ]
+    .map(|(value, outside)| {
+        let (odd, even) = if outside {
+            character(value, &OMNI_OUTSIDE, 16, 4, false, false)
+        } else {
+            character(value, &OMNI_INSIDE, 15, 4, true, true)
+        };
+        let checksum = weighted_sum(&odd, 9) + 3 * weighted_sum(&even, 9);
+        (interleave(&odd, &even), checksum)
+    });
+
+    let left_checksum = characters[0].1 + 4 * characters[1].1;
+    let right_checksum = characters[2].1 + 4 * characters[3].1;
+    let mut check = ((left_checksum + 16 * right_checksum) % 79) as usize;
+    // The combinations 0-8 and 8-0 of the finder patterns aren't used.
+    if check >= 8 {
+        check += 1;
+    }
+    if check >= 72 {
+        check += 1;
+    }
+    let left_finder = OMNI_FINDERS[check / 9];
+    let right_finder = OMNI_FINDERS[check % 9];
+
+    let mut bars = vec![1];
+    bars.extend(&characters[0].0);
+    bars.extend(left_finder);
+    bars.push(1);
+    push_reversed(&mut bars, &characters[1].0);
+    bars.extend(&characters[3].0);
+    bars.push(1);
+    push_reversed(&mut bars, &right_finder);
+    push_reversed(&mut bars, &characters[2].0);
+    push_guard(&mut bars);
+
+    bars
+}
+
+/// Groups of the characters of DataBar Limited, 26 modules wide.
+const LIMITED: [Group; 7] = [
+    group(0, 17, 6, 28),
+    group(183_064, 13, 5, 728),
+    group(820_064, 9, 3, 6454),
+    group(1_000_776, 15, 5, 203),
+    group(1_491_021, 11, 4, 2408),
+    group(1_979_845, 19, 8, 1),
+    group(1_996_939, 7, 1, 16632),
+];
+
+/// Check characters of DataBar Limited as 18 modules, a set bit for the
+/// elements of the first color.
+const LIMITED_CHECK: [u32; 89] = [
+    0x2aae2, 0x2aa72, 0x2aa3a, 0x2a972, 0x2a93a, 0x2a8ba, 0x2a572, 0x2a53a, 0x2a4ba, 0x2a2ba,
+    0x29572, 0x2953a, 0x294ba, 0x292ba, 0x28aba, 0x25572, 0x2553a, 0x254ba, 0x252ba, 0x24aba,
+    0x22aba, 0x2ab62, 0x2ab32, 0x2ab1a, 0x2a9b2, 0x2a99a, 0x2a8da, 0x2a5b2, 0x2a59a, 0x2a4da,
+    0x2a2da, 0x295b2, 0x2959a, 0x294da, 0x292da, 0x28ada, 0x255b2, 0x2559a, 0x254da, 0x252da,
+    0x24ada, 0x22ada, 0x2aba2, 0x2ab92, 0x2a9d2, 0x295d2, 0x255d2, 0x2ad62, 0x2ad32, 0x2ad1a,
+    0x2acb2, 0x296b2, 0x2969a, 0x2965a, 0x2935a, 0x28b5a, 0x256b2, 0x2569a, 0x24b5a, 0x2b562,
+    0x2b532, 0x2b51a, 0x2b4b2, 0x2b49a, 0x2b2b2, 0x25ab2, 0x25a9a, 0x25a5a, 0x2595a, 0x24d5a,
+    0x22d5a, 0x2d562, 0x2d532, 0x2d51a, 0x2d4b2, 0x2d49a, 0x2d45a, 0x2d2b2, 0x2d29a, 0x2cab2,
+    0x35532, 0x3551a, 0x354b2, 0x3549a, 0x3545a, 0x3529a, 0x3525a, 0x34a9a, 0x35592,
+];
+
+/// Returns the element widths of a DataBar Limited symbol, starting with a
+/// bar, for a GTIN of 13 digits without its check digit, starting with 0 or
+/// 1.
+pub(crate) fn limited(gtin: &[u8]) -> Vec<u8> {
+    let value = gtin.iter().fold(0u64, |value, &d| value * 10 + d as u64);
+
+    let [left, right] = [value / 2_013_571, value % 2_013_571].map(|value| {
+        let (odd, even) = character(value as u32, &LIMITED, 26, 7, false, false);
+        let widths = interleave(&odd, &even);
+        let checksum = weighted_sum(&widths, 3);
+        (widths, checksum)
+    });
+
+    let check = LIMITED_CHECK[((left.1 + 20 * right.1) % 89) as usize];
+
+    let mut bars = vec![1];
+    bars.extend(&left.0);
+    // Split the check character into runs of modules.
+    let mut width = 0;
+    for i in (0..18).rev() {
+        width += 1;
+        if i == 0 || (check >> i) & 1 != (check >> (i - 1)) & 1 {
+            bars.push(width);
+            width = 0;
+        }
+    }
+    bars.extend(&right.0);
+    push_guard(&mut bars);
+
+    bars
+}
+
+/// Groups of the characters of DataBar Expanded, 17 modules wide.
+const EXPANDED: [Group; 5] = [
+    group(0, 12, 7, 4),
+    group(348, 10, 5, 20),
+    group(1388, 8, 4, 52),
+    group(2948, 6, 3, 104),
+    group(3988, 4, 1, 204),
+];
+
+/// Finder patterns of DataBar Expanded, A to F, without their last element
+/// which is a single module.
+const EXPANDED_FINDERS: [[u8; 4]; 6] = [
+    [1, 8, 4, 1],
+    [3, 6, 4, 1],
+    [3, 4, 6, 1],
+    [3, 2, 8, 1],
+    [2, 6, 5, 1],
+    [2, 2, 9, 1],
+];
+
+/// Finder patterns of the symbols of 2 to 11 pairs of characters.
+const EXPANDED_SEQUENCES: [&[usize]; 10] = [
+    &[0, 0],
+    &[0, 1, 1],
+    &[0, 2, 1, 3],
+    &[0, 4, 1, 3, 2],
+    &[0, 4, 1, 3, 3, 5],
+    &[0, 4, 1, 3, 4, 5, 5],
+    &[0, 0, 1, 1, 2, 2, 3, 3],
+    &[0, 0, 1, 1, 2, 2, 3, 4, 4],
+    &[0, 0, 1, 1, 2, 2, 3, 4, 5, 5],
+    &[0, 0, 1, 1, 2, 3, 3, 4, 4, 5, 5],
+];
+
+/// Checksum weights of the elements of the characters, by finder pattern,
+/// parity of the pair and side of the character.
+const EXPANDED_WEIGHTS: [[u64; 8]; 23] = [
+    [1, 3, 9, 27, 81, 32, 96, 77],
+    [20, 60, 180, 118, 143, 7, 21, 63],
+    [189, 145, 13, 39, 117, 140, 209, 205],
+    [193, 157, 49, 147, 19, 57, 171, 91],
+    [62, 186, 136, 197, 169, 85, 44, 132],
+    [185, 133, 188, 142, 4, 12, 36, 108],
+    [113, 128, 173, 97, 80, 29, 87, 50],
+    [150, 28, 84, 41, 123, 158, 52, 156],
+    [46, 138, 203, 187, 139, 206, 196, 166],
+    [76, 17, 51, 153, 37, 111, 122, 155],
+    [43, 129, 176, 106, 107, 110, 119, 146],
+    [16, 48, 144, 10, 30, 90, 59, 177],
+    [109, 116, 137, 200, 178, 112, 125, 164],
+    [70, 210, 208, 202, 184, 130, 179, 115],
+    [134, 191, 151, 31, 93, 68, 204, 190],
+    [148, 22, 66, 198, 172, 94, 71, 2],
+    [6, 18, 54, 162, 64, 192, 154, 40],
+    [120, 149, 25, 75, 14, 42, 126, 167],
+    [79, 26, 78, 23, 69, 207, 199, 175],
+    [103, 98, 83, 38, 114, 131, 182, 124],
+    [161, 61, 183, 127, 170, 88, 53, 159],
+    [55, 165, 73, 8, 24, 72, 5, 15],
+    [45, 135, 194, 160, 58, 174, 100, 89],
+];
+
+/// Most data characters of a DataBar Expanded symbol.
+const EXPANDED_MAX_CHARACTERS: usize = 21;
+
+/// Encodation modes of the general purpose data field.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    Numeric,
+    Alphanumeric,
+    Iso646,
+}
+
+fn push_bits(bits: &mut Vec<bool>, value: u32, count: usize) {
+    bits.extend((0..count).rev().map(|i| (value >> i) & 1 == 1));
+}
+
+/// Characters of the alphanumeric mode besides digits and uppercase
+/// letters, with their 6-bit values.
+const EXPANDED_ALPHANUMERIC: [(char, u32); 5] =
+    [('*', 58), (',', 59), ('-', 60), ('.', 61), ('/', 62)];
+
+/// Characters of the ISO/IEC 646 mode besides digits and letters, with
+/// their 8-bit values.
+const EXPANDED_ISO646: [(char, u32); 21] = [
+    ('!', 232),
+    ('"', 233),
+    ('%', 234),
+    ('&', 235),
+    ('\'', 236),
+    ('(', 237),
+    (')', 238),
+    ('*', 239),
+    ('+', 240),
+    (',', 241),
+    ('-', 242),
+    ('.', 243),
+    ('/', 244),
+    (':', 245),
+    (';', 246),
+    ('<', 247),
+    ('=', 248),
+    ('>', 249),
+    ('?', 250),
+    ('_', 251),
+    (' ', 252),
+];
+
+fn is_numeric(c: char) -> bool {
+    c.is_ascii_digit() || c == FNC1
+}
+
+fn is_alphanumeric(c: char) -> bool {
+    is_numeric(c) || c.is_ascii_uppercase() || EXPANDED_ALPHANUMERIC.iter().any(|&(a, _)| a == c)
+}
+
+/// Whether the digits from the start of `data` are worth switching to the
+/// numeric mode: at least 6 of them, or 4 ending the data.
+fn numeric_run(data: &[char]) -> bool {
+    let run = data.iter().take_while(|c| c.is_ascii_digit()).count();
+    run >= 6 || (run >= 4 && run == data.len())
+}
+
+/// Encodes the data of a DataBar Expanded symbol with the general purpose
+/// method, returning the bits of the data characters or `None` if the data
+/// doesn't fit in a symbol.
+pub(crate) fn expanded_bits(data: &str) -> Option<Vec<bool>> {
+    let data: Vec<char> = data.chars().collect();
+
+    // The linkage flag, the encodation method and the variable length
+    // field, filled once the length is known.
+    let mut bits = vec![false; 5];
+    let mut mode = Mode::Numeric;
+    let mut i = 0;
+
+    while i < data.len() {
+        let c = data[i];
+        match mode {
+            Mode::Numeric => {
+                let next = data.get(i + 1).copied();
+                let digit = |c: char| if c == FNC1 { 10 } else { c as u32 - '0' as u32 };
+
+                match next {
+                    Some(next)
+                        if is_numeric(c) && is_numeric(next) && (c, next) != (FNC1, FNC1) =>
+                    {
+                        push_bits(&mut bits, 11 * digit(c) + digit(next) + 8, 7);
+                        i += 2;
+                    }
+                    // A last digit is paired with FNC1.
+                    None if c.is_ascii_digit() => {
+                        push_bits(&mut bits, 11 * digit(c) + 10 + 8, 7);
+                        i += 1;
+                    }
+                    _ => {
+                        push_bits(&mut bits, 0, 4);
+                        mode = Mode::Alphanumeric;
+                    }
+                }
+            }
+            Mode::Alphanumeric | Mode::Iso646 if numeric_run(&data[i..]) => {
+                push_bits(&mut bits, 0, 3);
+                mode = Mode::Numeric;
+            }
+            Mode::Alphanumeric if !is_alphanumeric(c) => {
+                push_bits(&mut bits, 4, 5);
+                mode = Mode::Iso646;
+            }
+            Mode::Iso646 if data[i..].iter().take(6).all(|&c| is_alphanumeric(c)) => {
+                push_bits(&mut bits, 4, 5);
+                mode = Mode::Alphanumeric;
+            }
+            Mode::Alphanumeric | Mode::Iso646 => {
+                match c {
+                    FNC1 => push_bits(&mut bits, 15, 5),
+                    '0'..='9' => push_bits(&mut bits, c as u32 - '0' as u32 + 5, 5),
+                    'A'..='Z' if mode == Mode::Alphanumeric => {
+                        push_bits(&mut bits, c as u32 - 33, 6)
+                    }
+                    'A'..='Z' => push_bits(&mut bits, c as u32 - 1, 7),
+                    'a'..='z' => push_bits(&mut bits, c as u32 - 7, 7),
+                    _ => {
+                        let (table, count) = match mode {
+                            Mode::Alphanumeric => (&EXPANDED_ALPHANUMERIC[..], 6),
+                            _ => (&EXPANDED_ISO646[..], 8),
+                        };
+                        let &(_, value) = table.iter().find(|&&(a, _)| a == c)?;
+                        push_bits(&mut bits, value, count);
+                    }
+                }
+                i += 1;
+            }
+        }
+    }
+
+    // At least 3 data characters, for 4 symbol characters with the check
+    // character.
+    let characters = bits.len().div_ceil(12).max(3);
+    if characters > EXPANDED_MAX_CHARACTERS {
+        return None;
+    }
+
+    // Pad with latches to the alphanumeric and the ISO/IEC 646 modes.
+    let mut padding = vec![];
+    if mode == Mode::Numeric && !data.is_empty() {
+        push_bits(&mut padding, 0, 4);
+    }
+    while bits.len() + padding.len() < characters * 12 {
+        push_bits(&mut padding, 4, 5);
+    }
+    padding.truncate(characters * 12 - bits.len());
+    bits.extend(padding);
+
+    // Whether the number of symbol characters is odd, and more than 14.
+    let symbol_characters = characters + 1;
+    bits[3] = symbol_characters % 2 == 1;
+    bits[4] = symbol_characters > 14;
+
+    Some(bits)
+}
+
+/// Returns the element widths of a single row DataBar Expanded symbol,
+/// starting with a bar, or `None` if the data doesn't fit in a symbol.
+///
+/// `data` is a GS1 element string, [`FNC1`] separating the variable length
+/// fields.
+pub(crate) fn expanded(data: &str) -> Option<Vec<u8>> {
+    let bits = expanded_bits(data)?;
+
+    let values: Vec<u32> = bits
+        .chunks(12)
+        .map(|chunk| chunk.iter().fold(0, |value, &bit| value << 1 | bit as u32))
+        .collect();
+
+    let pairs = (values.len() + 2) / 2;
+    let finders = EXPANDED_SEQUENCES[pairs - 2];
+
+    // The elements of each character are listed from left to right.
+    let mut characters: Vec<Vec<u8>> = vec![];
+    let mut checksum = 0;
+    for (i, &value) in values.iter().enumerate() {
+        // The check character is the first one.
+        let position = i + 1;
+        let (pair, right) = (position / 2, position % 2 == 1);
+
+        let (odd, even) = character(value, &EXPANDED, 17, 4, false, true);
+
+        let row = 4 * finders[pair] + if pair % 2 == 0 { 0 } else { 2 } + right as usize - 1;
+        let weights = &EXPANDED_WEIGHTS[row];
+        checksum += odd
+            .iter()
+            .zip(even.iter())
+            .enumerate()
+            .map(|(j, (&odd, &even))| {
+                odd as u64 * weights[2 * j] + even as u64 * weights[2 * j + 1]
+            })
+            .sum::<u64>();
+
+        characters.push(interleave(&odd, &even));
+    }
+
+    let check_value = 211 * (values.len() as u32 + 1 - 4) + (checksum % 211) as u32;
+    let (odd, even) = character(check_value, &EXPANDED, 17, 4, false, true);
+    characters.insert(0, interleave(&odd, &even));
+
+    let mut bars = vec![1];
+    for (pair, &finder) in finders.iter().enumerate() {
+        bars.extend(&characters[2 * pair]);
+
+        let finder = EXPANDED_FINDERS[finder];
+        if pair % 2 == 0 {
+            bars.extend(finder);
+            bars.push(1);
+        } else {
+            bars.push(1);
+            push_reversed(&mut bars, &finder);
+        }
+
+        if let Some(right) = characters.get(2 * pair + 1) {
+            push_reversed(&mut bars, right);
+        }
+    }
+    push_guard(&mut bars);
+
+    Some(bars)
+}
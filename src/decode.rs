@@ -0,0 +1,520 @@
+//! Reading ESC/POS data back into commands, to check what a program sends
+//! to a printer.
+
+use std::fmt;
+
+use crate::printer::{Align, BarcodeType};
+
+const HT: u8 = 0x09;
+const LF: u8 = 0x0a;
+const CR: u8 = 0x0d;
+const DLE: u8 = 0x10;
+const ESC: u8 = 0x1b;
+const FS: u8 = 0x1c;
+const GS: u8 = 0x1d;
+
+/// An ESC/POS command, with its parameters.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Command {
+    /// Characters in the code table of the printer.
+    Text(Vec<u8>),
+    LineFeed,
+    CarriageReturn,
+    Tab,
+    Initialize,
+    Bold(bool),
+    DoubleStrike(bool),
+    /// Underline thickness in dots, 0 for none.
+    Underline(u8),
+    /// Font number, 0 for font A.
+    Font(u8),
+    PrintMode(u8),
+    /// Character width and height, from 1 to 8.
+    Size {
+        width: u8,
+        height: u8,
+    },
+    Invert(bool),
+    UpsideDown(bool),
+    Rotation(bool),
+    CharacterSpacing(u8),
+    Smoothing(bool),
+    Color(u8),
+    Align(Align),
+    LeftMargin(u16),
+    PrintAreaWidth(u16),
+    AbsolutePosition(u16),
+    RelativePosition(i16),
+    CodeTable(u8),
+    InternationalSet(u8),
+    /// Line spacing in dots, or `None` for the default.
+    LineSpacing(Option<u8>),
+    FeedLines(u8),
+    FeedDots(u8),
+    /// A raster image of `width` by `height` dots, with one bit per dot. The
+    /// mode doubles the width with bit 0 and the height with bit 1.
+    Raster {
+        mode: u8,
+        width: u32,
+        height: u16,
+        data: Vec<u8>,
+    },
+    BarcodeHeight(u8),
+    BarcodeWidth(u8),
+    HriPosition(u8),
+    HriFont(u8),
+    /// A barcode of `system` `m` of `GS k`.
+    Barcode {
+        system: u8,
+        data: Vec<u8>,
+    },
+    /// A `GS ( k` function of the 2D symbol `cn`, such as 49 for QR codes.
+    Symbol {
+        cn: u8,
+        function: u8,
+        parameters: Vec<u8>,
+    },
+    Cut {
+        mode: u8,
+        feed: Option<u8>,
+    },
+    /// A drawer pulse of `on` and `off` times 2 ms.
+    CashDrawer {
+        pin: u8,
+        on: u8,
+        off: u8,
+    },
+    /// A real-time drawer pulse of `time` times 100 ms.
+    RealtimeDrawer {
+        pin: u8,
+        time: u8,
+    },
+    TransmitStatus(u8),
+    /// `times` beeps of `duration` times 50 ms, with the `ESC B` of Epson
+    /// clones. The `ESC ( A` of Epson printers is left [`Command::Extended`].
+    Beep {
+        times: u8,
+        duration: u8,
+    },
+    /// A command with a 16-bit length, such as `ESC ( A` or `GS ( L`, that
+    /// isn't read further.
+    Extended {
+        prefix: u8,
+        function: u8,
+        data: Vec<u8>,
+    },
+    Unknown(Vec<u8>),
+}
+
+/// A command and where it was in the data.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Instruction {
+    pub offset: usize,
+    pub bytes: Vec<u8>,
+    pub command: Command,
+}
+
+/// Splits ESC/POS data into commands. Bytes that aren't a known command are
+/// returned as [`Command::Unknown`], and reading goes on after them.
+///
+/// # Example
+///
+/// ```ignore
+/// let data = std::fs::read("receipt.bin")?;
+/// for instruction in decode(&data) {
+///     println!("{}", instruction);
+/// }
+/// ```
+pub fn decode(data: &[u8]) -> Vec<Instruction> {
+    let mut instructions = vec![];
+    let mut offset = 0;
+
+    while offset < data.len() {
+        let (length, command) = read(&data[offset..]);
+        let length = length.max(1).min(data.len() - offset);
+        instructions.push(Instruction {
+            offset,
+            bytes: data[offset..offset + length].to_vec(),
+            command,
+        });
+        offset += length;
+    }
+
+    instructions
+}
+
+/// Reads the command at the start of `data`, returning its length.
+fn read(data: &[u8]) -> (usize, Command) {
+    let byte = |i: usize| data.get(i).copied();
+    let word = |i: usize| Some(u16::from_le_bytes([byte(i)?, byte(i + 1)?]));
+    let unknown = |length: usize| {
+        let length = length.min(data.len());
+        (length, Command::Unknown(data[..length].to_vec()))
+    };
+    let simple = |command: fn(u8) -> Command| match byte(2) {
+        Some(n) => (3, command(n)),
+        None => unknown(data.len()),
+    };
+
+    match (data[0], byte(1)) {
+        (LF, _) => (1, Command::LineFeed),
+        (CR, _) => (1, Command::CarriageReturn),
+        (HT, _) => (1, Command::Tab),
+        (ESC, Some(b'@')) => (2, Command::Initialize),
+        (ESC, Some(b'E')) => simple(|n| Command::Bold(n & 1 == 1)),
+        (ESC, Some(b'G')) => simple(|n| Command::DoubleStrike(n & 1 == 1)),
+        (ESC, Some(b'-')) => simple(|n| Command::Underline(n & 3)),
+        (ESC, Some(b'M')) => simple(|n| {
+            Command::Font(match n {
+                b'0'..=b'4' => n - b'0',
+                _ => n,
+            })
+        }),
+        (ESC, Some(b'!')) => simple(Command::PrintMode),
+        (ESC, Some(b'V')) => simple(|n| Command::Rotation(n & 1 == 1)),
+        (ESC, Some(b'{')) => simple(|n| Command::UpsideDown(n & 1 == 1)),
+        (ESC, Some(b' ')) => simple(Command::CharacterSpacing),
+        (ESC, Some(b'r')) => simple(Command::Color),
+        (ESC, Some(b'a')) => simple(|n| {
+            Command::Align(match n & 3 {
+                1 => Align::Center,
+                2 => Align::Right,
+                _ => Align::Left,
+            })
+        }),
+        (ESC, Some(b't')) => simple(Command::CodeTable),
+        (ESC, Some(b'R')) => simple(Command::InternationalSet),
+        (ESC, Some(b'2')) => (2, Command::LineSpacing(None)),
+        (ESC, Some(b'3')) => simple(|n| Command::LineSpacing(Some(n))),
+        (ESC, Some(b'd')) => simple(Command::FeedLines),
+        (ESC, Some(b'J')) => simple(Command::FeedDots),
+        (ESC, Some(b'$')) => match word(2) {
+            Some(n) => (4, Command::AbsolutePosition(n)),
+            None => unknown(data.len()),
+        },
+        (ESC, Some(b'\\')) => match word(2) {
+            Some(n) => (4, Command::RelativePosition(n as i16)),
+            None => unknown(data.len()),
+        },
+        (ESC, Some(b'p')) => match (byte(2), byte(3), byte(4)) {
+            (Some(pin), Some(on), Some(off)) => (5, Command::CashDrawer { pin, on, off }),
+            _ => unknown(data.len()),
+        },
+        (ESC, Some(b'B')) => match (byte(2), byte(3)) {
+            (Some(times), Some(duration)) => (4, Command::Beep { times, duration }),
+            _ => unknown(data.len()),
+        },
+        (ESC | GS, Some(b'(')) => match (byte(2), word(3)) {
+            (Some(function), Some(length)) => {
+                let end = (5 + length as usize).min(data.len());
+                let extended = Command::Extended {
+                    prefix: data[0],
+                    function,
+                    data: data[5..end].to_vec(),
+                };
+
+                match (data[0], function, byte(5), byte(6)) {
+                    (GS, b'k', Some(cn), Some(function)) if length >= 2 => (
+                        end,
+                        Command::Symbol {
+                            cn,
+                            function,
+                            parameters: data[7.min(end)..end].to_vec(),
+                        },
+                    ),
+                    _ => (end, extended),
+                }
+            }
+            _ => unknown(data.len()),
+        },
+        (GS, Some(b'!')) => simple(|n| Command::Size {
+            width: (n >> 4) + 1,
+            height: (n & 0x0f) + 1,
+        }),
+        (GS, Some(b'B')) => simple(|n| Command::Invert(n & 1 == 1)),
+        (GS, Some(b'b')) => simple(|n| Command::Smoothing(n & 1 == 1)),
+        (GS, Some(b'L')) => match word(2) {
+            Some(n) => (4, Command::LeftMargin(n)),
+            None => unknown(data.len()),
+        },
+        (GS, Some(b'W')) => match word(2) {
+            Some(n) => (4, Command::PrintAreaWidth(n)),
+            None => unknown(data.len()),
+        },
+        (GS, Some(b'h')) => simple(Command::BarcodeHeight),
+        (GS, Some(b'w')) => simple(Command::BarcodeWidth),
+        (GS, Some(b'H')) => simple(|n| Command::HriPosition(n & 3)),
+        (GS, Some(b'f')) => simple(Command::HriFont),
+        (GS, Some(b'k')) => match byte(2) {
+            // Function A, with the data ending with NUL.
+            Some(system @ 0..=6) => match data[3..].iter().position(|&b| b == 0) {
+                Some(end) => (
+                    4 + end,
+                    Command::Barcode {
+                        system,
+                        data: data[3..3 + end].to_vec(),
+                    },
+                ),
+                None => unknown(data.len()),
+            },
+            Some(system) => match byte(3) {
+                Some(n) if data.len() >= 4 + n as usize => (
+                    4 + n as usize,
+                    Command::Barcode {
+                        system,
+                        data: data[4..4 + n as usize].to_vec(),
+                    },
+                ),
+                _ => unknown(data.len()),
+            },
+            None => unknown(data.len()),
+        },
+        (GS, Some(b'v')) => match (byte(2), byte(3), word(4), word(6)) {
+            (Some(b'0'), Some(mode), Some(width), Some(height)) => {
+                let end = 8 + width as usize * height as usize;
+                if end > data.len() {
+                    return unknown(data.len());
+                }
+                (
+                    end,
+                    Command::Raster {
+                        mode: mode & 3,
+                        width: width as u32 * 8,
+                        height,
+                        data: data[8..end].to_vec(),
+                    },
+                )
+            }
+            _ => unknown(data.len()),
+        },
+        (GS, Some(b'V')) => match byte(2) {
+            Some(mode @ (65 | 66 | 97 | 98 | 103 | 104)) => match byte(3) {
+                Some(n) => (
+                    4,
+                    Command::Cut {
+                        mode,
+                        feed: Some(n),
+                    },
+                ),
+                None => unknown(data.len()),
+            },
+            Some(mode) => (3, Command::Cut { mode, feed: None }),
+            None => unknown(data.len()),
+        },
+        (DLE, Some(0x04)) => simple(Command::TransmitStatus),
+        (DLE, Some(0x14)) => match (byte(2), byte(3), byte(4)) {
+            (Some(1), Some(pin), Some(time)) => (5, Command::RealtimeDrawer { pin, time }),
+            _ => unknown(3),
+        },
+        (ESC | GS | FS, _) => unknown(2),
+        (0x00..=0x1f, _) => unknown(1),
+        _ => {
+            let end = data.iter().position(|&b| b < 0x20).unwrap_or(data.len());
+            (end, Command::Text(data[..end].to_vec()))
+        }
+    }
+}
+
+fn on_off(enabled: bool) -> &'static str {
+    if enabled {
+        "on"
+    } else {
+        "off"
+    }
+}
+
+/// The barcode type of `system` `m` of `GS k`.
+pub(crate) fn barcode_type(system: u8) -> Option<BarcodeType> {
+    Some(match system {
+        0 | 65 => BarcodeType::UPCA,
+        1 | 66 => BarcodeType::UPCE,
+        2 | 67 => BarcodeType::JAN13,
+        3 | 68 => BarcodeType::JAN8,
+        4 | 69 => BarcodeType::CODE39,
+        5 | 70 => BarcodeType::ITF,
+        6 | 71 => BarcodeType::CODABAR,
+        72 => BarcodeType::CODE93,
+        73 => BarcodeType::CODE128,
+        74 => BarcodeType::GS1128,
+        75 => BarcodeType::GS1DataBarOmnidirectional,
+        76 => BarcodeType::GS1DataBarTruncated,
+        77 => BarcodeType::GS1DataBarLimited,
+        78 => BarcodeType::GS1DataBarExpanded,
+        _ => return None,
+    })
+}
+
+/// Writes bytes as text, escaping the ones outside of printable ASCII.
+fn escaped(f: &mut fmt::Formatter<'_>, bytes: &[u8]) -> fmt::Result {
+    write!(f, "\"")?;
+    for &byte in bytes {
+        match byte {
+            b'"' | b'\\' => write!(f, "\\{}", byte as char)?,
+            b' '..=b'~' => write!(f, "{}", byte as char)?,
+            _ => write!(f, "\\x{:02X}", byte)?,
+        }
+    }
+    write!(f, "\"")
+}
+
+impl fmt::Display for Command {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Command::Text(text) => {
+                write!(f, "Text ")?;
+                escaped(f, text)
+            }
+            Command::LineFeed => write!(f, "Line feed"),
+            Command::CarriageReturn => write!(f, "Carriage return"),
+            Command::Tab => write!(f, "Horizontal tab"),
+            Command::Initialize => write!(f, "Initialize"),
+            Command::Bold(bold) => write!(f, "Bold {}", on_off(*bold)),
+            Command::DoubleStrike(enabled) => write!(f, "Double strike {}", on_off(*enabled)),
+            Command::Underline(0) => write!(f, "Underline off"),
+            Command::Underline(dots) => write!(f, "Underline {} dot(s)", dots),
+            Command::Font(font @ 0..=4) => write!(f, "Font {}", (b'A' + font) as char),
+            Command::Font(font) => write!(f, "Font {}", font),
+            Command::PrintMode(mode) => write!(f, "Print mode 0x{:02X}", mode),
+            Command::Size { width, height } => write!(f, "Character size {}x{}", width, height),
+            Command::Invert(enabled) => write!(f, "Reverse printing {}", on_off(*enabled)),
+            Command::UpsideDown(enabled) => write!(f, "Upside down {}", on_off(*enabled)),
+            Command::Rotation(enabled) => write!(f, "90 degree rotation {}", on_off(*enabled)),
+            Command::CharacterSpacing(dots) => write!(f, "Character spacing {} dots", dots),
+            Command::Smoothing(enabled) => write!(f, "Smoothing {}", on_off(*enabled)),
+            Command::Color(color) => write!(f, "Color {}", color),
+            Command::Align(align) => write!(f, "Align {:?}", align),
+            Command::LeftMargin(dots) => write!(f, "Left margin {} dots", dots),
+            Command::PrintAreaWidth(dots) => write!(f, "Print area width {} dots", dots),
+            Command::AbsolutePosition(dots) => write!(f, "Absolute position {} dots", dots),
+            Command::RelativePosition(dots) => write!(f, "Relative position {} dots", dots),
+            Command::CodeTable(table) => write!(f, "Code table {}", table),
+            Command::InternationalSet(set) => write!(f, "International character set {}", set),
+            Command::LineSpacing(None) => write!(f, "Default line spacing"),
+            Command::LineSpacing(Some(dots)) => write!(f, "Line spacing {} dots", dots),
+            Command::FeedLines(lines) => write!(f, "Feed {} lines", lines),
+            Command::FeedDots(dots) => write!(f, "Feed {} dots", dots),
+            Command::Raster {
+                mode,
+                width,
+                height,
+                ..
+            } => write!(f, "Raster image {}x{} dots, mode {}", width, height, mode),
+            Command::BarcodeHeight(dots) => write!(f, "Barcode height {} dots", dots),
+            Command::BarcodeWidth(width) => write!(f, "Barcode module width {}", width),
+            Command::HriPosition(position) => write!(
+                f,
+                "HRI {}",
+                ["off", "above", "below", "above and below"][*position as usize & 3]
+            ),
+            Command::HriFont(font) => write!(f, "HRI font {}", font),
+            Command::Barcode { system, data } => {
+                match barcode_type(*system) {
+                    Some(r#type) => write!(f, "Barcode {} ", r#type)?,
+                    None => write!(f, "Barcode system {} ", system)?,
+                }
+                escaped(f, data)
+            }
+            Command::Symbol {
+                cn,
+                function,
+                parameters,
+            } => {
+                let symbol = match cn {
+                    48 => "PDF417",
+                    49 => "QR code",
+                    50 => "MaxiCode",
+                    53 => "Aztec Code",
+                    54 => "DataMatrix",
+                    _ => "2D symbol",
+                };
+                match (cn, function, parameters.first()) {
+                    (49, 65, Some(49)) => write!(f, "{} model 1", symbol),
+                    (49, 65, Some(50)) => write!(f, "{} model 2", symbol),
+                    (49, 65, Some(51)) => write!(f, "Micro {}", symbol),
+                    (49, 67, Some(size)) => write!(f, "{} module size {}", symbol, size),
+                    (49, 69, Some(level)) => write!(
+                        f,
+                        "{} error correction {}",
+                        symbol,
+                        ["L", "M", "Q", "H"][(level & 3) as usize]
+                    ),
+                    // The data follows m, which is always 48.
+                    (_, 80, _) => {
+                        write!(f, "{} store ", symbol)?;
+                        escaped(f, parameters.get(1..).unwrap_or_default())
+                    }
+                    (_, 81, _) => write!(f, "{} print", symbol),
+                    _ => write!(f, "{} function {} {:?}", symbol, function, parameters),
+                }
+            }
+            Command::Cut { mode, feed } => {
+                let cut = match mode {
+                    1 | 49 | 66 | 98 | 104 => "Partial cut",
+                    _ => "Full cut",
+                };
+                match (mode, feed) {
+                    (103 | 104, Some(feed)) => {
+                        write!(f, "{} after feeding {} dots, then reverse", cut, feed)
+                    }
+                    (_, Some(feed)) => write!(f, "{} after feeding {} dots", cut, feed),
+                    (_, None) => write!(f, "{}", cut),
+                }
+            }
+            Command::CashDrawer { pin, on, off } => write!(
+                f,
+                "Drawer pulse on pin {}, {} ms on, {} ms off",
+                if pin & 1 == 1 { 5 } else { 2 },
+                *on as u16 * 2,
+                *off as u16 * 2
+            ),
+            Command::RealtimeDrawer { pin, time } => write!(
+                f,
+                "Real-time drawer pulse on pin {}, {} ms",
+                if pin & 1 == 1 { 5 } else { 2 },
+                *time as u16 * 100
+            ),
+            Command::TransmitStatus(status) => write!(f, "Transmit status {}", status),
+            Command::Beep { times, duration } => {
+                write!(f, "Beep {} times, {} ms", times, *duration as u16 * 50)
+            }
+            Command::Extended {
+                prefix,
+                function,
+                data,
+            } => write!(
+                f,
+                "{} ( {} with {} bytes",
+                if *prefix == ESC { "ESC" } else { "GS" },
+                *function as char,
+                data.len()
+            ),
+            Command::Unknown(bytes) => write!(f, "Unknown {:02X?}", bytes),
+        }
+    }
+}
+
+impl fmt::Display for Instruction {
+    /// Writes the offset, the first bytes and the command, as a line of a
+    /// listing.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        const SHOWN: usize = 8;
+
+        let mut bytes: Vec<String> = self
+            .bytes
+            .iter()
+            .take(SHOWN)
+            .map(|byte| format!("{:02X}", byte))
+            .collect();
+        if self.bytes.len() > SHOWN {
+            bytes.push("..".to_string());
+        }
+
+        write!(
+            f,
+            "{:06X}  {:<27} {}",
+            self.offset,
+            bytes.join(" "),
+            self.command
+        )
+    }
+}
@@ -0,0 +1,179 @@
+use std::fmt;
+
+use crate::printer::BarcodeType;
+
+/// Why a barcode payload was rejected.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BarcodeError {
+    /// The payload has fewer than `min` or more than `max` characters.
+    InvalidLength {
+        min: usize,
+        max: usize,
+        length: usize,
+    },
+    /// The payload has an odd number of digits, while ITF encodes digits in
+    /// pairs.
+    OddLength { length: usize },
+    /// `character` at `position` can't be encoded by the symbology.
+    InvalidCharacter { character: char, position: usize },
+    /// CODABAR payloads must start and end with one of `A`, `B`, `C` or `D`.
+    MissingStartStop,
+    /// The check digit given with the payload doesn't match the data.
+    InvalidCheckDigit { expected: u8, found: u8 },
+    /// UPC-E supports the number systems 0 and 1 only.
+    InvalidNumberSystem(u8),
+    /// A GS1 date isn't a valid `YYMMDD` date.
+    InvalidDate,
+    /// The data doesn't fit in a single symbol.
+    TooLong,
+    /// An amount is negative, zero where it can't be, or too large.
+    InvalidAmount,
+    /// A number isn't between `min` and `max`.
+    OutOfRange { min: u32, max: u32, value: u32 },
+    /// The symbology has no symbol of the requested size.
+    InvalidSize,
+}
+
+impl fmt::Display for BarcodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BarcodeError::InvalidLength { min, max, length } if min == max => {
+                write!(f, "expected {} characters, got {}", min, length)
+            }
+            BarcodeError::InvalidLength { min, max, length } => {
+                write!(f, "expected {} to {} characters, got {}", min, max, length)
+            }
+            BarcodeError::OddLength { length } => {
+                write!(f, "expected an even number of digits, got {}", length)
+            }
+            BarcodeError::InvalidCharacter {
+                character,
+                position,
+            } => write!(
+                f,
+                "character {:?} at position {} can't be encoded",
+                character, position
+            ),
+            BarcodeError::MissingStartStop => {
+                write!(f, "expected the data to start and end with A, B, C or D")
+            }
+            BarcodeError::InvalidCheckDigit { expected, found } => {
+                write!(f, "check digit should be {}, got {}", expected, found)
+            }
+            BarcodeError::InvalidNumberSystem(number_system) => {
+                write!(f, "number system should be 0 or 1, got {}", number_system)
+            }
+            BarcodeError::InvalidDate => write!(f, "expected a date as YYMMDD"),
+            BarcodeError::TooLong => write!(f, "the data doesn't fit in a symbol"),
+            BarcodeError::InvalidAmount => write!(f, "the amount is out of range"),
+            BarcodeError::OutOfRange { min, max, value } => {
+                write!(f, "expected {} to {}, got {}", min, max, value)
+            }
+            BarcodeError::InvalidSize => write!(f, "no symbol has the requested size"),
+        }
+    }
+}
+
+/// Why QR code data was rejected.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum QRCodeError {
+    /// The data of `length` bytes doesn't fit in the largest symbol of the
+    /// model at the error correction level.
+    TooLong { length: usize },
+    /// Micro QR codes have no error correction level H.
+    UnsupportedCorrection,
+    /// Micro QR codes can't carry an ECI header.
+    UnsupportedEci,
+    /// ECI assignment numbers go from 0 to 999999.
+    InvalidEci(u32),
+}
+
+impl fmt::Display for QRCodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            QRCodeError::TooLong { length } => write!(
+                f,
+                "{} bytes of data don't fit in a symbol, use a lower error correction level or less data",
+                length
+            ),
+            QRCodeError::UnsupportedCorrection => {
+                write!(f, "micro QR codes have no error correction level H")
+            }
+            QRCodeError::UnsupportedEci => write!(f, "micro QR codes can't carry an ECI header"),
+            QRCodeError::InvalidEci(eci) => {
+                write!(f, "ECI should be between 0 and 999999, got {}", eci)
+            }
+        }
+    }
+}
+
+/// Errors returned by the printers when a command can't be built.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Error {
+    /// The data of a barcode of type `r#type` is invalid.
+    InvalidBarcode {
+        r#type: BarcodeType,
+        reason: BarcodeError,
+    },
+    /// The GS1 Application Identifier isn't known.
+    UnknownApplicationIdentifier(String),
+    /// The value given for the GS1 Application Identifier `ai` is invalid.
+    InvalidGs1Element { ai: String, reason: BarcodeError },
+    /// The data of a QR code can't be encoded.
+    InvalidQRCode(QRCodeError),
+    /// The printer can't print symbols of `symbology`, such as `PDF417`.
+    UnsupportedSymbol(&'static str),
+    /// The data or options of a 2D symbol of `symbology` are invalid.
+    InvalidSymbol {
+        symbology: &'static str,
+        reason: BarcodeError,
+    },
+    /// The value of `field` of a QR code payload is invalid.
+    InvalidQRCodeField {
+        field: &'static str,
+        reason: BarcodeError,
+    },
+    /// A receipt template has a syntax error, or can't be filled with the
+    /// data, at `line`.
+    InvalidTemplate { line: usize, reason: String },
+    /// The data of a receipt template can't be serialized.
+    InvalidTemplateData(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::InvalidBarcode { r#type, reason } => {
+                write!(f, "invalid {} barcode: {}", r#type, reason)
+            }
+            Error::UnknownApplicationIdentifier(ai) => {
+                write!(f, "unknown GS1 application identifier ({})", ai)
+            }
+            Error::InvalidGs1Element { ai, reason } => {
+                write!(
+                    f,
+                    "invalid value for GS1 application identifier ({}): {}",
+                    ai, reason
+                )
+            }
+            Error::InvalidQRCode(reason) => write!(f, "invalid QR code: {}", reason),
+            Error::UnsupportedSymbol(symbology) => {
+                write!(f, "the printer can't print {} symbols", symbology)
+            }
+            Error::InvalidSymbol { symbology, reason } => {
+                write!(f, "invalid {} symbol: {}", symbology, reason)
+            }
+            Error::InvalidQRCodeField { field, reason } => {
+                write!(f, "invalid {}: {}", field, reason)
+            }
+            Error::InvalidTemplate { line, reason } => {
+                write!(f, "invalid template at line {}: {}", line, reason)
+            }
+            Error::InvalidTemplateData(reason) => {
+                write!(f, "invalid template data: {}", reason)
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {}
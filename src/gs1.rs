@@ -0,0 +1,466 @@
+use std::ops::{Range, RangeInclusive};
+
+use crate::{
+    barcode::{check_chars, check_length, with_check_digit, FNC1},
+    error::{BarcodeError, Error},
+};
+
+/// Format of the value of an Application Identifier.
+struct Format {
+    min: usize,
+    max: usize,
+    numeric: bool,
+    /// The last digit is a GS1 check digit.
+    check_digit: bool,
+    /// The value is a `YYMMDD` date.
+    date: bool,
+    /// The AI is a measure whose third digit is the kind of measure and
+    /// whose last digit is the number of decimals of the value.
+    measure: bool,
+}
+
+const fn numeric(length: usize) -> Format {
+    Format {
+        min: length,
+        max: length,
+        numeric: true,
+        check_digit: false,
+        date: false,
+        measure: false,
+    }
+}
+
+const fn numeric_up_to(max: usize) -> Format {
+    Format {
+        min: 1,
+        max,
+        numeric: true,
+        check_digit: false,
+        date: false,
+        measure: false,
+    }
+}
+
+const fn alphanumeric(max: usize) -> Format {
+    Format {
+        min: 1,
+        max,
+        numeric: false,
+        check_digit: false,
+        date: false,
+        measure: false,
+    }
+}
+
+const fn with_check(length: usize) -> Format {
+    Format {
+        check_digit: true,
+        ..numeric(length)
+    }
+}
+
+const DATE: Format = Format {
+    date: true,
+    ..numeric(6)
+};
+
+const MEASURE: Format = Format {
+    measure: true,
+    ..numeric(6)
+};
+
+/// The kinds of measure of each family of measure AIs, as their third
+/// digit.
+const MEASURES: &[(&str, RangeInclusive<u8>)] = &[
+    ("31", b'0'..=b'6'),
+    ("32", b'0'..=b'9'),
+    ("33", b'0'..=b'7'),
+    ("34", b'0'..=b'9'),
+    ("35", b'0'..=b'7'),
+    ("36", b'0'..=b'9'),
+];
+
+/// The most decimals of a measure, since its value has 6 digits.
+const MAX_DECIMALS: u8 = b'5';
+
+/// Application Identifiers as their leading digits, their length and the
+/// format of their value.
+const APPLICATION_IDENTIFIERS: &[(&str, usize, Format)] = &[
+    ("00", 2, with_check(18)),
+    ("01", 2, with_check(14)),
+    ("02", 2, with_check(14)),
+    ("10", 2, alphanumeric(20)),
+    ("11", 2, DATE),
+    ("12", 2, DATE),
+    ("13", 2, DATE),
+    ("15", 2, DATE),
+    ("16", 2, DATE),
+    ("17", 2, DATE),
+    ("20", 2, numeric(2)),
+    ("21", 2, alphanumeric(20)),
+    ("22", 2, alphanumeric(20)),
+    ("240", 3, alphanumeric(30)),
+    ("241", 3, alphanumeric(30)),
+    ("250", 3, alphanumeric(30)),
+    ("251", 3, alphanumeric(30)),
+    ("30", 2, numeric_up_to(8)),
+    // Trade and logistic measures.
+    ("31", 4, MEASURE),
+    ("32", 4, MEASURE),
+    ("33", 4, MEASURE),
+    ("34", 4, MEASURE),
+    ("35", 4, MEASURE),
+    ("36", 4, MEASURE),
+    ("37", 2, numeric_up_to(8)),
+    ("390", 4, numeric_up_to(15)),
+    ("391", 4, numeric_up_to(18)),
+    ("392", 4, numeric_up_to(15)),
+    ("393", 4, numeric_up_to(18)),
+    ("400", 3, alphanumeric(30)),
+    ("401", 3, alphanumeric(30)),
+    ("402", 3, with_check(17)),
+    ("403", 3, alphanumeric(30)),
+    ("410", 3, with_check(13)),
+    ("411", 3, with_check(13)),
+    ("412", 3, with_check(13)),
+    ("413", 3, with_check(13)),
+    ("414", 3, with_check(13)),
+    ("415", 3, with_check(13)),
+    ("416", 3, with_check(13)),
+    ("417", 3, with_check(13)),
+    ("420", 3, alphanumeric(20)),
+    ("421", 3, alphanumeric(12)),
+    ("422", 3, numeric(3)),
+    ("8004", 4, alphanumeric(30)),
+    ("8005", 4, numeric(6)),
+    ("8020", 4, alphanumeric(25)),
+    ("90", 2, alphanumeric(30)),
+    ("91", 2, alphanumeric(90)),
+    ("92", 2, alphanumeric(90)),
+    ("93", 2, alphanumeric(90)),
+    ("94", 2, alphanumeric(90)),
+    ("95", 2, alphanumeric(90)),
+    ("96", 2, alphanumeric(90)),
+    ("97", 2, alphanumeric(90)),
+    ("98", 2, alphanumeric(90)),
+    ("99", 2, alphanumeric(90)),
+];
+
+/// Leading digits of the Application Identifiers whose length is predefined
+/// by the GS1 specification, which need no FNC1 separator after them.
+const PREDEFINED_LENGTH: &[&str] = &[
+    "00", "01", "02", "03", "04", "11", "12", "13", "14", "15", "16", "17", "18", "19", "20", "23",
+    "31", "32", "33", "34", "35", "36", "41",
+];
+
+/// Whether the measure AI `ai` has a known kind of measure and at most
+/// [`MAX_DECIMALS`] decimals.
+fn is_measure(ai: &str) -> bool {
+    let digits = ai.as_bytes();
+    MEASURES
+        .iter()
+        .any(|(family, kinds)| ai.starts_with(family) && kinds.contains(&digits[2]))
+        && digits[3] <= MAX_DECIMALS
+}
+
+/// Whether the six digits `date` are a valid `YYMMDD` date, a day of 00
+/// standing for the last day of the month.
+fn is_date(date: &str) -> bool {
+    let number = |range: Range<usize>| date[range].parse::<u32>().unwrap_or(0);
+    let (year, month, day) = (number(0..2), number(2..4), number(4..6));
+
+    let days = match month {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+        4 | 6 | 9 | 11 => 30,
+        // The century is left out, and 2000 was a leap year.
+        2 if year % 4 == 0 => 29,
+        2 => 28,
+        _ => return false,
+    };
+
+    day <= days
+}
+
+/// GS1 character set 82, allowed in alphanumeric values.
+pub(crate) fn is_gs1_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || "!\"%&'()*+,-./:;<=>?_".contains(c)
+}
+
+/// Builder of GS1 element strings, the data of GS1-128 and GS1 DataBar
+/// Expanded barcodes, made of Application Identifiers (AIs) and their
+/// values.
+///
+/// # Example
+///
+/// ```ignore
+/// let mut gs1 = Gs1::new();
+/// gs1.gtin("0950110153000")?.expiry(25, 12, 31)?.batch("ABC123")?;
+///
+/// printer.barcode(&gs1.element_string(), BarcodeType::GS1128, BarcodeOptions::default())?;
+/// printer.text(&gs1.hri());
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct Gs1 {
+    elements: Vec<(String, String)>,
+}
+
+impl Gs1 {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the element `ai` with `value`, after checking the value against
+    /// the format of the AI. The check digit of numeric values like the
+    /// GTIN is computed when it is left out.
+    pub fn add(&mut self, ai: &str, value: &str) -> Result<&mut Self, Error> {
+        let (_, _, format) = APPLICATION_IDENTIFIERS
+            .iter()
+            .find(|(prefix, length, _)| ai.len() == *length && ai.starts_with(prefix))
+            .filter(|_| ai.chars().all(|c| c.is_ascii_digit()))
+            .filter(|(_, _, format)| !format.measure || is_measure(ai))
+            .ok_or_else(|| Error::UnknownApplicationIdentifier(ai.to_string()))?;
+
+        let invalid = |reason| Error::InvalidGs1Element {
+            ai: ai.to_string(),
+            reason,
+        };
+
+        let value = if format.check_digit {
+            with_check_digit(value, format.max).map_err(invalid)?
+        } else {
+            check_length(value, format.min, format.max).map_err(invalid)?;
+            if format.numeric {
+                check_chars(value, |c| c.is_ascii_digit()).map_err(invalid)?;
+            } else {
+                check_chars(value, is_gs1_char).map_err(invalid)?;
+            }
+            value.to_string()
+        };
+
+        if format.date && !is_date(&value) {
+            return Err(invalid(BarcodeError::InvalidDate));
+        }
+
+        self.elements.push((ai.to_string(), value));
+        Ok(self)
+    }
+
+    /// Adds the Global Trade Item Number (AI 01) as 14 digits, or 13 digits
+    /// without the check digit.
+    pub fn gtin(&mut self, gtin: &str) -> Result<&mut Self, Error> {
+        self.add("01", gtin)
+    }
+
+    /// Adds the batch or lot number (AI 10).
+    pub fn batch(&mut self, batch: &str) -> Result<&mut Self, Error> {
+        self.add("10", batch)
+    }
+
+    /// Adds the serial number (AI 21).
+    pub fn serial(&mut self, serial: &str) -> Result<&mut Self, Error> {
+        self.add("21", serial)
+    }
+
+    /// Adds the expiration date (AI 17). A `day` of 0 stands for the last
+    /// day of the month.
+    pub fn expiry(&mut self, year: u8, month: u8, day: u8) -> Result<&mut Self, Error> {
+        self.add("17", &format!("{:02}{:02}{:02}", year % 100, month, day))
+    }
+
+    /// Adds the net weight in kilograms (AI 310n), with `decimals` digits
+    /// after the decimal point (0 to 5).
+    pub fn net_weight_kg(&mut self, weight: f64, decimals: u8) -> Result<&mut Self, Error> {
+        let decimals = decimals.min(5);
+        let ai = format!("310{}", decimals);
+        let value = (weight * 10f64.powi(decimals as i32)).round();
+
+        if !(0.0..=999_999.0).contains(&value) {
+            return Err(Error::InvalidGs1Element {
+                ai,
+                reason: BarcodeError::InvalidLength {
+                    min: 6,
+                    max: 6,
+                    length: format!("{}", value.abs() as u64).len(),
+                },
+            });
+        }
+
+        self.add(&ai, &format!("{:06}", value as u32))
+    }
+
+    /// Returns the element string to print as a GS1-128 or GS1 DataBar
+    /// Expanded barcode, with [`FNC1`] after each variable length value
+    /// that isn't the last. The leading FNC1 is added by the printer.
+    pub fn element_string(&self) -> String {
+        let mut data = String::new();
+
+        for (i, (ai, value)) in self.elements.iter().enumerate() {
+            data.push_str(ai);
+            data.push_str(value);
+
+            let last = i + 1 == self.elements.len();
+            if !last && !PREDEFINED_LENGTH.contains(&&ai[..2]) {
+                data.push(FNC1);
+            }
+        }
+
+        data
+    }
+
+    /// Returns the human readable interpretation of the element string,
+    /// with each AI in parentheses, like `(01)09501101530003(10)ABC123`.
+    pub fn hri(&self) -> String {
+        self.elements
+            .iter()
+            .map(|(ai, value)| format!("({}){}", ai, value))
+            .collect()
+    }
+}
+
+/// Splits an element string into its AIs and values to return its human
+/// readable interpretation, or `None` if a field doesn't start with a known
+/// AI.
+pub(crate) fn parse_hri(data: &str) -> Option<String> {
+    let mut hri = String::new();
+    let mut rest = data.strip_prefix(FNC1).unwrap_or(data);
+
+    while !rest.is_empty() {
+        let (_, length, format) = APPLICATION_IDENTIFIERS
+            .iter()
+            .find(|(prefix, length, _)| rest.starts_with(prefix) && rest.len() >= *length)?;
+        // An FNC1 can follow a short AI, where the AI would end.
+        let (ai, tail) = rest.split_at_checked(*length)?;
+        if !ai.chars().all(|c| c.is_ascii_digit()) {
+            return None;
+        }
+
+        let value_length = if PREDEFINED_LENGTH.contains(&&ai[..2]) {
+            format.max
+        } else {
+            tail.find(FNC1).unwrap_or(tail.len())
+        };
+        let value = tail
+            .get(..value_length)
+            .filter(|value| !value.contains(FNC1))?;
+
+        hri.push_str(&format!("({}){}", ai, value));
+        rest = &tail[value_length..];
+        rest = rest.strip_prefix(FNC1).unwrap_or(rest);
+    }
+
+    Some(hri)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        printer::{BarcodeOptions, BarcodeType, HriPosition},
+        Image,
+    };
+
+    #[test]
+    fn parses_hri() {
+        assert_eq!(
+            parse_hri("0109501101530003\u{e001}10ABC").as_deref(),
+            Some("(01)09501101530003(10)ABC")
+        );
+        assert_eq!(
+            parse_hri("\u{e001}10ABC\u{e001}3103000150").as_deref(),
+            Some("(10)ABC(3103)000150")
+        );
+    }
+
+    #[test]
+    fn refuses_fnc1_inside_an_ai() {
+        assert_eq!(parse_hri("31\u{e001}00"), None);
+        assert_eq!(parse_hri("7\u{e001}"), None);
+
+        // The software fallback shows the data as is.
+        let options = BarcodeOptions {
+            hri_position: HriPosition::Bottom,
+            ..BarcodeOptions::default()
+        };
+        assert!(Image::from_barcode("31\u{e001}00", BarcodeType::GS1128, &options).is_ok());
+    }
+
+    #[test]
+    fn knows_only_defined_location_numbers() {
+        let gln = "0614141000005";
+        for ai in ["410", "414", "417"] {
+            assert!(Gs1::new().add(ai, gln).is_ok(), "{}", ai);
+        }
+        for ai in ["418", "419"] {
+            assert!(matches!(
+                Gs1::new().add(ai, gln),
+                Err(Error::UnknownApplicationIdentifier(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn builds_element_strings() {
+        let mut gs1 = Gs1::new();
+        gs1.gtin("0950110153000")
+            .unwrap()
+            .batch("ABC123")
+            .unwrap()
+            .expiry(25, 12, 0)
+            .unwrap()
+            .net_weight_kg(1.5, 3)
+            .unwrap()
+            .serial("42")
+            .unwrap();
+
+        assert_eq!(
+            gs1.element_string(),
+            "0109501101530003\
+             10ABC123\u{e001}\
+             17251200\
+             3103001500\
+             2142"
+        );
+        assert_eq!(
+            gs1.hri(),
+            "(01)09501101530003(10)ABC123(17)251200(3103)001500(21)42"
+        );
+        assert_eq!(parse_hri(&gs1.element_string()), Some(gs1.hri()));
+    }
+
+    #[test]
+    fn refuses_invalid_elements() {
+        assert!(matches!(
+            Gs1::new().gtin("09501101530004"),
+            Err(Error::InvalidGs1Element {
+                reason: BarcodeError::InvalidCheckDigit { .. },
+                ..
+            })
+        ));
+        assert!(matches!(
+            Gs1::new().expiry(25, 2, 30),
+            Err(Error::InvalidGs1Element {
+                reason: BarcodeError::InvalidDate,
+                ..
+            })
+        ));
+        assert!(matches!(
+            Gs1::new().add("10", "é"),
+            Err(Error::InvalidGs1Element {
+                reason: BarcodeError::InvalidCharacter { .. },
+                ..
+            })
+        ));
+        assert!(matches!(
+            Gs1::new().net_weight_kg(1_000_000.0, 0),
+            Err(Error::InvalidGs1Element { .. })
+        ));
+        assert!(matches!(
+            Gs1::new().add("3170", "000100"),
+            Err(Error::UnknownApplicationIdentifier(_))
+        ));
+        assert!(matches!(
+            Gs1::new().add("3106", "000100"),
+            Err(Error::UnknownApplicationIdentifier(_))
+        ));
+    }
+}
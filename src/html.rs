@@ -0,0 +1,867 @@
+//! Receipts designed in HTML, printed with native commands where the printer
+//! has them and rasterized otherwise.
+//!
+//! The supported subset is:
+//!
+//! - `p`, `div`, `header`, `footer` and `section` blocks, and `br`.
+//! - `b`, `strong`, `u` and `ins` text, and `span` to apply a style.
+//! - `table` with `tr`, `td` and `th` cells, optionally in `thead`, `tbody`
+//!   and `tfoot`. Cells are printed as plain text, in columns sized with
+//!   `width: 30%` or `width="30%"` on the cells of the first row.
+//! - `img`, whose `src` is looked up in the images added with
+//!   [`Html::add_image`], or decoded from a `data:` URI with the `image`
+//!   feature. Its `alt` text is printed if there is no such image.
+//! - `hr`, printed as a line of `-` across the paper.
+//! - The `text-align` and `font-size` properties of blocks, and the
+//!   `font-weight` and `text-decoration` properties, in `style` attributes.
+//!   The `align` attribute works as `text-align`.
+//!
+//! Font sizes are relative to `medium`, printed with font A: `16px`, `12pt`,
+//! `1em` and `100%` are all `medium`. Sizes that are a whole multiple of it,
+//! up to the largest text size of the printer, are printed as native text,
+//! and smaller sizes in font B. Other sizes are drawn in an image. Other
+//! elements print their content, except `head`, `title`, `style` and
+//! `script`.
+
+use std::collections::HashMap;
+
+use crate::{
+    pixel::Pixel,
+    printer::{Align, BasePrinter, Printer, TextFont, TextSize},
+    profile::PrinterProfile,
+    receipt::{column_widths, table_row, Column},
+    wrap::{print_wrapped, wrap_styled, Emphasis, Styled},
+    Image,
+};
+
+/// A parsed HTML document.
+///
+/// # Example
+///
+/// ```ignore
+/// let mut html = Html::parse(
+///     r#"<div style="text-align: center">
+///          <img src="logo.png" alt="ACME">
+///          <p style="font-size: 200%"><b>ACME Coffee</b></p>
+///        </div>
+///        <hr>
+///        <table>
+///          <tr><th style="width: 70%">Item</th><th style="text-align: right">Price</th></tr>
+///          <tr><td>Espresso</td><td align="right">2.50</td></tr>
+///        </table>
+///        <p style="font-size: 1.5em; text-align: right">Total <u>2.50</u></p>"#,
+/// );
+/// html.add_image("logo.png", logo);
+///
+/// let mut printer = BasePrinter::new(PrinterProfile::EPSON_TM_T20);
+/// html.print(&mut printer);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct Html {
+    blocks: Vec<Block>,
+    images: HashMap<String, Image>,
+}
+
+#[derive(Clone, Debug)]
+enum Block {
+    Text {
+        text: Vec<Styled>,
+        align: Align,
+        scale: f32,
+    },
+    Rule,
+    Image {
+        src: String,
+        alt: String,
+        align: Align,
+    },
+    Table(Vec<Vec<Cell>>),
+}
+
+#[derive(Clone, Debug)]
+struct Cell {
+    text: String,
+    align: Align,
+    header: bool,
+    /// Width in percent of the line.
+    width: Option<f32>,
+}
+
+#[derive(Clone, Debug)]
+enum Node {
+    Element {
+        name: String,
+        attributes: Vec<(String, String)>,
+        children: Vec<Node>,
+    },
+    Text(String),
+}
+
+/// Style inherited by the content of an element.
+#[derive(Clone, Copy)]
+struct Context {
+    emphasis: Emphasis,
+    align: Align,
+    /// Font size relative to `medium`.
+    scale: f32,
+}
+
+const VOID: [&str; 7] = ["br", "hr", "img", "meta", "link", "input", "col"];
+const BLOCKS: [&str; 5] = ["p", "div", "header", "footer", "section"];
+const HIDDEN: [&str; 4] = ["head", "title", "style", "script"];
+
+/// The largest font size, 8 times `medium` as the largest size of `GS !`.
+const MAX_SCALE: f32 = 8.0;
+
+impl Html {
+    /// Parses `source`. Like browsers, the parser accepts any input, closing
+    /// the elements left open and ignoring stray end tags.
+    pub fn parse(source: &str) -> Self {
+        let nodes = parse_nodes(source);
+
+        let mut layout = Layout::default();
+        let context = Context {
+            emphasis: Emphasis::default(),
+            align: Align::Left,
+            scale: 1.0,
+        };
+        for node in &nodes {
+            layout.node(node, context);
+        }
+        layout.flush(context, false);
+
+        Self {
+            blocks: layout.blocks,
+            images: HashMap::new(),
+        }
+    }
+
+    /// Adds an image printed by `<img src="src">`.
+    pub fn add_image(&mut self, src: &str, image: Image) -> &mut Self {
+        self.images.insert(src.to_string(), image);
+        self
+    }
+
+    /// Prints the document. The text style and alignment of `printer` are
+    /// reset for each block and restored at the end.
+    pub fn print(&self, printer: &mut BasePrinter) {
+        for block in &self.blocks {
+            printer.push_style();
+            printer.reset_text_style();
+            self.print_block(printer, block);
+            printer.pop_style();
+        }
+    }
+
+    fn print_block(&self, printer: &mut BasePrinter, block: &Block) {
+        match block {
+            Block::Text { text, align, scale } => match native_size(*scale, printer.profile()) {
+                Some((font, size)) => {
+                    printer
+                        .set_align(*align)
+                        .set_text_font(font)
+                        .set_text_size(size, size);
+                    print_wrapped(printer, text, "", "");
+                }
+                None => {
+                    let lines = rasterize(
+                        text,
+                        *align,
+                        *scale,
+                        printer.profile(),
+                        printer.print_area_width(),
+                    );
+                    printer.set_align(Align::Left);
+                    for line in lines {
+                        printer.image(line);
+                    }
+                }
+            },
+            Block::Rule => {
+                let line: String =
+                    std::iter::repeat_n('-', printer.chars_per_line() as usize).collect();
+                printer.set_align(Align::Left).text(&line).new_line();
+            }
+            Block::Image { src, alt, align } => match self.image(src) {
+                Some(image) => {
+                    printer.set_align(*align).image(image);
+                }
+                None if !alt.is_empty() => {
+                    printer.set_align(*align).text(alt).new_line();
+                }
+                None => {}
+            },
+            Block::Table(rows) => print_table(printer, rows),
+        }
+    }
+
+    fn image(&self, src: &str) -> Option<Image> {
+        if let Some(image) = self.images.get(src) {
+            return Some(image.clone());
+        }
+
+        #[cfg(feature = "image")]
+        if let Some(bytes) = data_uri(src) {
+            return ::image::load_from_memory(&bytes).ok().map(Image::from);
+        }
+
+        None
+    }
+}
+
+/// The font and size printing text of `scale`, if the printer has one.
+fn native_size(scale: f32, profile: &PrinterProfile) -> Option<(TextFont, TextSize)> {
+    if scale < 0.95 {
+        return profile
+            .chars_per_line(TextFont::B)
+            .map(|_| (TextFont::B, TextSize::Size1));
+    }
+
+    const SIZES: [TextSize; 8] = [
+        TextSize::Size1,
+        TextSize::Size2,
+        TextSize::Size3,
+        TextSize::Size4,
+        TextSize::Size5,
+        TextSize::Size6,
+        TextSize::Size7,
+        TextSize::Size8,
+    ];
+    let largest = SIZES
+        .iter()
+        .position(|size| *size == profile.max_text_size)
+        .unwrap_or(0);
+
+    let whole = scale.round();
+    if (scale - whole).abs() > 0.05 || whole as usize > largest + 1 {
+        return None;
+    }
+
+    Some((TextFont::A, SIZES[whole as usize - 1]))
+}
+
+/// Draws `text` with the dot font of the barcodes, in characters `scale`
+/// times the size of font A, wrapped to `width` dots. Each line is an image
+/// of its own, so that a long text doesn't make an image too high to print.
+fn rasterize(
+    text: &[Styled],
+    align: Align,
+    scale: f32,
+    profile: &PrinterProfile,
+    width: u16,
+) -> Vec<Image> {
+    use crate::font::GLYPH_HEIGHT;
+
+    // Font A characters are twice as high as they are wide.
+    let chars = profile.chars_per_line(TextFont::A).unwrap_or(48).max(1);
+    let base = (profile.dots_per_line / chars).max(1) as f32;
+    let cell_width = ((base * scale).round() as usize).max(1);
+    let cell_height = ((base * 2.0 * scale).round() as usize).max(1);
+    let width = width as usize;
+
+    let black = Pixel::new(0, 0, 0, 255);
+    let white = Pixel::new(255, 255, 255, 255);
+
+    let lines = wrap_styled(text, (width / cell_width).max(1));
+    let mut images = Vec::with_capacity(lines.len());
+    for line in &lines {
+        let mut dark = vec![false; width * cell_height];
+        let space = width.saturating_sub(line.len() * cell_width);
+        let left = match align {
+            Align::Left => 0,
+            Align::Center => space / 2,
+            Align::Right => space,
+        };
+
+        for (i, &(character, emphasis)) in line.iter().enumerate() {
+            let boldness = if emphasis.bold { cell_width / 8 + 1 } else { 0 };
+            let left = left + i * cell_width;
+
+            let mut set = |x: usize, y: usize| {
+                for dx in 0..=boldness {
+                    if left + x + dx < width {
+                        dark[y * width + left + x + dx] = true;
+                    }
+                }
+            };
+            crate::font::draw(character, cell_width, cell_height, &mut set);
+
+            if emphasis.underline {
+                // The underline takes the spacing below the glyph.
+                for y in cell_height * GLYPH_HEIGHT / (GLYPH_HEIGHT + 1)..cell_height {
+                    for x in 0..cell_width {
+                        set(x, y);
+                    }
+                }
+            }
+        }
+
+        let pixels = dark
+            .into_iter()
+            .map(|dark| if dark { black } else { white })
+            .collect();
+        images.push(Image::new(pixels, width as u16, cell_height as u16));
+    }
+
+    images
+}
+
+fn print_table(printer: &mut BasePrinter, rows: &[Vec<Cell>]) {
+    printer.set_align(Align::Left);
+
+    let chars = printer.chars_per_line() as usize;
+    let count = rows.iter().map(Vec::len).max().unwrap_or(0);
+    let sizes: Vec<u16> = (0..count)
+        .map(|i| {
+            let width = rows.iter().find_map(|row| row.get(i)?.width);
+            width.map_or(0, |percent| {
+                ((chars as f32 * percent / 100.0).round() as u16).max(1)
+            })
+        })
+        .collect();
+    let sizing: Vec<Column> = sizes
+        .iter()
+        .map(|&width| Column {
+            width,
+            align: Align::Left,
+        })
+        .collect();
+    let widths = column_widths(&sizing, chars);
+
+    for row in rows {
+        let columns: Vec<Column> = sizes
+            .iter()
+            .enumerate()
+            .map(|(i, &width)| Column {
+                width,
+                align: row.get(i).map_or(Align::Left, |cell| cell.align),
+            })
+            .collect();
+        let cells: Vec<String> = row.iter().map(|cell| cell.text.clone()).collect();
+
+        printer.set_text_bold(!row.is_empty() && row.iter().all(|cell| cell.header));
+        for line in table_row(&columns, &widths, &cells) {
+            printer.text(&line).new_line();
+        }
+    }
+}
+
+/// Turns the document tree into blocks, collecting inline content in lines.
+#[derive(Default)]
+struct Layout {
+    blocks: Vec<Block>,
+    line: Vec<Styled>,
+}
+
+impl Layout {
+    fn node(&mut self, node: &Node, context: Context) {
+        let (name, attributes, children) = match node {
+            Node::Text(text) => {
+                for character in text.chars() {
+                    if character.is_whitespace() {
+                        if self.line.last().is_some_and(|(last, _)| *last != ' ') {
+                            self.line.push((' ', context.emphasis));
+                        }
+                    } else {
+                        self.line.push((character, context.emphasis));
+                    }
+                }
+                return;
+            }
+            Node::Element {
+                name,
+                attributes,
+                children,
+            } => (name.as_str(), attributes, children),
+        };
+
+        match name {
+            "br" => self.flush(context, true),
+            "hr" => {
+                self.flush(context, false);
+                self.blocks.push(Block::Rule);
+            }
+            "img" => {
+                self.flush(context, false);
+                self.blocks.push(Block::Image {
+                    src: attribute(attributes, "src").unwrap_or_default().to_string(),
+                    alt: attribute(attributes, "alt").unwrap_or_default().to_string(),
+                    align: context.align,
+                });
+            }
+            "table" => {
+                self.flush(context, false);
+                let mut rows = vec![];
+                table_rows(children, context, &mut rows);
+                self.blocks.push(Block::Table(rows));
+            }
+            _ if HIDDEN.contains(&name) => {}
+            _ => {
+                let block = BLOCKS.contains(&name);
+                if block {
+                    self.flush(context, false);
+                }
+
+                let inner = style(context, name, attributes, block);
+                for child in children {
+                    self.node(child, inner);
+                }
+
+                if block {
+                    self.flush(inner, false);
+                }
+            }
+        }
+    }
+
+    /// Ends the current line, printing an empty one if `empty` is set.
+    fn flush(&mut self, context: Context, empty: bool) {
+        if self.line.last().is_some_and(|(last, _)| *last == ' ') {
+            self.line.pop();
+        }
+        if self.line.is_empty() && !empty {
+            return;
+        }
+
+        self.blocks.push(Block::Text {
+            text: std::mem::take(&mut self.line),
+            align: context.align,
+            scale: context.scale,
+        });
+    }
+}
+
+fn table_rows(nodes: &[Node], context: Context, rows: &mut Vec<Vec<Cell>>) {
+    for node in nodes {
+        let Node::Element {
+            name,
+            attributes,
+            children,
+        } = node
+        else {
+            continue;
+        };
+
+        match name.as_str() {
+            "thead" | "tbody" | "tfoot" => {
+                let inner = style(context, name, attributes, true);
+                table_rows(children, inner, rows);
+            }
+            "tr" => {
+                let inner = style(context, name, attributes, true);
+                let cells = children
+                    .iter()
+                    .filter_map(|child| match child {
+                        Node::Element {
+                            name,
+                            attributes,
+                            children,
+                        } if name == "td" || name == "th" => {
+                            let header = name == "th";
+                            let mut cell = inner;
+                            if header {
+                                cell.align = Align::Center;
+                            }
+                            let cell = style(cell, name, attributes, true);
+
+                            let mut text = String::new();
+                            for child in children {
+                                text_content(child, &mut text);
+                            }
+
+                            Some(Cell {
+                                text: text.split_whitespace().collect::<Vec<_>>().join(" "),
+                                align: cell.align,
+                                header,
+                                width: width(attributes),
+                            })
+                        }
+                        _ => None,
+                    })
+                    .collect();
+                rows.push(cells);
+            }
+            _ => {}
+        }
+    }
+}
+
+fn text_content(node: &Node, text: &mut String) {
+    match node {
+        Node::Text(content) => text.push_str(content),
+        Node::Element { name, .. } if HIDDEN.contains(&name.as_str()) => {}
+        Node::Element { name, .. } if name == "br" => text.push(' '),
+        Node::Element { children, .. } => {
+            for child in children {
+                text_content(child, text);
+            }
+        }
+    }
+}
+
+fn attribute<'a>(attributes: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    attributes
+        .iter()
+        .find(|(candidate, _)| candidate == name)
+        .map(|(_, value)| value.as_str())
+}
+
+/// The declarations of the `style` attribute, with lowercase properties.
+fn declarations(attributes: &[(String, String)]) -> Vec<(String, String)> {
+    attribute(attributes, "style")
+        .unwrap_or_default()
+        .split(';')
+        .filter_map(|declaration| {
+            let (property, value) = declaration.split_once(':')?;
+            Some((
+                property.trim().to_ascii_lowercase(),
+                value.trim().to_ascii_lowercase(),
+            ))
+        })
+        .collect()
+}
+
+/// The context of the content of an element. Only blocks and table cells
+/// change the alignment and the font size.
+fn style(
+    mut context: Context,
+    name: &str,
+    attributes: &[(String, String)],
+    block: bool,
+) -> Context {
+    match name {
+        "b" | "strong" => context.emphasis.bold = true,
+        "u" | "ins" => context.emphasis.underline = true,
+        _ => {}
+    }
+
+    if block {
+        if let Some(align) = attribute(attributes, "align").and_then(text_align) {
+            context.align = align;
+        }
+    }
+
+    for (property, value) in declarations(attributes) {
+        match property.as_str() {
+            "text-align" if block => {
+                if let Some(align) = text_align(&value) {
+                    context.align = align;
+                }
+            }
+            "font-size" if block => {
+                if let Some(scale) = font_size(&value, context.scale) {
+                    context.scale = scale;
+                }
+            }
+            "font-weight" => match value.as_str() {
+                "bold" | "bolder" | "600" | "700" | "800" | "900" => context.emphasis.bold = true,
+                "normal" | "lighter" | "100" | "200" | "300" | "400" | "500" => {
+                    context.emphasis.bold = false
+                }
+                _ => {}
+            },
+            "text-decoration" | "text-decoration-line" => match value.as_str() {
+                "underline" => context.emphasis.underline = true,
+                "none" => context.emphasis.underline = false,
+                _ => {}
+            },
+            _ => {}
+        }
+    }
+
+    context
+}
+
+fn text_align(value: &str) -> Option<Align> {
+    match value.trim().to_ascii_lowercase().as_str() {
+        "left" | "start" | "justify" => Some(Align::Left),
+        "center" => Some(Align::Center),
+        "right" | "end" => Some(Align::Right),
+        _ => None,
+    }
+}
+
+/// A CSS font size relative to `medium`, in an element of font size
+/// `parent`, at most [`MAX_SCALE`].
+fn font_size(value: &str, parent: f32) -> Option<f32> {
+    let scale = match value {
+        "xx-small" => 0.6,
+        "x-small" => 0.75,
+        "small" => 0.89,
+        "medium" => 1.0,
+        "large" => 1.2,
+        "x-large" => 1.5,
+        "xx-large" => 2.0,
+        "xxx-large" => 3.0,
+        "smaller" => parent / 1.2,
+        "larger" => parent * 1.2,
+        _ => {
+            let unit = value.trim_start_matches(|c: char| c.is_ascii_digit() || c == '.');
+            let number: f32 = value[..value.len() - unit.len()].parse().ok()?;
+            match unit {
+                "px" => number / 16.0,
+                "pt" => number / 12.0,
+                "em" => number * parent,
+                "rem" => number,
+                "%" => number / 100.0 * parent,
+                _ => return None,
+            }
+        }
+    };
+
+    (scale > 0.0).then_some(scale.min(MAX_SCALE))
+}
+
+/// The width of a table cell in percent, from its `width` attribute or
+/// property.
+fn width(attributes: &[(String, String)]) -> Option<f32> {
+    let property = declarations(attributes)
+        .into_iter()
+        .find(|(property, _)| property == "width")
+        .map(|(_, value)| value);
+    let value = property.or_else(|| attribute(attributes, "width").map(str::to_string))?;
+
+    value.trim().strip_suffix('%')?.trim().parse().ok()
+}
+
+/// Decodes the data of a base64 `data:` URI.
+#[cfg(feature = "image")]
+fn data_uri(src: &str) -> Option<Vec<u8>> {
+    let (header, data) = src.strip_prefix("data:")?.split_once(',')?;
+    if !header.ends_with(";base64") {
+        return None;
+    }
+
+    let mut bytes = vec![];
+    let mut buffer = 0u32;
+    let mut bits = 0;
+    for byte in data.bytes() {
+        let value = match byte {
+            b'A'..=b'Z' => byte - b'A',
+            b'a'..=b'z' => byte - b'a' + 26,
+            b'0'..=b'9' => byte - b'0' + 52,
+            b'+' | b'-' => 62,
+            b'/' | b'_' => 63,
+            b'=' | b' ' | b'\n' | b'\r' | b'\t' => continue,
+            _ => return None,
+        };
+        buffer = buffer << 6 | value as u32;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((buffer >> bits) as u8);
+        }
+    }
+
+    Some(bytes)
+}
+
+/// Parses `source` into a tree of elements and text.
+fn parse_nodes(source: &str) -> Vec<Node> {
+    let mut parser = Parser::default();
+    let mut rest = source;
+
+    while !rest.is_empty() {
+        if let Some(comment) = rest.strip_prefix("<!--") {
+            rest = comment.find("-->").map_or("", |end| &comment[end + 3..]);
+        } else if rest.starts_with("<!") || rest.starts_with("<?") {
+            rest = rest.find('>').map_or("", |end| &rest[end + 1..]);
+        } else if let Some(tag) = rest.strip_prefix("</") {
+            let end = tag.find('>').unwrap_or(tag.len());
+            parser.close(&tag[..end].trim().to_ascii_lowercase());
+            rest = tag.get(end + 1..).unwrap_or("");
+        } else if rest.starts_with('<') && rest[1..].starts_with(|c: char| c.is_ascii_alphabetic())
+        {
+            let (name, attributes, closed, after) = parse_tag(&rest[1..]);
+            rest = after;
+
+            if name == "script" || name == "style" {
+                // Their content isn't HTML.
+                let end = rest.to_ascii_lowercase().find(&format!("</{}", name));
+                rest = end.map_or("", |end| &rest[end..]);
+                continue;
+            }
+
+            parser.open(name, attributes);
+            if closed || VOID.contains(&parser.current()) {
+                let name = parser.current().to_string();
+                parser.close(&name);
+            }
+        } else {
+            let first = rest.chars().next().map_or(0, char::len_utf8);
+            let end = rest[first..]
+                .find('<')
+                .map_or(rest.len(), |end| end + first);
+            parser.text(&decode_entities(&rest[..end]));
+            rest = &rest[end..];
+        }
+    }
+
+    parser.finish()
+}
+
+/// Parses a start tag after its `<`, returning its name, its attributes,
+/// whether it ends with `/>` and what follows it.
+fn parse_tag(tag: &str) -> (String, Vec<(String, String)>, bool, &str) {
+    let is_name = |c: char| c.is_ascii_alphanumeric() || c == '-' || c == ':' || c == '_';
+    let end = tag.find(|c: char| !is_name(c)).unwrap_or(tag.len());
+    let name = tag[..end].to_ascii_lowercase();
+    let mut rest = &tag[end..];
+    let mut attributes = vec![];
+
+    loop {
+        rest = rest.trim_start();
+        if let Some(after) = rest.strip_prefix("/>") {
+            return (name, attributes, true, after);
+        }
+        if let Some(after) = rest.strip_prefix('>') {
+            return (name, attributes, false, after);
+        }
+        if rest.is_empty() {
+            return (name, attributes, false, rest);
+        }
+
+        let end = rest
+            .find(|c: char| c.is_whitespace() || c == '=' || c == '>' || c == '/')
+            .unwrap_or(rest.len());
+        if end == 0 {
+            // A stray `/` or `=`.
+            rest = &rest[1..];
+            continue;
+        }
+        let key = rest[..end].to_ascii_lowercase();
+        rest = rest[end..].trim_start();
+
+        let mut value = String::new();
+        if let Some(after) = rest.strip_prefix('=') {
+            let after = after.trim_start();
+            let (raw, next) = match after.chars().next() {
+                Some(quote @ ('"' | '\'')) => {
+                    let inner = &after[1..];
+                    let end = inner.find(quote).unwrap_or(inner.len());
+                    (&inner[..end], inner.get(end + 1..).unwrap_or(""))
+                }
+                _ => {
+                    let end = after
+                        .find(|c: char| c.is_whitespace() || c == '>')
+                        .unwrap_or(after.len());
+                    (&after[..end], &after[end..])
+                }
+            };
+            value = decode_entities(raw);
+            rest = next;
+        }
+        attributes.push((key, value));
+    }
+}
+
+fn decode_entities(text: &str) -> String {
+    let mut decoded = String::new();
+    let mut rest = text;
+
+    while let Some(start) = rest.find('&') {
+        decoded.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let entity = rest[1..]
+            .find(';')
+            .filter(|end| *end <= 10)
+            .map(|end| &rest[1..end + 1]);
+        let character = entity.and_then(|entity| match entity {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            "nbsp" => Some(' '),
+            _ => {
+                let number = entity.strip_prefix('#')?;
+                let code = match number.strip_prefix(['x', 'X']) {
+                    Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+                    None => number.parse().ok()?,
+                };
+                char::from_u32(code)
+            }
+        });
+
+        match (entity, character) {
+            (Some(entity), Some(character)) => {
+                decoded.push(character);
+                rest = &rest[entity.len() + 2..];
+            }
+            _ => {
+                decoded.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    decoded.push_str(rest);
+
+    decoded
+}
+
+/// An element whose end tag hasn't been read: its name, attributes and
+/// children.
+type OpenElement = (String, Vec<(String, String)>, Vec<Node>);
+
+/// The elements being parsed, and the nodes outside of them.
+#[derive(Default)]
+struct Parser {
+    nodes: Vec<Node>,
+    open: Vec<OpenElement>,
+}
+
+impl Parser {
+    fn current(&self) -> &str {
+        self.open.last().map_or("", |(name, _, _)| name)
+    }
+
+    fn open(&mut self, name: String, attributes: Vec<(String, String)>) {
+        // Elements whose end tag can be left out are closed by the next one.
+        let closes: &[&str] = match name.as_str() {
+            "p" | "div" | "header" | "footer" | "section" | "table" | "hr" => &["p"],
+            "tr" => &["td", "th", "tr"],
+            "td" | "th" => &["td", "th"],
+            "thead" | "tbody" | "tfoot" => &["td", "th", "tr", "thead", "tbody", "tfoot"],
+            _ => &[],
+        };
+        while closes.contains(&self.current()) {
+            self.pop();
+        }
+
+        self.open.push((name, attributes, vec![]));
+    }
+
+    fn close(&mut self, name: &str) {
+        if self.open.iter().any(|(open, _, _)| open == name) {
+            while self.pop().is_some_and(|closed| closed != name) {}
+        }
+    }
+
+    /// Closes the current element, returning its name.
+    fn pop(&mut self) -> Option<String> {
+        let (name, attributes, children) = self.open.pop()?;
+        self.push(Node::Element {
+            name: name.clone(),
+            attributes,
+            children,
+        });
+        Some(name)
+    }
+
+    fn push(&mut self, node: Node) {
+        match self.open.last_mut() {
+            Some((_, _, children)) => children.push(node),
+            None => self.nodes.push(node),
+        }
+    }
+
+    fn text(&mut self, text: &str) {
+        self.push(Node::Text(text.to_string()));
+    }
+
+    fn finish(mut self) -> Vec<Node> {
+        while self.pop().is_some() {}
+        self.nodes
+    }
+}
@@ -1,5 +1,24 @@
-use crate::pixel::{Pixel, DEFAULT_PIXEL};
+use crate::{
+    barcode,
+    error::{BarcodeError, Error},
+    pixel::{Pixel, DEFAULT_PIXEL},
+    printer::{BarcodeOptions, BarcodeType, QRCodeOptions},
+    render,
+};
 
+const BLACK: Pixel = Pixel::new(0, 0, 0, 255);
+const WHITE: Pixel = Pixel::new(255, 255, 255, 255);
+
+/// An image, as RGBA pixels row by row.
+///
+/// It is serialized as its `width`, its `height` and its pixels as `data`,
+/// four bytes each packed and encoded in base64.
+#[derive(Clone, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(into = "ImageData", try_from = "ImageData")
+)]
 pub struct Image {
     data: Vec<Pixel>,
     width: u16,
@@ -15,6 +34,38 @@ impl Image {
         }
     }
 
+    /// Renders a barcode the way a printer prints it, with the human
+    /// readable interpretation placed as `options.hri_position` asks. The
+    /// data is checked like [`crate::Printer::barcode`] does.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let mut options = BarcodeOptions::default();
+    /// options.hri_position = HriPosition::Bottom;
+    ///
+    /// let image = Image::from_barcode("4006381333931", BarcodeType::JAN13, &options)?;
+    /// ```
+    pub fn from_barcode(
+        data: &str,
+        r#type: BarcodeType,
+        options: &BarcodeOptions,
+    ) -> Result<Self, Error> {
+        let data = barcode::validate(data, r#type)?;
+
+        render::barcode(&data, r#type, options, u16::MAX).ok_or(Error::InvalidBarcode {
+            r#type,
+            reason: BarcodeError::TooLong,
+        })
+    }
+
+    /// Renders a QR code with the model, error correction level, cell size
+    /// and ECI of `options`, or returns an error if `data` doesn't fit in a
+    /// symbol. Model 1 symbols are rendered as model 2.
+    pub fn from_qr_code(data: impl AsRef<[u8]>, options: &QRCodeOptions) -> Result<Self, Error> {
+        render::qr_code(data.as_ref(), options)
+    }
+
     pub const fn height(&self) -> u16 {
         self.height
     }
@@ -23,13 +74,60 @@ impl Image {
         self.width
     }
 
+    /// Converts the image to black and white dots with Floyd-Steinberg error
+    /// diffusion, so that grays and photos keep their shades once printed.
+    /// Transparent pixels are taken as white.
+    pub fn dither(&self) -> Image {
+        let (width, height) = (self.width as usize, self.height as usize);
+
+        let mut levels: Vec<i16> = (0..width * height)
+            .map(|i| {
+                let pixel = self.data.get(i).copied().unwrap_or(DEFAULT_PIXEL);
+                let luminance = (pixel.red() as u32 * 299
+                    + pixel.green() as u32 * 587
+                    + pixel.blue() as u32 * 114)
+                    / 1000;
+                // Blended over white paper.
+                let alpha = pixel.alpha() as u32;
+                (255 - alpha * (255 - luminance) / 255) as i16
+            })
+            .collect();
+
+        let mut data = Vec::with_capacity(width * height);
+        for y in 0..height {
+            for x in 0..width {
+                let level = levels[y * width + x];
+                let (pixel, error) = if level < 128 {
+                    (BLACK, level)
+                } else {
+                    (WHITE, level - 255)
+                };
+                data.push(pixel);
+
+                let mut spread = |x: usize, y: usize, weight: i16| {
+                    if x < width && y < height {
+                        levels[y * width + x] += error * weight / 16;
+                    }
+                };
+                spread(x + 1, y, 7);
+                if x > 0 {
+                    spread(x - 1, y + 1, 3);
+                }
+                spread(x, y + 1, 5);
+                spread(x + 1, y + 1, 1);
+            }
+        }
+
+        Image::new(data, self.width, self.height)
+    }
+
     pub fn to_raster(&self) -> Vec<u8> {
         let pixels = self.get_pixels();
 
         let mut raster = Vec::new();
 
         for i in 0..self.height {
-            for j in 0..(self.width + 7) / 8 {
+            for j in 0..self.width.div_ceil(8) {
                 let mut byte = 0x00;
                 for k in 0..8 {
                     let pixel = pixels
@@ -53,8 +151,8 @@ impl Image {
         for i in 0..self.height {
             let mut line = Vec::new();
             for j in 0..self.width {
-                let index = (self.width * i + j) << 2;
-                line.push(self.data[index as usize]);
+                let index = self.width as usize * i as usize + j as usize;
+                line.push(self.data.get(index).copied().unwrap_or(DEFAULT_PIXEL));
             }
             pixels.push(line);
         }
@@ -62,11 +160,69 @@ impl Image {
     }
 }
 
+/// The serialized form of an [`Image`].
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct ImageData {
+    width: u16,
+    height: u16,
+    data: String,
+}
+
+#[cfg(feature = "serde")]
+impl From<Image> for ImageData {
+    fn from(image: Image) -> Self {
+        use base64::Engine;
+
+        let bytes: Vec<u8> = image
+            .get_pixels()
+            .into_iter()
+            .flatten()
+            .flat_map(|pixel| [pixel.red(), pixel.green(), pixel.blue(), pixel.alpha()])
+            .collect();
+
+        ImageData {
+            width: image.width,
+            height: image.height,
+            data: base64::engine::general_purpose::STANDARD.encode(bytes),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<ImageData> for Image {
+    type Error = String;
+
+    fn try_from(image: ImageData) -> Result<Self, String> {
+        use base64::Engine;
+
+        let bytes = base64::engine::general_purpose::STANDARD
+            .decode(&image.data)
+            .map_err(|error| format!("invalid image data: {}", error))?;
+        let pixels = image.width as usize * image.height as usize;
+        if bytes.len() != pixels * 4 {
+            return Err(format!(
+                "expected {} pixels for a {}x{} image, got {} bytes",
+                pixels,
+                image.width,
+                image.height,
+                bytes.len()
+            ));
+        }
+
+        let data = bytes
+            .chunks_exact(4)
+            .map(|pixel| Pixel::new(pixel[0], pixel[1], pixel[2], pixel[3]))
+            .collect();
+        Ok(Image::new(data, image.width, image.height))
+    }
+}
+
 #[cfg(feature = "image")]
-impl Into<Image> for ::image::DynamicImage {
-    fn into(self) -> Image {
+impl From<::image::DynamicImage> for Image {
+    fn from(image: ::image::DynamicImage) -> Self {
         // Convert the image to an RGBA image buffer
-        let rgba_img = self.to_rgba8();
+        let rgba_img = image.to_rgba8();
 
         // Get width and height of the image
         let width = rgba_img.width();
@@ -0,0 +1,200 @@
+mod tspl;
+mod zpl;
+
+pub use tspl::TsplPrinter;
+pub use zpl::ZplPrinter;
+
+use crate::{
+    barcode,
+    error::Error,
+    printer::{
+        BarcodeOptions, BarcodeType, BarcodeWidth, Length, QRCodeCellSize, QRCodeOptions, TextSize,
+    },
+    render, Image,
+};
+
+/// An element of a [`Label`], placed at an absolute position measured from
+/// the top left corner of the label.
+pub enum LabelItem {
+    Text {
+        x: Length,
+        y: Length,
+        data: String,
+        width: TextSize,
+        height: TextSize,
+    },
+    Image {
+        x: Length,
+        y: Length,
+        image: Image,
+    },
+    Barcode {
+        x: Length,
+        y: Length,
+        data: String,
+        r#type: BarcodeType,
+        options: BarcodeOptions,
+    },
+    QRCode {
+        x: Length,
+        y: Length,
+        data: String,
+        options: QRCodeOptions,
+    },
+}
+
+/// The layout of a label, which can be printed by any [`LabelPrinter`].
+///
+/// # Example
+///
+/// ```ignore
+/// let mut label = Label::new(Length::Millimeters(50.0), Length::Millimeters(30.0));
+/// label
+///     .text(Length::Millimeters(2.0), Length::Millimeters(2.0), "Apples", TextSize::Size2, TextSize::Size2)
+///     .barcode(Length::Millimeters(2.0), Length::Millimeters(10.0), "4006381333931", BarcodeType::JAN13, BarcodeOptions::default());
+///
+/// let data = ZplPrinter::default().print_label(&label, 1)?.get_data();
+/// ```
+pub struct Label {
+    pub width: Length,
+    pub height: Length,
+    /// Gap between two labels on the roll.
+    pub gap: Length,
+    pub items: Vec<LabelItem>,
+}
+
+impl Label {
+    pub fn new(width: Length, height: Length) -> Self {
+        Self {
+            width,
+            height,
+            gap: Length::Millimeters(2.0),
+            items: vec![],
+        }
+    }
+
+    pub fn text(
+        &mut self,
+        x: Length,
+        y: Length,
+        data: &str,
+        width: TextSize,
+        height: TextSize,
+    ) -> &mut Self {
+        self.items.push(LabelItem::Text {
+            x,
+            y,
+            data: data.to_string(),
+            width,
+            height,
+        });
+        self
+    }
+
+    pub fn image(&mut self, x: Length, y: Length, image: Image) -> &mut Self {
+        self.items.push(LabelItem::Image { x, y, image });
+        self
+    }
+
+    pub fn barcode(
+        &mut self,
+        x: Length,
+        y: Length,
+        data: &str,
+        r#type: BarcodeType,
+        options: BarcodeOptions,
+    ) -> &mut Self {
+        self.items.push(LabelItem::Barcode {
+            x,
+            y,
+            data: data.to_string(),
+            r#type,
+            options,
+        });
+        self
+    }
+
+    pub fn qrcode(
+        &mut self,
+        x: Length,
+        y: Length,
+        data: &str,
+        options: QRCodeOptions,
+    ) -> &mut Self {
+        self.items.push(LabelItem::QRCode {
+            x,
+            y,
+            data: data.to_string(),
+            options,
+        });
+        self
+    }
+}
+
+/// A printer that prints whole labels with absolutely positioned content,
+/// as opposed to the line oriented [`crate::Printer`].
+pub trait LabelPrinter {
+    /// Prints `copies` of `label`. Items the printer can't draw itself are
+    /// rasterized. Returns an error, and prints nothing, if the data of a
+    /// barcode is invalid, or a barcode or QR code can't be rendered.
+    fn print_label(&mut self, label: &Label, copies: u16) -> Result<&mut Self, Error>;
+    fn raw(&mut self, data: &[u8]) -> &mut Self;
+    fn get_data(&self) -> Vec<u8>;
+    fn clear(&mut self) -> &mut Self;
+}
+
+/// Checks the data of the barcodes of `label` against the rules of their
+/// symbology, like [`crate::Printer::barcode`] does, and that the data of
+/// its QR codes fits in a symbol.
+fn validate(label: &Label) -> Result<(), Error> {
+    for item in &label.items {
+        match item {
+            LabelItem::Barcode { data, r#type, .. } => {
+                barcode::validate(data, *r#type)?;
+            }
+            LabelItem::QRCode { data, options, .. } => {
+                render::qr_symbol(data.as_bytes(), options)?;
+            }
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
+
+fn text_multiplier(size: TextSize) -> u8 {
+    match size {
+        TextSize::Size1 => 1,
+        TextSize::Size2 => 2,
+        TextSize::Size3 => 3,
+        TextSize::Size4 => 4,
+        TextSize::Size5 => 5,
+        TextSize::Size6 => 6,
+        TextSize::Size7 => 7,
+        TextSize::Size8 => 8,
+    }
+}
+
+fn module_width(width: &BarcodeWidth) -> u8 {
+    match width {
+        BarcodeWidth::Width1 => 1,
+        BarcodeWidth::Width2 => 2,
+        BarcodeWidth::Width3 => 3,
+        BarcodeWidth::Width4 => 4,
+        BarcodeWidth::Width5 => 5,
+        BarcodeWidth::Width6 => 6,
+    }
+}
+
+fn cell_size(size: &QRCodeCellSize) -> u8 {
+    match size {
+        QRCodeCellSize::Size1 => 1,
+        QRCodeCellSize::Size2 => 2,
+        QRCodeCellSize::Size3 => 3,
+        QRCodeCellSize::Size4 => 4,
+        QRCodeCellSize::Size5 => 5,
+        QRCodeCellSize::Size6 => 6,
+        QRCodeCellSize::Size7 => 7,
+        QRCodeCellSize::Size8 => 8,
+    }
+}
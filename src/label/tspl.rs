@@ -0,0 +1,310 @@
+use crate::{
+    barcode::FNC1,
+    error::Error,
+    printer::{BarcodeType, HriPosition, Length, QRCodeCorrection, QRCodeModel},
+    profile::DEFAULT_DOTS_PER_MM,
+    render, Image,
+};
+
+use super::{cell_size, module_width, text_multiplier, validate, Label, LabelItem, LabelPrinter};
+
+/// A label printer speaking TSPL, the command language of TSC printers and
+/// many compatible label printers.
+pub struct TsplPrinter {
+    cmds: Vec<u8>,
+    dots_per_mm: f32,
+}
+
+impl Default for TsplPrinter {
+    fn default() -> Self {
+        Self::new(DEFAULT_DOTS_PER_MM)
+    }
+}
+
+impl TsplPrinter {
+    /// Creates a printer with a resolution of `dots_per_mm` (8 for 203 dpi,
+    /// 12 for 300 dpi).
+    pub fn new(dots_per_mm: f32) -> Self {
+        Self {
+            cmds: vec![],
+            dots_per_mm,
+        }
+    }
+
+    fn dots(&self, length: &Length) -> i32 {
+        length.to_dots(self.dots_per_mm).max(0)
+    }
+
+    fn mm(&self, length: &Length) -> f32 {
+        match length {
+            Length::Millimeters(mm) => *mm,
+            Length::Dots(dots) => *dots as f32 / self.dots_per_mm,
+        }
+    }
+
+    fn line(&mut self, line: &str) {
+        self.cmds.extend_from_slice(line.as_bytes());
+        self.cmds.extend_from_slice(b"\r\n");
+    }
+
+    fn bitmap(&mut self, x: i32, y: i32, image: &Image) {
+        let width = image.width().div_ceil(8);
+        self.cmds.extend_from_slice(
+            format!("BITMAP {},{},{},{},0,", x, y, width, image.height()).as_bytes(),
+        );
+        // TSPL prints the dots whose bits are cleared.
+        self.cmds
+            .extend(image.to_raster().into_iter().map(|byte| !byte));
+        self.cmds.extend_from_slice(b"\r\n");
+    }
+}
+
+/// Quotes `data` as a TSPL string, escaping embedded double quotes.
+fn quote(data: &str) -> String {
+    format!("\"{}\"", data.replace('"', "\\[\"]"))
+}
+
+impl TsplPrinter {
+    /// Writes the commands printing `label`, returning an error if a
+    /// symbol printed in software can't be rendered.
+    fn write_label(&mut self, label: &Label, copies: u16) -> Result<(), Error> {
+        let (width, height, gap) = (
+            self.mm(&label.width),
+            self.mm(&label.height),
+            self.mm(&label.gap),
+        );
+        self.line(&format!("SIZE {} mm,{} mm", width, height));
+        self.line(&format!("GAP {} mm,0 mm", gap));
+        self.line("DIRECTION 0");
+        self.line("CODEPAGE UTF-8");
+        self.line("CLS");
+
+        for item in &label.items {
+            match item {
+                LabelItem::Text {
+                    x,
+                    y,
+                    data,
+                    width,
+                    height,
+                } => {
+                    let (x, y) = (self.dots(x), self.dots(y));
+                    self.line(&format!(
+                        "TEXT {},{},\"3\",0,{},{},{}",
+                        x,
+                        y,
+                        text_multiplier(*width),
+                        text_multiplier(*height),
+                        quote(data)
+                    ));
+                }
+                LabelItem::Image { x, y, image } => {
+                    let (x, y) = (self.dots(x), self.dots(y));
+                    self.bitmap(x, y, image);
+                }
+                LabelItem::Barcode {
+                    x,
+                    y,
+                    data,
+                    r#type,
+                    options,
+                } => {
+                    let (x, y) = (self.dots(x), self.dots(y));
+                    let narrow = module_width(&options.width);
+
+                    let (code, wide) = match r#type {
+                        BarcodeType::UPCA => ("UPCA", narrow),
+                        BarcodeType::UPCE => ("UPCE", narrow),
+                        BarcodeType::JAN13 => ("EAN13", narrow),
+                        BarcodeType::JAN8 => ("EAN8", narrow),
+                        BarcodeType::CODE39 => ("39", narrow * 3),
+                        BarcodeType::ITF => ("25", narrow * 3),
+                        BarcodeType::CODABAR => ("CODA", narrow * 3),
+                        BarcodeType::CODE93 => ("93", narrow),
+                        BarcodeType::CODE128 => ("128", narrow),
+                        // TSPL can't separate GS1 fields with FNC1.
+                        BarcodeType::GS1128 if !data.contains(FNC1) => ("EAN128", narrow),
+                        _ => {
+                            let image = Image::from_barcode(data, *r#type, options)?;
+                            self.bitmap(x, y, &image);
+                            continue;
+                        }
+                    };
+
+                    // TSPL prints the human readable text below the barcode
+                    // only.
+                    let readable = match options.hri_position {
+                        HriPosition::None => 0,
+                        _ => 2,
+                    };
+
+                    self.line(&format!(
+                        "BARCODE {},{},\"{}\",{},{},0,{},{},{}",
+                        x,
+                        y,
+                        code,
+                        options.height,
+                        readable,
+                        narrow,
+                        wide,
+                        quote(data)
+                    ));
+                }
+                LabelItem::QRCode {
+                    x,
+                    y,
+                    data,
+                    options,
+                } => {
+                    let (x, y) = (self.dots(x), self.dots(y));
+
+                    let model = match options.model {
+                        QRCodeModel::Model1 if options.eci.is_none() => "M1",
+                        QRCodeModel::Model2 if options.eci.is_none() => "M2",
+                        // Micro QR codes and ECI headers are rendered in
+                        // software.
+                        _ => {
+                            let image = render::qr_code(data.as_bytes(), options)?;
+                            self.bitmap(x, y, &image);
+                            continue;
+                        }
+                    };
+
+                    let level = match options.correction {
+                        QRCodeCorrection::L => "L",
+                        QRCodeCorrection::M => "M",
+                        QRCodeCorrection::Q => "Q",
+                        QRCodeCorrection::H => "H",
+                    };
+
+                    self.line(&format!(
+                        "QRCODE {},{},{},{},A,0,{},{}",
+                        x,
+                        y,
+                        level,
+                        cell_size(&options.cell_size),
+                        model,
+                        quote(data)
+                    ));
+                }
+            }
+        }
+
+        self.line(&format!("PRINT {}", copies.max(1)));
+
+        Ok(())
+    }
+}
+
+impl LabelPrinter for TsplPrinter {
+    fn print_label(&mut self, label: &Label, copies: u16) -> Result<&mut Self, Error> {
+        validate(label)?;
+
+        let start = self.cmds.len();
+        if let Err(error) = self.write_label(label, copies) {
+            self.cmds.truncate(start);
+            return Err(error);
+        }
+
+        Ok(self)
+    }
+
+    fn raw(&mut self, data: &[u8]) -> &mut Self {
+        self.cmds.extend_from_slice(data);
+        self
+    }
+
+    fn get_data(&self) -> Vec<u8> {
+        self.cmds.clone()
+    }
+
+    fn clear(&mut self) -> &mut Self {
+        self.cmds.clear();
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::printer::{BarcodeOptions, QRCodeOptions, TextSize};
+
+    fn print(label: &Label) -> String {
+        let data = TsplPrinter::new(8.0)
+            .print_label(label, 2)
+            .unwrap()
+            .get_data();
+        String::from_utf8_lossy(&data).into_owned()
+    }
+
+    #[test]
+    fn prints_labels() {
+        let mut label = Label::new(Length::Millimeters(50.0), Length::Millimeters(30.0));
+        label
+            .text(
+                Length::Dots(16),
+                Length::Dots(16),
+                "Say \"hi\"",
+                TextSize::Size1,
+                TextSize::Size2,
+            )
+            .barcode(
+                Length::Millimeters(2.0),
+                Length::Millimeters(10.0),
+                "400638133393",
+                BarcodeType::JAN13,
+                BarcodeOptions::default(),
+            )
+            .qrcode(
+                Length::Dots(300),
+                Length::Dots(16),
+                "abc",
+                QRCodeOptions::default(),
+            );
+
+        assert_eq!(
+            print(&label),
+            "SIZE 50 mm,30 mm\r\n\
+             GAP 2 mm,0 mm\r\n\
+             DIRECTION 0\r\n\
+             CODEPAGE UTF-8\r\n\
+             CLS\r\n\
+             TEXT 16,16,\"3\",0,1,2,\"Say \\[\"]hi\\[\"]\"\r\n\
+             BARCODE 16,80,\"EAN13\",162,0,0,3,3,\"400638133393\"\r\n\
+             QRCODE 300,16,L,3,A,0,M2,\"abc\"\r\n\
+             PRINT 2\r\n"
+        );
+    }
+
+    #[test]
+    fn draws_gs1_fields_in_software() {
+        let mut label = Label::new(Length::Dots(400), Length::Dots(200));
+        label.barcode(
+            Length::Dots(16),
+            Length::Dots(16),
+            "10ABC\u{e001}2142",
+            BarcodeType::GS1128,
+            BarcodeOptions::default(),
+        );
+
+        let data = print(&label);
+        assert!(data.contains("CLS\r\nBITMAP 16,16,"));
+        assert!(!data.contains("BARCODE"));
+    }
+
+    #[test]
+    fn prints_nothing_for_invalid_labels() {
+        let mut label = Label::new(Length::Dots(400), Length::Dots(200));
+        label.barcode(
+            Length::Dots(0),
+            Length::Dots(0),
+            "4006381333932",
+            BarcodeType::JAN13,
+            BarcodeOptions::default(),
+        );
+
+        let mut printer = TsplPrinter::new(8.0);
+        assert!(printer.print_label(&label, 1).is_err());
+        assert!(printer.get_data().is_empty());
+    }
+}
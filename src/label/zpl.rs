@@ -0,0 +1,395 @@
+use crate::{
+    barcode::{FNC1, FNC2, FNC3, FNC4},
+    error::Error,
+    printer::{BarcodeType, HriPosition, Length, QRCodeCorrection, QRCodeModel},
+    profile::DEFAULT_DOTS_PER_MM,
+    render, Image,
+};
+
+use super::{cell_size, module_width, text_multiplier, validate, Label, LabelItem, LabelPrinter};
+
+/// Height of a line of text at normal size, in dots.
+const TEXT_HEIGHT: u32 = 24;
+
+/// A label printer speaking ZPL II, the command language of Zebra printers.
+pub struct ZplPrinter {
+    cmds: Vec<u8>,
+    dots_per_mm: f32,
+}
+
+impl Default for ZplPrinter {
+    fn default() -> Self {
+        Self::new(DEFAULT_DOTS_PER_MM)
+    }
+}
+
+impl ZplPrinter {
+    /// Creates a printer with a resolution of `dots_per_mm` (8 for 203 dpi,
+    /// 12 for 300 dpi).
+    pub fn new(dots_per_mm: f32) -> Self {
+        Self {
+            cmds: vec![],
+            dots_per_mm,
+        }
+    }
+
+    fn dots(&self, length: &Length) -> i32 {
+        length.to_dots(self.dots_per_mm).max(0)
+    }
+
+    fn push(&mut self, command: &str) {
+        self.cmds.extend_from_slice(command.as_bytes());
+    }
+
+    fn field(&mut self, x: i32, y: i32, format: &str, data: &str) {
+        self.push(&format!(
+            "^FO{},{}{}^FH^FD{}^FS\n",
+            x,
+            y,
+            format,
+            escape(data)
+        ));
+    }
+
+    fn graphic(&mut self, x: i32, y: i32, image: &Image) {
+        let raster = image.to_raster();
+        let row = image.width().div_ceil(8);
+        let hex: String = raster.iter().map(|byte| format!("{:02X}", byte)).collect();
+
+        self.push(&format!(
+            "^FO{},{}^GFA,{},{},{},{}^FS\n",
+            x,
+            y,
+            raster.len(),
+            raster.len(),
+            row,
+            hex
+        ));
+    }
+}
+
+/// Escapes the characters that ZPL would take as commands, using the `^FH`
+/// hexadecimal indicator `_`.
+fn escape(data: &str) -> String {
+    let mut escaped = String::with_capacity(data.len());
+    for c in data.chars() {
+        match c {
+            '_' => escaped.push_str("_5F"),
+            '^' => escaped.push_str("_5E"),
+            '~' => escaped.push_str("_7E"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// The field data of a `^BC` symbol, with `>` escaped as `><` and the
+/// function characters as their invocation codes. GS1-128 data starts with
+/// FNC1, in code set C (`>;`) if it is made of pairs of digits and in code
+/// set B (`>:`) otherwise.
+fn code128(data: &str, gs1: bool) -> String {
+    let mut field = String::with_capacity(data.len() + 4);
+    if gs1 {
+        let pairs = data.split(FNC1).all(|digits| {
+            digits.len() % 2 == 0 && digits.bytes().all(|byte| byte.is_ascii_digit())
+        });
+        field.push_str(if pairs { ">;>8" } else { ">:>8" });
+    }
+
+    for c in data.chars() {
+        match c {
+            '>' => field.push_str("><"),
+            FNC1 => field.push_str(">8"),
+            FNC2 => field.push_str(">3"),
+            FNC3 => field.push_str(">2"),
+            // FNC4 in code set B, which `^BC` starts with.
+            FNC4 => field.push_str(">6"),
+            c => field.push(c),
+        }
+    }
+    field
+}
+
+impl ZplPrinter {
+    /// Writes the commands printing `label`, returning an error if a
+    /// symbol printed in software can't be rendered.
+    fn write_label(&mut self, label: &Label, copies: u16) -> Result<(), Error> {
+        let (width, height) = (self.dots(&label.width), self.dots(&label.height));
+
+        // ^CI28 selects UTF-8.
+        self.push(&format!("^XA\n^CI28\n^PW{}\n^LL{}\n", width, height));
+
+        for item in &label.items {
+            match item {
+                LabelItem::Text {
+                    x,
+                    y,
+                    data,
+                    width,
+                    height,
+                } => {
+                    let (x, y) = (self.dots(x), self.dots(y));
+                    let format = format!(
+                        "^A0N,{},{}",
+                        TEXT_HEIGHT * text_multiplier(*height) as u32,
+                        TEXT_HEIGHT * text_multiplier(*width) as u32
+                    );
+                    self.field(x, y, &format, data);
+                }
+                LabelItem::Image { x, y, image } => {
+                    let (x, y) = (self.dots(x), self.dots(y));
+                    self.graphic(x, y, image);
+                }
+                LabelItem::Barcode {
+                    x,
+                    y,
+                    data,
+                    r#type,
+                    options,
+                } => {
+                    let (x, y) = (self.dots(x), self.dots(y));
+
+                    let (line, above) = match options.hri_position {
+                        HriPosition::None => ("N", "N"),
+                        HriPosition::Top => ("Y", "Y"),
+                        HriPosition::Bottom | HriPosition::TopBottom => ("Y", "N"),
+                    };
+                    let h = options.height;
+
+                    let (symbol, data) = match r#type {
+                        BarcodeType::UPCA => {
+                            (format!("^BUN,{},{},{},Y", h, line, above), data.clone())
+                        }
+                        BarcodeType::UPCE => {
+                            (format!("^B9N,{},{},{},Y", h, line, above), data.clone())
+                        }
+                        BarcodeType::JAN13 => {
+                            (format!("^BEN,{},{},{}", h, line, above), data.clone())
+                        }
+                        BarcodeType::JAN8 => {
+                            (format!("^B8N,{},{},{}", h, line, above), data.clone())
+                        }
+                        // ZPL adds the start and stop characters.
+                        BarcodeType::CODE39 => (
+                            format!("^B3N,N,{},{},{}", h, line, above),
+                            data.trim_matches('*').to_string(),
+                        ),
+                        BarcodeType::ITF => {
+                            (format!("^B2N,{},{},{},N", h, line, above), data.clone())
+                        }
+                        BarcodeType::CODABAR => {
+                            // The start and stop characters are parameters.
+                            let (start, stop) = (&data[..1], &data[data.len() - 1..]);
+                            (
+                                format!(
+                                    "^BKN,N,{},{},{},{},{}",
+                                    h,
+                                    line,
+                                    above,
+                                    start.to_ascii_uppercase(),
+                                    stop.to_ascii_uppercase()
+                                ),
+                                data[1..data.len() - 1].to_string(),
+                            )
+                        }
+                        BarcodeType::CODE93 => {
+                            (format!("^BAN,{},{},{},N", h, line, above), data.clone())
+                        }
+                        BarcodeType::CODE128 => (
+                            format!("^BCN,{},{},{},N,N", h, line, above),
+                            code128(data, false),
+                        ),
+                        BarcodeType::GS1128 => (
+                            format!("^BCN,{},{},{},N,N", h, line, above),
+                            code128(data, true),
+                        ),
+                        BarcodeType::GS1DataBarOmnidirectional => (
+                            format!("^BRN,1,{},1,{},22", module_width(&options.width), h),
+                            data.clone(),
+                        ),
+                        BarcodeType::GS1DataBarTruncated => (
+                            format!("^BRN,2,{},1,{},22", module_width(&options.width), h),
+                            data.clone(),
+                        ),
+                        BarcodeType::GS1DataBarLimited => (
+                            format!("^BRN,5,{},1,{},22", module_width(&options.width), h),
+                            data.clone(),
+                        ),
+                        BarcodeType::GS1DataBarExpanded => (
+                            format!("^BRN,6,{},1,{},22", module_width(&options.width), h),
+                            data.clone(),
+                        ),
+                    };
+
+                    let format = format!("^BY{},3,{}{}", module_width(&options.width), h, symbol);
+                    self.field(x, y, &format, &data);
+                }
+                LabelItem::QRCode {
+                    x,
+                    y,
+                    data,
+                    options,
+                } => {
+                    let (x, y) = (self.dots(x), self.dots(y));
+
+                    let model = match options.model {
+                        QRCodeModel::Model1 if options.eci.is_none() => 1,
+                        QRCodeModel::Model2 if options.eci.is_none() => 2,
+                        // Micro QR codes and ECI headers are rendered in
+                        // software.
+                        _ => {
+                            let image = render::qr_code(data.as_bytes(), options)?;
+                            self.graphic(x, y, &image);
+                            continue;
+                        }
+                    };
+
+                    let level = match options.correction {
+                        QRCodeCorrection::L => 'L',
+                        QRCodeCorrection::M => 'M',
+                        QRCodeCorrection::Q => 'Q',
+                        QRCodeCorrection::H => 'H',
+                    };
+
+                    let format = format!("^BQN,{},{}", model, cell_size(&options.cell_size));
+                    // The data starts with the error correction level and
+                    // automatic mode selection.
+                    self.field(x, y, &format, &format!("{}A,{}", level, data));
+                }
+            }
+        }
+
+        self.push(&format!("^PQ{}\n^XZ\n", copies.max(1)));
+
+        Ok(())
+    }
+}
+
+impl LabelPrinter for ZplPrinter {
+    fn print_label(&mut self, label: &Label, copies: u16) -> Result<&mut Self, Error> {
+        validate(label)?;
+
+        let start = self.cmds.len();
+        if let Err(error) = self.write_label(label, copies) {
+            self.cmds.truncate(start);
+            return Err(error);
+        }
+
+        Ok(self)
+    }
+
+    fn raw(&mut self, data: &[u8]) -> &mut Self {
+        self.cmds.extend_from_slice(data);
+        self
+    }
+
+    fn get_data(&self) -> Vec<u8> {
+        self.cmds.clone()
+    }
+
+    fn clear(&mut self) -> &mut Self {
+        self.cmds.clear();
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::printer::{BarcodeOptions, QRCodeOptions, TextSize};
+
+    fn label(item: LabelItem) -> String {
+        let mut label = Label::new(Length::Dots(400), Length::Dots(200));
+        label.items.push(item);
+        let data = ZplPrinter::new(8.0)
+            .print_label(&label, 1)
+            .unwrap()
+            .get_data();
+        String::from_utf8(data).unwrap()
+    }
+
+    fn barcode(data: &str, r#type: BarcodeType) -> String {
+        label(LabelItem::Barcode {
+            x: Length::Dots(10),
+            y: Length::Dots(20),
+            data: data.to_string(),
+            r#type,
+            options: BarcodeOptions::default(),
+        })
+    }
+
+    #[test]
+    fn passes_codabar_start_and_stop_as_parameters() {
+        assert_eq!(
+            barcode("a40156b", BarcodeType::CODABAR),
+            "^XA\n^CI28\n^PW400\n^LL200\n\
+             ^FO10,20^BY3,3,162^BKN,N,162,N,N,A,B^FH^FD40156^FS\n\
+             ^PQ1\n^XZ\n"
+        );
+    }
+
+    #[test]
+    fn leaves_out_code39_start_and_stop() {
+        let field = "^FO10,20^BY3,3,162^B3N,N,162,N,N^FH^FDCODE 39^FS\n";
+        assert!(barcode("*CODE 39*", BarcodeType::CODE39).contains(field));
+        assert!(barcode("CODE 39", BarcodeType::CODE39).contains(field));
+    }
+
+    #[test]
+    fn refuses_qr_codes_over_capacity() {
+        let mut label = Label::new(Length::Dots(400), Length::Dots(200));
+        label.qrcode(
+            Length::Dots(0),
+            Length::Dots(0),
+            &"A".repeat(8000),
+            QRCodeOptions::default(),
+        );
+        let mut printer = ZplPrinter::new(8.0);
+        assert!(printer.print_label(&label, 1).is_err());
+        assert!(printer.get_data().is_empty());
+    }
+
+    #[test]
+    fn prints_text_and_qr_codes() {
+        assert!(label(LabelItem::Text {
+            x: Length::Dots(10),
+            y: Length::Dots(20),
+            data: "5^_off~".to_string(),
+            width: TextSize::Size1,
+            height: TextSize::Size2,
+        })
+        .contains("^FO10,20^A0N,48,24^FH^FD5_5E_5Foff_7E^FS\n"));
+
+        assert!(label(LabelItem::QRCode {
+            x: Length::Dots(10),
+            y: Length::Dots(20),
+            data: "abc".to_string(),
+            options: QRCodeOptions::default(),
+        })
+        .contains("^FO10,20^BQN,2,3^FH^FDLA,abc^FS\n"));
+    }
+
+    #[test]
+    fn starts_gs1_128_with_fnc1() {
+        assert!(barcode("0109501101530003", BarcodeType::GS1128)
+            .contains("^BCN,162,N,N,N,N^FH^FD>;>80109501101530003^FS\n"));
+        assert!(barcode("10AB>C\u{e001}2142", BarcodeType::GS1128)
+            .contains("^BCN,162,N,N,N,N^FH^FD>:>810AB><C>82142^FS\n"));
+    }
+
+    #[test]
+    fn draws_micro_qr_codes_in_software() {
+        let options = QRCodeOptions {
+            model: QRCodeModel::Micro,
+            ..QRCodeOptions::default()
+        };
+        let data = label(LabelItem::QRCode {
+            x: Length::Dots(10),
+            y: Length::Dots(20),
+            data: "12345".to_string(),
+            options,
+        });
+        assert!(data.contains("^FO10,20^GFA,"));
+        assert!(!data.contains("^BQ"));
+    }
+}
@@ -1,15 +1,64 @@
+mod barcode;
 mod character_set;
 mod command;
 mod constants;
+#[cfg(feature = "cups")]
+mod cups;
+mod databar;
+mod decode;
+mod error;
+mod font;
+mod gs1;
+mod html;
 mod image;
+mod label;
+mod markdown;
+mod payload;
 mod pixel;
+mod preview;
 mod printer;
+mod profile;
+mod receipt;
+mod render;
+#[cfg(feature = "server")]
+mod server;
+mod star;
+mod state;
+mod status;
+#[cfg(feature = "template")]
+mod template;
+mod transport;
+mod wrap;
 
+pub use barcode::{FNC1, FNC2, FNC3, FNC4};
 pub use character_set::CharacterSet;
+#[cfg(feature = "cups")]
+pub use cups::{CupsRaster, RasterPage};
+pub use decode::{decode, Command, Instruction};
+pub use error::{BarcodeError, Error, QRCodeError};
+pub use gs1::Gs1;
+pub use html::Html;
 pub use image::Image;
+pub use label::{Label, LabelItem, LabelPrinter, TsplPrinter, ZplPrinter};
+pub use markdown::Markdown;
+pub use payload::{EmvcoPayment, SepaTransfer, TrackedUrl, VCard, WifiNetwork, WifiSecurity};
 pub use pixel::{Pixel, DEFAULT_PIXEL};
+pub use preview::preview;
 pub use printer::{
-    Align, BarcodeOptions, BarcodeType, BarcodeWidth, BasePrinter, CashDrawerPin, HriPosition,
-    Printer, QRCodeCellSize, QRCodeCorrection, QRCodeModel, QRCodeOptions, TextFont, TextSize,
+    Align, AztecMode, AztecOptions, BarcodeOptions, BarcodeType, BarcodeWidth, BasePrinter,
+    CashDrawerPin, DataMatrixOptions, DataMatrixSize, HriPosition, Length, MaxiCodeMode,
+    MaxiCodeOptions, Pdf417Correction, Pdf417Options, PrintMode, Printer, QRCodeCellSize,
+    QRCodeCorrection, QRCodeModel, QRCodeOptions, TextColor, TextFont, TextSize, TextStyle,
     TextUnderline,
 };
+pub use profile::{
+    Buzzer, CodePage, CutFeed, PrinterProfile, DEFAULT_DOTS_PER_LINE, DEFAULT_DOTS_PER_MM,
+};
+pub use receipt::{Column, Receipt, ReceiptItem};
+#[cfg(feature = "server")]
+pub use server::{PrintServer, RunningServer};
+pub use star::StarPrinter;
+pub use status::DrawerStatus;
+#[cfg(feature = "template")]
+pub use template::Template;
+pub use transport::{Connection, Destination};
@@ -0,0 +1,339 @@
+//! Markdown printed with the text styles of the printer, for notices and
+//! kitchen tickets written by hand.
+//!
+//! The supported syntax is:
+//!
+//! - `# Heading` in double width and height, `## Heading` in double height
+//!   and deeper headings in normal size, all in bold.
+//! - `**bold**` and `__underlined__` text, and `\` to escape `*` and `_`.
+//! - `-`, `*`, `+` and `1.` lists, nested by indenting them with 2 spaces,
+//!   with the lines of an item indented after its marker.
+//! - `---`, `***` and `___` rules, printed as a line of `-` across the paper.
+//! - Fenced code blocks, printed as written in font B.
+//!
+//! Paragraphs and list items are wrapped on spaces to the width of the
+//! paper, and blank lines are printed as one empty line.
+
+use crate::{
+    printer::{Align, Printer, TextFont, TextSize},
+    wrap::{print_wrapped, Emphasis, Styled},
+};
+
+/// A parsed Markdown document.
+///
+/// # Example
+///
+/// ```ignore
+/// let ticket = Markdown::parse(
+///     "# Table 12\n\
+///      \n\
+///      - 2 x **Burger**, __no onions__\n\
+///      - 1 x Fries\n\
+///      \n\
+///      ---\n\
+///      ```\n\
+///      Waiter: Sam  19:42\n\
+///      ```",
+/// );
+/// ticket.print(&mut printer);
+/// printer.cut(false);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct Markdown {
+    blocks: Vec<Block>,
+}
+
+#[derive(Clone, Debug)]
+enum Block {
+    Heading {
+        level: usize,
+        text: Vec<Styled>,
+    },
+    Paragraph(Vec<Styled>),
+    Item {
+        depth: usize,
+        marker: String,
+        text: Vec<Styled>,
+    },
+    Rule,
+    Code(Vec<String>),
+    Blank,
+}
+
+/// A block whose text can go on over the next lines.
+enum Open {
+    Paragraph(String),
+    Item {
+        depth: usize,
+        marker: String,
+        text: String,
+    },
+}
+
+impl Markdown {
+    pub fn parse(source: &str) -> Self {
+        let mut blocks = vec![];
+        let mut open = None;
+        let mut code: Option<Vec<String>> = None;
+
+        for line in source.lines() {
+            let line = line.replace('\t', "    ");
+            let trimmed = line.trim();
+
+            if let Some(lines) = &mut code {
+                if trimmed.starts_with("```") {
+                    blocks.push(Block::Code(std::mem::take(lines)));
+                    code = None;
+                } else {
+                    lines.push(line.trim_end().to_string());
+                }
+                continue;
+            }
+
+            if trimmed.starts_with("```") {
+                close(&mut blocks, open.take());
+                code = Some(vec![]);
+            } else if trimmed.is_empty() {
+                close(&mut blocks, open.take());
+                if !matches!(blocks.last(), None | Some(Block::Blank)) {
+                    blocks.push(Block::Blank);
+                }
+            } else if is_rule(trimmed) {
+                close(&mut blocks, open.take());
+                blocks.push(Block::Rule);
+            } else if let Some((level, text)) = heading(trimmed) {
+                close(&mut blocks, open.take());
+                blocks.push(Block::Heading {
+                    level,
+                    text: inline(text),
+                });
+            } else if let Some((marker, text)) = list_marker(trimmed) {
+                close(&mut blocks, open.take());
+                let indent = line.len() - line.trim_start().len();
+                open = Some(Open::Item {
+                    depth: indent / 2,
+                    marker,
+                    text: text.to_string(),
+                });
+            } else {
+                match &mut open {
+                    Some(Open::Paragraph(text) | Open::Item { text, .. }) => {
+                        text.push(' ');
+                        text.push_str(trimmed);
+                    }
+                    None => open = Some(Open::Paragraph(trimmed.to_string())),
+                }
+            }
+        }
+
+        close(&mut blocks, open);
+        if let Some(lines) = code {
+            blocks.push(Block::Code(lines));
+        }
+        if let Some(Block::Blank) = blocks.last() {
+            blocks.pop();
+        }
+
+        Self { blocks }
+    }
+
+    /// Prints the document. The text style of `printer` is reset for each
+    /// block and restored at the end, and headings and paragraphs keep its
+    /// alignment.
+    pub fn print<P: Printer>(&self, printer: &mut P) {
+        for block in &self.blocks {
+            printer.push_style();
+            printer.reset_text_style();
+            print_block(printer, block);
+            printer.pop_style();
+        }
+    }
+}
+
+fn close(blocks: &mut Vec<Block>, open: Option<Open>) {
+    match open {
+        Some(Open::Paragraph(text)) => blocks.push(Block::Paragraph(inline(&text))),
+        Some(Open::Item {
+            depth,
+            marker,
+            text,
+        }) => blocks.push(Block::Item {
+            depth,
+            marker,
+            text: inline(&text),
+        }),
+        None => {}
+    }
+}
+
+/// Whether `line` is 3 or more `-`, `*` or `_`, optionally separated by
+/// spaces.
+fn is_rule(line: &str) -> bool {
+    let mut characters = line.chars().filter(|character| *character != ' ');
+    let Some(first) = characters.next() else {
+        return false;
+    };
+
+    matches!(first, '-' | '*' | '_')
+        && characters.clone().all(|character| character == first)
+        && characters.count() >= 2
+}
+
+/// The level and the text of a heading.
+fn heading(line: &str) -> Option<(usize, &str)> {
+    let level = line
+        .chars()
+        .take_while(|character| *character == '#')
+        .count();
+    let text = &line[level..];
+    if !(1..=6).contains(&level) || !(text.is_empty() || text.starts_with(' ')) {
+        return None;
+    }
+
+    // Closing hashes are optional.
+    let text = text.trim();
+    let text = match text.trim_end_matches('#') {
+        rest if rest.is_empty() || rest.ends_with(' ') => rest.trim_end(),
+        _ => text,
+    };
+
+    Some((level, text))
+}
+
+/// The marker printed for a list item, and the text of the item.
+fn list_marker(line: &str) -> Option<(String, &str)> {
+    if let Some(text) = ["- ", "* ", "+ "]
+        .iter()
+        .find_map(|marker| line.strip_prefix(marker))
+    {
+        return Some(("- ".to_string(), text.trim_start()));
+    }
+
+    let digits = line.chars().take_while(char::is_ascii_digit).count();
+    let rest = &line[digits..];
+    if (1..=9).contains(&digits) && (rest.starts_with(". ") || rest.starts_with(") ")) {
+        return Some((format!("{}. ", &line[..digits]), rest[2..].trim_start()));
+    }
+
+    None
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Piece {
+    Character(char),
+    Bold,
+    Underline,
+}
+
+/// Reads the emphasis of `text`, collapsing spaces. A marker that isn't
+/// closed is printed as written.
+fn inline(text: &str) -> Vec<Styled> {
+    let mut pieces = vec![];
+    let mut characters = text.chars().peekable();
+    while let Some(character) = characters.next() {
+        match character {
+            '\\' if characters.peek().is_some_and(char::is_ascii_punctuation) => {
+                pieces.push(Piece::Character(characters.next().unwrap()));
+            }
+            '*' | '_' if characters.peek() == Some(&character) => {
+                characters.next();
+                pieces.push(match character {
+                    '*' => Piece::Bold,
+                    _ => Piece::Underline,
+                });
+            }
+            _ => pieces.push(Piece::Character(character)),
+        }
+    }
+
+    for (marker, literal) in [(Piece::Bold, '*'), (Piece::Underline, '_')] {
+        let count = pieces.iter().filter(|piece| **piece == marker).count();
+        if count % 2 == 1 {
+            let last = pieces.iter().rposition(|piece| *piece == marker).unwrap();
+            pieces.splice(last..=last, [Piece::Character(literal); 2]);
+        }
+    }
+
+    let mut emphasis = Emphasis::default();
+    let mut styled: Vec<Styled> = vec![];
+    for piece in pieces {
+        match piece {
+            Piece::Bold => emphasis.bold = !emphasis.bold,
+            Piece::Underline => emphasis.underline = !emphasis.underline,
+            Piece::Character(character) if character.is_whitespace() => {
+                if styled.last().is_some_and(|(last, _)| *last != ' ') {
+                    styled.push((' ', emphasis));
+                }
+            }
+            Piece::Character(character) => styled.push((character, emphasis)),
+        }
+    }
+    if styled.last().is_some_and(|(last, _)| *last == ' ') {
+        styled.pop();
+    }
+
+    styled
+}
+
+fn print_block<P: Printer>(printer: &mut P, block: &Block) {
+    match block {
+        Block::Heading { level, text } => {
+            let height = match level {
+                1 | 2 => TextSize::Size2,
+                _ => TextSize::Size1,
+            };
+            let width = match level {
+                1 => TextSize::Size2,
+                _ => TextSize::Size1,
+            };
+            printer.set_text_size(width, height);
+
+            let bold: Vec<Styled> = text
+                .iter()
+                .map(|&(character, emphasis)| {
+                    let bold = Emphasis {
+                        bold: true,
+                        ..emphasis
+                    };
+                    (character, bold)
+                })
+                .collect();
+            print_wrapped(printer, &bold, "", "");
+        }
+        Block::Paragraph(text) => print_wrapped(printer, text, "", ""),
+        Block::Item {
+            depth,
+            marker,
+            text,
+        } => {
+            printer.set_align(Align::Left);
+            let indent = " ".repeat(depth * 2);
+            let first = format!("{}{}", indent, marker);
+            let hanging = " ".repeat(first.chars().count());
+            print_wrapped(printer, text, &first, &hanging);
+        }
+        Block::Rule => {
+            let line: String =
+                std::iter::repeat_n('-', printer.chars_per_line() as usize).collect();
+            printer.set_align(Align::Left).text(&line).new_line();
+        }
+        Block::Code(lines) => {
+            printer.set_align(Align::Left).set_text_font(TextFont::B);
+
+            // Long lines are broken where the paper ends, keeping the spaces.
+            let width = (printer.chars_per_line() as usize).max(1);
+            for line in lines {
+                let characters: Vec<char> = line.chars().collect();
+                if characters.is_empty() {
+                    printer.new_line();
+                }
+                for chunk in characters.chunks(width) {
+                    printer.text(&chunk.iter().collect::<String>()).new_line();
+                }
+            }
+        }
+        Block::Blank => {
+            printer.new_line();
+        }
+    }
+}
@@ -0,0 +1,930 @@
+//! Builders of the payloads of special purpose QR codes: payments, Wi-Fi
+//! networks, contacts and tracked URLs.
+//!
+//! Each builder checks its fields as they are set and formats them with
+//! `payload`, which is printed with [`crate::Printer::qrcode`].
+
+use crate::{
+    barcode::{check_chars, check_length},
+    error::{BarcodeError, Error},
+};
+
+fn invalid(field: &'static str) -> impl Fn(BarcodeError) -> Error {
+    move |reason| Error::InvalidQRCodeField { field, reason }
+}
+
+/// Checks that `value` has `min` to `max` characters, all of them allowed.
+fn check(
+    field: &'static str,
+    value: &str,
+    min: usize,
+    max: usize,
+    allowed: impl Fn(char) -> bool,
+) -> Result<(), Error> {
+    check_length(value, min, max)
+        .and_then(|_| check_chars(value, allowed))
+        .map_err(invalid(field))
+}
+
+fn check_id(field: &'static str, id: u8, min: u8, max: u8) -> Result<(), Error> {
+    if !(min..=max).contains(&id) {
+        return Err(Error::InvalidQRCodeField {
+            field,
+            reason: BarcodeError::OutOfRange {
+                min: min as u32,
+                max: max as u32,
+                value: id as u32,
+            },
+        });
+    }
+
+    Ok(())
+}
+
+fn is_printable(c: char) -> bool {
+    c == ' ' || c.is_ascii_graphic()
+}
+
+/// Formats `amount` with `decimals` digits after the decimal point, or
+/// returns `None` if it is negative or not a number.
+fn format_amount(amount: f64, decimals: u8) -> Option<String> {
+    if !(amount >= 0.0 && amount.is_finite()) {
+        return None;
+    }
+
+    Some(format!("{:.*}", decimals as usize, amount))
+}
+
+/// CRC-16/CCITT-FALSE, the checksum of EMVCo payloads.
+fn crc16(data: &[u8]) -> u16 {
+    data.iter().fold(0xffff, |crc, &byte| {
+        (0..8).fold(crc ^ (byte as u16) << 8, |crc, _| {
+            if crc & 0x8000 != 0 {
+                crc << 1 ^ 0x1021
+            } else {
+                crc << 1
+            }
+        })
+    })
+}
+
+/// Builder of EMVCo merchant-presented payment QR codes, made of data
+/// objects as ID, length and value, and ended by a CRC.
+///
+/// # Example
+///
+/// ```ignore
+/// let mut payment = EmvcoPayment::new("ACME Coffee", "Singapore", "SG", "702")?;
+/// payment
+///     .merchant_account_template("SG.PAYNOW", &[(1, "2"), (2, "201403121W")])?
+///     .amount(4.5, 2)?
+///     .reference_label("INV-1042")?;
+///
+/// printer.qrcode(payment.payload(), QRCodeOptions::default())?;
+/// ```
+#[derive(Clone, Debug)]
+pub struct EmvcoPayment {
+    accounts: Vec<(u8, String)>,
+    category_code: String,
+    currency: String,
+    amount: Option<String>,
+    country: String,
+    name: String,
+    city: String,
+    postal_code: Option<String>,
+    additional_data: Vec<(u8, String)>,
+}
+
+impl EmvcoPayment {
+    /// Starts a payment to the merchant `name` in `city`, with the ISO 3166
+    /// alpha-2 `country` code and the ISO 4217 numeric `currency` code.
+    pub fn new(name: &str, city: &str, country: &str, currency: &str) -> Result<Self, Error> {
+        check("merchant name", name, 1, 25, is_printable)?;
+        check("merchant city", city, 1, 15, is_printable)?;
+        check("country code", country, 2, 2, |c| c.is_ascii_uppercase())?;
+        check("currency", currency, 3, 3, |c| c.is_ascii_digit())?;
+
+        Ok(Self {
+            accounts: Vec::new(),
+            category_code: "0000".to_string(),
+            currency: currency.to_string(),
+            amount: None,
+            country: country.to_string(),
+            name: name.to_string(),
+            city: city.to_string(),
+            postal_code: None,
+            additional_data: Vec::new(),
+        })
+    }
+
+    /// Adds the merchant account information with the ID `id` (2 to 51),
+    /// for IDs reserved to a payment network. The value is written as is.
+    pub fn merchant_account(&mut self, id: u8, value: &str) -> Result<&mut Self, Error> {
+        check_id("merchant account ID", id, 2, 51)?;
+        check("merchant account", value, 1, 99, is_printable)?;
+
+        self.accounts.retain(|(account, _)| *account != id);
+        self.accounts.push((id, value.to_string()));
+        Ok(self)
+    }
+
+    /// Adds a merchant account information template (IDs 26 to 51, the
+    /// first one free), made of the globally unique identifier `guid` of
+    /// the payment system and its `fields` as ID and value.
+    pub fn merchant_account_template(
+        &mut self,
+        guid: &str,
+        fields: &[(u8, &str)],
+    ) -> Result<&mut Self, Error> {
+        check("globally unique identifier", guid, 1, 32, is_printable)?;
+
+        let mut template = data_object(0, guid);
+        for &(id, value) in fields {
+            check_id("merchant account field ID", id, 1, 99)?;
+            check("merchant account", value, 1, 99, is_printable)?;
+            template.push_str(&data_object(id, value));
+        }
+
+        let id = (26..=51)
+            .find(|id| self.accounts.iter().all(|(account, _)| account != id))
+            .ok_or(Error::InvalidQRCodeField {
+                field: "merchant account",
+                reason: BarcodeError::TooLong,
+            })?;
+        self.merchant_account(id, &template)
+    }
+
+    /// Sets the ISO 18245 merchant category code, `0000` by default.
+    pub fn category_code(&mut self, code: &str) -> Result<&mut Self, Error> {
+        check("merchant category code", code, 4, 4, |c| c.is_ascii_digit())?;
+        self.category_code = code.to_string();
+        Ok(self)
+    }
+
+    /// Sets the amount, with `decimals` digits after the decimal point as
+    /// the currency has. Payments without an amount let the payer enter it,
+    /// and are encoded as static QR codes that can be reused.
+    pub fn amount(&mut self, amount: f64, decimals: u8) -> Result<&mut Self, Error> {
+        let amount = format_amount(amount, decimals).ok_or(Error::InvalidQRCodeField {
+            field: "amount",
+            reason: BarcodeError::InvalidAmount,
+        })?;
+        check("amount", &amount, 1, 13, |_| true)?;
+
+        self.amount = Some(amount);
+        Ok(self)
+    }
+
+    /// Sets the postal code of the merchant.
+    pub fn postal_code(&mut self, postal_code: &str) -> Result<&mut Self, Error> {
+        check("postal code", postal_code, 1, 10, is_printable)?;
+        self.postal_code = Some(postal_code.to_string());
+        Ok(self)
+    }
+
+    /// Adds the field `id` of the additional data template, such as 01 for
+    /// the bill number or 07 for the terminal label. The fields share the 99
+    /// characters of the template, each taking 4 more for its ID and length.
+    pub fn additional_data(&mut self, id: u8, value: &str) -> Result<&mut Self, Error> {
+        check_id("additional data ID", id, 1, 99)?;
+
+        let others: usize = self
+            .additional_data
+            .iter()
+            .filter(|(field, _)| *field != id)
+            .map(|(_, value)| 4 + value.chars().count())
+            .sum();
+        let max = 99usize.saturating_sub(others + 4).min(25);
+        check("additional data", value, 1, max, is_printable)?;
+
+        self.additional_data.retain(|(field, _)| *field != id);
+        self.additional_data.push((id, value.to_string()));
+        Ok(self)
+    }
+
+    /// Sets the bill number (additional data field 01).
+    pub fn bill_number(&mut self, number: &str) -> Result<&mut Self, Error> {
+        self.additional_data(1, number)
+    }
+
+    /// Sets the reference label (additional data field 05), identifying the
+    /// transaction.
+    pub fn reference_label(&mut self, label: &str) -> Result<&mut Self, Error> {
+        self.additional_data(5, label)
+    }
+
+    /// Sets the terminal label (additional data field 07).
+    pub fn terminal_label(&mut self, label: &str) -> Result<&mut Self, Error> {
+        self.additional_data(7, label)
+    }
+
+    /// Returns the payload, ended by its CRC.
+    pub fn payload(&self) -> String {
+        let mut payload = data_object(0, "01");
+        // Point of initiation: 11 for a static QR code, 12 for a dynamic one.
+        payload.push_str(&data_object(
+            1,
+            if self.amount.is_some() { "12" } else { "11" },
+        ));
+
+        let mut accounts = self.accounts.clone();
+        accounts.sort_by_key(|(id, _)| *id);
+        for (id, value) in &accounts {
+            payload.push_str(&data_object(*id, value));
+        }
+
+        payload.push_str(&data_object(52, &self.category_code));
+        payload.push_str(&data_object(53, &self.currency));
+        if let Some(amount) = &self.amount {
+            payload.push_str(&data_object(54, amount));
+        }
+        payload.push_str(&data_object(58, &self.country));
+        payload.push_str(&data_object(59, &self.name));
+        payload.push_str(&data_object(60, &self.city));
+        if let Some(postal_code) = &self.postal_code {
+            payload.push_str(&data_object(61, postal_code));
+        }
+
+        if !self.additional_data.is_empty() {
+            let mut fields = self.additional_data.clone();
+            fields.sort_by_key(|(id, _)| *id);
+            let template: String = fields
+                .iter()
+                .map(|(id, value)| data_object(*id, value))
+                .collect();
+            payload.push_str(&data_object(62, &template));
+        }
+
+        // The CRC covers its own ID and length.
+        payload.push_str("6304");
+        let crc = crc16(payload.as_bytes());
+        payload.push_str(&format!("{:04X}", crc));
+
+        payload
+    }
+}
+
+fn data_object(id: u8, value: &str) -> String {
+    format!("{:02}{:02}{}", id, value.chars().count(), value)
+}
+
+/// Builder of EPC QR codes, the SEPA credit transfers scanned by banking
+/// apps. The EPC asks for them to be printed with error correction level M.
+///
+/// # Example
+///
+/// ```ignore
+/// let mut transfer = SepaTransfer::new("Red Cross", "BE71 0961 2345 6769")?;
+/// transfer.bic("BPOTBEB1")?.amount(25.0)?.text("Donation")?;
+///
+/// let mut options = QRCodeOptions::default();
+/// options.correction = QRCodeCorrection::M;
+/// printer.qrcode(transfer.payload(), options)?;
+/// ```
+#[derive(Clone, Debug)]
+pub struct SepaTransfer {
+    bic: Option<String>,
+    name: String,
+    iban: String,
+    amount: Option<String>,
+    purpose: Option<String>,
+    remittance: Remittance,
+    information: Option<String>,
+}
+
+#[derive(Clone, Debug)]
+enum Remittance {
+    None,
+    Reference(String),
+    Text(String),
+}
+
+impl SepaTransfer {
+    /// Starts a transfer to the beneficiary `name` with the account `iban`,
+    /// given with or without spaces.
+    pub fn new(name: &str, iban: &str) -> Result<Self, Error> {
+        check("beneficiary name", name, 1, 70, |c| !c.is_control())?;
+
+        let iban: String = iban
+            .chars()
+            .filter(|c| *c != ' ')
+            .map(|c| c.to_ascii_uppercase())
+            .collect();
+        check("IBAN", &iban, 15, 34, |c| c.is_ascii_alphanumeric())?;
+        check_iban(&iban)?;
+
+        Ok(Self {
+            bic: None,
+            name: name.to_string(),
+            iban,
+            amount: None,
+            purpose: None,
+            remittance: Remittance::None,
+            information: None,
+        })
+    }
+
+    /// Sets the BIC of the bank of the beneficiary, which is optional in
+    /// the EEA.
+    pub fn bic(&mut self, bic: &str) -> Result<&mut Self, Error> {
+        check("BIC", bic, 8, 11, |c| c.is_ascii_alphanumeric())?;
+        if bic.len() == 9 || bic.len() == 10 {
+            return Err(Error::InvalidQRCodeField {
+                field: "BIC",
+                reason: BarcodeError::InvalidLength {
+                    min: 8,
+                    max: 11,
+                    length: bic.len(),
+                },
+            });
+        }
+
+        self.bic = Some(bic.to_ascii_uppercase());
+        Ok(self)
+    }
+
+    /// Sets the amount in euros, from 0.01 to 999999999.99.
+    pub fn amount(&mut self, amount: f64) -> Result<&mut Self, Error> {
+        let amount = format_amount(amount, 2)
+            .filter(|amount| amount != "0.00" && amount.len() <= 12)
+            .ok_or(Error::InvalidQRCodeField {
+                field: "amount",
+                reason: BarcodeError::InvalidAmount,
+            })?;
+
+        self.amount = Some(amount);
+        Ok(self)
+    }
+
+    /// Sets the four letter ISO 20022 purpose code, such as `CHAR` for a
+    /// charity payment.
+    pub fn purpose(&mut self, purpose: &str) -> Result<&mut Self, Error> {
+        check("purpose", purpose, 4, 4, |c| c.is_ascii_uppercase())?;
+        self.purpose = Some(purpose.to_string());
+        Ok(self)
+    }
+
+    /// Sets the structured creditor reference, like `RF18539007547034`,
+    /// replacing the remittance text.
+    pub fn reference(&mut self, reference: &str) -> Result<&mut Self, Error> {
+        check("reference", reference, 1, 35, |c| c.is_ascii_alphanumeric())?;
+        self.remittance = Remittance::Reference(reference.to_string());
+        Ok(self)
+    }
+
+    /// Sets the unstructured remittance text, replacing the reference.
+    pub fn text(&mut self, text: &str) -> Result<&mut Self, Error> {
+        check("remittance text", text, 1, 140, |c| !c.is_control())?;
+        self.remittance = Remittance::Text(text.to_string());
+        Ok(self)
+    }
+
+    /// Sets the information shown to the payer by the banking app.
+    pub fn information(&mut self, information: &str) -> Result<&mut Self, Error> {
+        check("information", information, 1, 70, |c| !c.is_control())?;
+        self.information = Some(information.to_string());
+        Ok(self)
+    }
+
+    /// Returns the payload, as the lines of version 002 of the format in
+    /// UTF-8.
+    pub fn payload(&self) -> String {
+        let (reference, text) = match &self.remittance {
+            Remittance::None => ("", ""),
+            Remittance::Reference(reference) => (reference.as_str(), ""),
+            Remittance::Text(text) => ("", text.as_str()),
+        };
+        let amount = self
+            .amount
+            .as_ref()
+            .map(|amount| format!("EUR{}", amount))
+            .unwrap_or_default();
+
+        let lines = [
+            "BCD",
+            "002",
+            "1",
+            "SCT",
+            self.bic.as_deref().unwrap_or_default(),
+            &self.name,
+            &self.iban,
+            &amount,
+            self.purpose.as_deref().unwrap_or_default(),
+            reference,
+            text,
+            self.information.as_deref().unwrap_or_default(),
+        ];
+
+        // Trailing empty lines are left out.
+        let length = lines.iter().rposition(|line| !line.is_empty()).unwrap_or(0);
+        lines[..=length].join("\n")
+    }
+}
+
+/// Verifies the check digits of an IBAN, the remainder of the number by 97
+/// with its first four characters moved to the end.
+fn check_iban(iban: &str) -> Result<(), Error> {
+    let remainder = |iban: &str| {
+        iban[4..]
+            .chars()
+            .chain(iban[..4].chars())
+            .fold(0, |remainder, c| {
+                let value = c.to_digit(36).unwrap_or(0);
+                if value < 10 {
+                    (remainder * 10 + value) % 97
+                } else {
+                    (remainder * 100 + value) % 97
+                }
+            })
+    };
+
+    let expected = (98 - remainder(&format!("{}00{}", &iban[..2], &iban[4..]))) as u8;
+    let found = iban[2..4].parse::<u8>().unwrap_or(u8::MAX);
+    if remainder(iban) != 1 {
+        return Err(Error::InvalidQRCodeField {
+            field: "IBAN",
+            reason: BarcodeError::InvalidCheckDigit { expected, found },
+        });
+    }
+
+    Ok(())
+}
+
+/// Security of a Wi-Fi network.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WifiSecurity {
+    /// An open network.
+    None,
+    Wep,
+    /// WPA, WPA2 or WPA3 with a passphrase.
+    Wpa,
+}
+
+/// Builder of Wi-Fi QR codes, which phones scan to join a network.
+///
+/// # Example
+///
+/// ```ignore
+/// let mut wifi = WifiNetwork::new("Café guests")?;
+/// wifi.password(WifiSecurity::Wpa, "espresso;2024")?;
+///
+/// printer.qrcode(wifi.payload(), QRCodeOptions::default())?;
+/// ```
+#[derive(Clone, Debug)]
+pub struct WifiNetwork {
+    ssid: String,
+    security: WifiSecurity,
+    password: String,
+    hidden: bool,
+}
+
+impl WifiNetwork {
+    /// Starts an open network named `ssid`, of at most 32 bytes.
+    pub fn new(ssid: &str) -> Result<Self, Error> {
+        if ssid.is_empty() || ssid.len() > 32 {
+            return Err(Error::InvalidQRCodeField {
+                field: "SSID",
+                reason: BarcodeError::InvalidLength {
+                    min: 1,
+                    max: 32,
+                    length: ssid.len(),
+                },
+            });
+        }
+
+        Ok(Self {
+            ssid: ssid.to_string(),
+            security: WifiSecurity::None,
+            password: String::new(),
+            hidden: false,
+        })
+    }
+
+    /// Sets the security of the network and its password, 8 to 63
+    /// characters for WPA, and 5 or 13 characters or 10 or 26 hexadecimal
+    /// digits for WEP.
+    pub fn password(&mut self, security: WifiSecurity, password: &str) -> Result<&mut Self, Error> {
+        match security {
+            WifiSecurity::None => check("password", password, 0, 0, |_| false)?,
+            WifiSecurity::Wep => check_wep_key(password)?,
+            WifiSecurity::Wpa => check("password", password, 8, 63, |c| !c.is_control())?,
+        }
+
+        self.security = security;
+        self.password = password.to_string();
+        Ok(self)
+    }
+
+    /// Marks the network as hidden, not broadcasting its SSID.
+    pub fn hidden(&mut self, hidden: bool) -> &mut Self {
+        self.hidden = hidden;
+        self
+    }
+
+    /// Returns the payload, like `WIFI:T:WPA;S:name;P:password;;`.
+    pub fn payload(&self) -> String {
+        let mut payload = String::from("WIFI:");
+
+        match self.security {
+            WifiSecurity::None => payload.push_str("T:nopass;"),
+            WifiSecurity::Wep => payload.push_str("T:WEP;"),
+            WifiSecurity::Wpa => payload.push_str("T:WPA;"),
+        }
+        payload.push_str(&format!("S:{};", escape(&self.ssid, "\\;,\":")));
+        if self.security != WifiSecurity::None {
+            payload.push_str(&format!("P:{};", escape(&self.password, "\\;,\":")));
+        }
+        if self.hidden {
+            payload.push_str("H:true;");
+        }
+        payload.push(';');
+
+        payload
+    }
+}
+
+/// Checks that `key` is a 64 or 128-bit WEP key, as 5 or 13 characters or
+/// as 10 or 26 hexadecimal digits.
+fn check_wep_key(key: &str) -> Result<(), Error> {
+    let length = key.chars().count();
+    match length {
+        5 | 13 => check_chars(key, |c| !c.is_control()),
+        10 | 26 => check_chars(key, |c| c.is_ascii_hexdigit()),
+        _ => Err(BarcodeError::InvalidLength {
+            min: 5,
+            max: 26,
+            length,
+        }),
+    }
+    .map_err(invalid("password"))
+}
+
+/// Escapes the `special` characters of `value` with a backslash.
+fn escape(value: &str, special: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\n' => escaped.push_str("\\n"),
+            '\r' => {}
+            c if special.contains(c) => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Builder of contact cards as vCard 3.0, which phones offer to add to the
+/// address book.
+///
+/// # Example
+///
+/// ```ignore
+/// let mut card = VCard::new("Jane Doe");
+/// card.name("Doe", "Jane")
+///     .organization("ACME")
+///     .phone("+1 555 0100")
+///     .email("jane@example.com");
+///
+/// printer.qrcode(card.payload(), QRCodeOptions::default())?;
+/// ```
+#[derive(Clone, Debug)]
+pub struct VCard {
+    formatted_name: String,
+    name: Option<(String, String)>,
+    properties: Vec<(&'static str, String)>,
+}
+
+impl VCard {
+    /// Starts a card with the name displayed for the contact.
+    pub fn new(formatted_name: &str) -> Self {
+        Self {
+            formatted_name: formatted_name.to_string(),
+            name: None,
+            properties: Vec::new(),
+        }
+    }
+
+    /// Sets the structured name of the contact.
+    pub fn name(&mut self, family: &str, given: &str) -> &mut Self {
+        self.name = Some((family.to_string(), given.to_string()));
+        self
+    }
+
+    pub fn organization(&mut self, organization: &str) -> &mut Self {
+        self.property("ORG", organization)
+    }
+
+    pub fn title(&mut self, title: &str) -> &mut Self {
+        self.property("TITLE", title)
+    }
+
+    /// Adds a phone number. Cards can have several.
+    pub fn phone(&mut self, phone: &str) -> &mut Self {
+        self.property("TEL", phone)
+    }
+
+    /// Adds an email address. Cards can have several.
+    pub fn email(&mut self, email: &str) -> &mut Self {
+        self.property("EMAIL", email)
+    }
+
+    pub fn url(&mut self, url: &str) -> &mut Self {
+        self.property("URL", url)
+    }
+
+    /// Adds a postal address.
+    pub fn address(
+        &mut self,
+        street: &str,
+        city: &str,
+        postal_code: &str,
+        country: &str,
+    ) -> &mut Self {
+        let address = ["", "", street, city, "", postal_code, country]
+            .map(|part| escape(part, "\\;,"))
+            .join(";");
+        self.properties.push(("ADR", address));
+        self
+    }
+
+    pub fn note(&mut self, note: &str) -> &mut Self {
+        self.property("NOTE", note)
+    }
+
+    fn property(&mut self, name: &'static str, value: &str) -> &mut Self {
+        self.properties.push((name, escape(value, "\\;,")));
+        self
+    }
+
+    /// Returns the payload, with lines ended by CRLF as vCard asks.
+    pub fn payload(&self) -> String {
+        let (family, given) = self.name.clone().unwrap_or_default();
+
+        let mut lines = vec![
+            "BEGIN:VCARD".to_string(),
+            "VERSION:3.0".to_string(),
+            format!(
+                "N:{};{};;;",
+                escape(&family, "\\;,"),
+                escape(&given, "\\;,")
+            ),
+            format!("FN:{}", escape(&self.formatted_name, "\\;,")),
+        ];
+        lines.extend(
+            self.properties
+                .iter()
+                .map(|(name, value)| format!("{}:{}", name, value)),
+        );
+        lines.push("END:VCARD".to_string());
+
+        lines.join("\r\n")
+    }
+}
+
+/// Builder of URLs with query parameters, such as UTM parameters tracking
+/// which receipt a visit comes from.
+///
+/// # Example
+///
+/// ```ignore
+/// let mut url = TrackedUrl::new("https://example.com/survey")?;
+/// url.utm("receipt", "print", "spring-sale").param("store", "42");
+///
+/// printer.qrcode(url.payload(), QRCodeOptions::default())?;
+/// ```
+#[derive(Clone, Debug)]
+pub struct TrackedUrl {
+    url: String,
+    params: Vec<(String, String)>,
+}
+
+impl TrackedUrl {
+    /// Starts from an `http` or `https` URL, which may already have a query
+    /// and a fragment.
+    pub fn new(url: &str) -> Result<Self, Error> {
+        check("URL", url, 1, 2048, |c| c.is_ascii_graphic())?;
+
+        let scheme = url.split_once("://").map(|(scheme, _)| scheme);
+        if !matches!(scheme, Some(scheme) if scheme.eq_ignore_ascii_case("http") || scheme.eq_ignore_ascii_case("https"))
+        {
+            return Err(Error::InvalidQRCodeField {
+                field: "URL",
+                reason: BarcodeError::InvalidCharacter {
+                    character: url.chars().next().unwrap_or_default(),
+                    position: 0,
+                },
+            });
+        }
+
+        Ok(Self {
+            url: url.to_string(),
+            params: Vec::new(),
+        })
+    }
+
+    /// Adds the query parameter `name` with `value`, both percent-encoded.
+    pub fn param(&mut self, name: &str, value: &str) -> &mut Self {
+        self.params.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    /// Adds the `utm_source`, `utm_medium` and `utm_campaign` parameters.
+    pub fn utm(&mut self, source: &str, medium: &str, campaign: &str) -> &mut Self {
+        self.param("utm_source", source)
+            .param("utm_medium", medium)
+            .param("utm_campaign", campaign)
+    }
+
+    /// Returns the URL with the parameters added to its query, before the
+    /// fragment.
+    pub fn payload(&self) -> String {
+        let (url, fragment) = match self.url.split_once('#') {
+            Some((url, fragment)) => (url, Some(fragment)),
+            None => (self.url.as_str(), None),
+        };
+
+        let mut payload = url.to_string();
+        for (name, value) in &self.params {
+            let separator = if payload.contains('?') { '&' } else { '?' };
+            payload.push(separator);
+            payload.push_str(&percent_encode(name));
+            payload.push('=');
+            payload.push_str(&percent_encode(value));
+        }
+
+        if let Some(fragment) = fragment {
+            payload.push('#');
+            payload.push_str(fragment);
+        }
+
+        payload
+    }
+}
+
+/// Percent-encodes the bytes of `value` other than the unreserved
+/// characters of RFC 3986.
+fn percent_encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                (byte as char).to_string()
+            }
+            _ => format!("%{:02X}", byte),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn limits_the_additional_data_template() {
+        let mut payment = EmvcoPayment::new("ACME Coffee", "Singapore", "SG", "702").unwrap();
+        let long = "X".repeat(25);
+        payment
+            .additional_data(1, &long)
+            .unwrap()
+            .additional_data(5, &long)
+            .unwrap()
+            .additional_data(7, &long)
+            .unwrap();
+
+        // 87 characters are taken, leaving 8 for the value of a fourth field.
+        assert!(matches!(
+            payment.additional_data(8, &long),
+            Err(Error::InvalidQRCodeField {
+                field: "additional data",
+                reason: BarcodeError::InvalidLength {
+                    max: 8,
+                    length: 25,
+                    ..
+                },
+            })
+        ));
+        // Replacing a field frees its characters.
+        payment.additional_data(7, &long).unwrap();
+        payment.additional_data(8, "12345678").unwrap();
+
+        let template = format!("0125{0}0525{0}0725{0}080812345678", long);
+        assert_eq!(template.len(), 99);
+        assert!(payment.payload().contains(&format!("6299{}6304", template)));
+    }
+
+    #[test]
+    fn computes_the_emvco_crc() {
+        assert_eq!(crc16(b"123456789"), 0x29b1);
+    }
+
+    #[test]
+    fn builds_emvco_payments() {
+        let mut payment = EmvcoPayment::new("ACME Coffee", "Singapore", "SG", "702").unwrap();
+        payment
+            .merchant_account_template("SG.PAYNOW", &[(1, "2"), (2, "201403121W")])
+            .unwrap()
+            .amount(4.5, 2)
+            .unwrap()
+            .reference_label("INV-1042")
+            .unwrap();
+
+        assert_eq!(
+            payment.payload(),
+            "000201010212\
+             26320009SG.PAYNOW010120210201403121W\
+             52040000530370254044.505802SG\
+             5911ACME Coffee6009Singapore\
+             62120508INV-1042\
+             6304FE0D"
+        );
+        assert!(EmvcoPayment::new("ACME", "Singapore", "sg", "702").is_err());
+        assert!(payment.amount(-1.0, 2).is_err());
+    }
+
+    #[test]
+    fn builds_sepa_transfers() {
+        let mut transfer = SepaTransfer::new("Red Cross", "BE71 0961 2345 6769").unwrap();
+        transfer
+            .bic("BPOTBEB1")
+            .unwrap()
+            .amount(25.0)
+            .unwrap()
+            .text("Donation")
+            .unwrap();
+
+        assert_eq!(
+            transfer.payload(),
+            "BCD\n002\n1\nSCT\nBPOTBEB1\nRed Cross\nBE71096123456769\nEUR25.00\n\n\nDonation"
+        );
+        assert!(matches!(
+            SepaTransfer::new("Red Cross", "BE72 0961 2345 6769"),
+            Err(Error::InvalidQRCodeField {
+                field: "IBAN",
+                reason: BarcodeError::InvalidCheckDigit {
+                    expected: 71,
+                    found: 72
+                },
+            })
+        ));
+        assert!(transfer.bic("BPOTBEB1X").is_err());
+        assert!(transfer.amount(0.001).is_err());
+    }
+
+    #[test]
+    fn builds_wifi_networks() {
+        let mut wifi = WifiNetwork::new("Café guests").unwrap();
+        assert_eq!(wifi.payload(), "WIFI:T:nopass;S:Café guests;;");
+
+        wifi.password(WifiSecurity::Wpa, "espresso;2024")
+            .unwrap()
+            .hidden(true);
+        assert_eq!(
+            wifi.payload(),
+            r"WIFI:T:WPA;S:Café guests;P:espresso\;2024;H:true;;"
+        );
+
+        assert!(wifi.password(WifiSecurity::Wpa, "short").is_err());
+        assert!(wifi.password(WifiSecurity::Wep, "0123456789").is_ok());
+        assert!(wifi.password(WifiSecurity::Wep, "012345678g").is_err());
+        assert!(WifiNetwork::new(&"x".repeat(33)).is_err());
+    }
+
+    #[test]
+    fn builds_vcards() {
+        let mut card = VCard::new("Jane Doe");
+        card.name("Doe", "Jane")
+            .organization("ACME, Inc.")
+            .phone("+1 555 0100")
+            .address("1 Main St", "Springfield", "12345", "US");
+
+        assert_eq!(
+            card.payload(),
+            "BEGIN:VCARD\r\n\
+             VERSION:3.0\r\n\
+             N:Doe;Jane;;;\r\n\
+             FN:Jane Doe\r\n\
+             ORG:ACME\\, Inc.\r\n\
+             TEL:+1 555 0100\r\n\
+             ADR:;;1 Main St;Springfield;;12345;US\r\n\
+             END:VCARD"
+        );
+    }
+
+    #[test]
+    fn builds_tracked_urls() {
+        let mut url = TrackedUrl::new("https://example.com/survey?lang=en#top").unwrap();
+        url.utm("receipt", "print", "spring sale")
+            .param("store", "4/2");
+
+        assert_eq!(
+            url.payload(),
+            "https://example.com/survey?lang=en\
+             &utm_source=receipt&utm_medium=print&utm_campaign=spring%20sale\
+             &store=4%2F2#top"
+        );
+        assert!(TrackedUrl::new("ftp://example.com").is_err());
+    }
+}
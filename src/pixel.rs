@@ -1,6 +1,7 @@
 use std::ops::{Add, AddAssign, Div, Mul, Sub, SubAssign};
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Pixel {
     r: u8,
     g: u8,
@@ -14,7 +15,7 @@ impl Pixel {
     }
 
     pub const fn is_gray(&self) -> bool {
-        self.a > 0 && ((self.r + self.g + self.b) / 3) < 230
+        self.a > 0 && ((self.r as u16 + self.g as u16 + self.b as u16) / 3) < 230
     }
 
     pub const fn red(&self) -> u8 {
@@ -0,0 +1,472 @@
+//! An approximate picture of what a printer prints from ESC/POS data, drawn
+//! with the built-in font, to look at a receipt without printing it.
+
+use crate::{
+    barcode,
+    decode::{barcode_type, decode, Command},
+    font::{self, GLYPH_HEIGHT},
+    pixel::Pixel,
+    printer::{
+        Align, BarcodeOptions, BarcodeWidth, HriPosition, QRCodeCellSize, QRCodeCorrection,
+        QRCodeModel, QRCodeOptions, TextFont,
+    },
+    profile::PrinterProfile,
+    render, Image,
+};
+
+const BLACK: Pixel = Pixel::new(0, 0, 0, 255);
+const WHITE: Pixel = Pixel::new(255, 255, 255, 255);
+
+/// Draws the paper printed from `data` by a printer of `profile`, as wide as
+/// its print area. Text is drawn in cells of the size of the fonts of the
+/// profile, barcodes and QR codes are rendered in software, other 2D symbols
+/// are drawn as a labelled box, and cuts as a dashed line.
+///
+/// # Example
+///
+/// ```ignore
+/// let image = preview(&printer.get_data(), &PrinterProfile::EPSON_TM_T20);
+/// ```
+pub fn preview(data: &[u8], profile: &PrinterProfile) -> Image {
+    let mut paper = Paper::new(profile);
+    for instruction in decode(data) {
+        paper.run(instruction.command);
+    }
+    paper.flush(None);
+
+    let width = paper.width;
+    let height = paper.height.max(paper.y).max(1);
+    paper.dots.resize(width * height, false);
+    let mut pixels = vec![WHITE; width * height];
+    for (pixel, &dark) in pixels.iter_mut().zip(&paper.dots) {
+        if dark {
+            *pixel = BLACK;
+        }
+    }
+
+    Image::new(pixels, width as u16, height as u16)
+}
+
+#[derive(Clone, Copy)]
+struct Style {
+    font: TextFont,
+    width: usize,
+    height: usize,
+    bold: bool,
+    underline: u8,
+    invert: bool,
+}
+
+impl Default for Style {
+    fn default() -> Self {
+        Self {
+            font: TextFont::A,
+            width: 1,
+            height: 1,
+            bold: false,
+            underline: 0,
+            invert: false,
+        }
+    }
+}
+
+/// A character waiting in the line buffer, at `x` dots from the start of
+/// the line.
+struct Glyph {
+    character: char,
+    x: usize,
+    style: Style,
+}
+
+struct Paper<'a> {
+    profile: &'a PrinterProfile,
+    width: usize,
+    height: usize,
+    dots: Vec<bool>,
+    y: usize,
+    line: Vec<Glyph>,
+    cursor: usize,
+    style: Style,
+    align: Align,
+    left_margin: usize,
+    area_width: usize,
+    line_spacing: Option<usize>,
+    barcode: BarcodeOptions,
+    qr_code: QRCodeOptions,
+    symbol: Vec<u8>,
+}
+
+impl<'a> Paper<'a> {
+    fn new(profile: &'a PrinterProfile) -> Self {
+        let width = profile.dots_per_line as usize;
+        Self {
+            profile,
+            width,
+            height: 0,
+            dots: vec![],
+            y: 0,
+            line: vec![],
+            cursor: 0,
+            style: Style::default(),
+            align: Align::Left,
+            left_margin: 0,
+            area_width: width,
+            line_spacing: None,
+            barcode: BarcodeOptions::default(),
+            qr_code: QRCodeOptions::default(),
+            symbol: vec![],
+        }
+    }
+
+    fn run(&mut self, command: Command) {
+        match command {
+            Command::Text(text) => {
+                for byte in text {
+                    self.character(byte as char);
+                }
+            }
+            Command::LineFeed => {
+                let advance = self.line_height();
+                self.flush(Some(advance));
+            }
+            Command::Tab => {
+                let tab = self.cell(self.style).0 * 8;
+                let spaces = (tab - self.cursor % tab).div_ceil(self.cell(self.style).0);
+                for _ in 0..spaces {
+                    self.character(' ');
+                }
+            }
+            Command::Initialize => {
+                self.flush(None);
+                *self = Self {
+                    height: self.height,
+                    dots: std::mem::take(&mut self.dots),
+                    y: self.y,
+                    ..Self::new(self.profile)
+                };
+            }
+            Command::Bold(bold) | Command::DoubleStrike(bold) => self.style.bold = bold,
+            Command::Underline(dots) => self.style.underline = dots,
+            Command::Font(font) => self.style.font = text_font(font),
+            Command::PrintMode(mode) => {
+                self.style = Style {
+                    font: text_font(mode & 1),
+                    width: if mode & 0x20 != 0 { 2 } else { 1 },
+                    height: if mode & 0x10 != 0 { 2 } else { 1 },
+                    bold: mode & 0x08 != 0,
+                    underline: if mode & 0x80 != 0 { 1 } else { 0 },
+                    invert: self.style.invert,
+                }
+            }
+            Command::Size { width, height } => {
+                self.style.width = width as usize;
+                self.style.height = height as usize;
+            }
+            Command::Invert(invert) => self.style.invert = invert,
+            Command::Align(align) => self.align = align,
+            Command::LeftMargin(dots) => {
+                self.left_margin = (dots as usize).min(self.width);
+                self.area_width = self.area_width.min(self.width - self.left_margin);
+            }
+            Command::PrintAreaWidth(dots) => {
+                self.area_width = (dots as usize).min(self.width - self.left_margin)
+            }
+            Command::AbsolutePosition(dots) => self.cursor = dots as usize,
+            Command::RelativePosition(dots) => {
+                self.cursor = self.cursor.saturating_add_signed(dots as isize)
+            }
+            Command::LineSpacing(spacing) => self.line_spacing = spacing.map(usize::from),
+            Command::FeedLines(lines) => {
+                let advance = self.line_height();
+                self.flush(Some(advance));
+                self.y += advance * lines.saturating_sub(1) as usize;
+            }
+            Command::FeedDots(dots) => self.flush(Some(dots as usize)),
+            Command::Raster {
+                mode,
+                width,
+                height,
+                data,
+            } => {
+                let (scale_x, scale_y) = (1 + (mode & 1) as usize, 1 + (mode >> 1 & 1) as usize);
+                let (width, height) = (width as usize, height as usize);
+                let bytes = width / 8;
+                self.block(width * scale_x, height * scale_y, |paper, left, top| {
+                    for y in 0..height * scale_y {
+                        for x in 0..width * scale_x {
+                            let (column, row) = (x / scale_x, y / scale_y);
+                            if data[row * bytes + column / 8] >> (7 - column % 8) & 1 == 1 {
+                                paper.dot(left + x, top + y);
+                            }
+                        }
+                    }
+                });
+            }
+            Command::BarcodeHeight(height) => self.barcode.height = height,
+            Command::BarcodeWidth(width) => {
+                self.barcode.width = match width {
+                    0..=1 => BarcodeWidth::Width1,
+                    2 => BarcodeWidth::Width2,
+                    3 => BarcodeWidth::Width3,
+                    4 => BarcodeWidth::Width4,
+                    5 => BarcodeWidth::Width5,
+                    _ => BarcodeWidth::Width6,
+                }
+            }
+            Command::HriPosition(position) => {
+                self.barcode.hri_position = match position {
+                    1 => HriPosition::Top,
+                    2 => HriPosition::Bottom,
+                    3 => HriPosition::TopBottom,
+                    _ => HriPosition::None,
+                }
+            }
+            Command::HriFont(font) => self.barcode.hri_font = text_font(font),
+            Command::Barcode { system, data } => {
+                let image = barcode_type(system).and_then(|r#type| {
+                    let data = barcode::decode(&data, r#type);
+                    render::barcode(&data, r#type, &self.barcode, self.area_width as u16)
+                });
+                match image {
+                    Some(image) => self.image(&image),
+                    None => self.placeholder("BARCODE"),
+                }
+            }
+            Command::Symbol {
+                cn,
+                function,
+                parameters,
+            } => self.symbol(cn, function, &parameters),
+            Command::Cut { feed, .. } => {
+                self.flush(None);
+                self.y += feed.unwrap_or(0) as usize;
+                let y = self.y + 4;
+                for x in (0..self.width).filter(|x| x % 12 < 6) {
+                    self.dot(x, y);
+                }
+                self.y += 9;
+            }
+            _ => {}
+        }
+    }
+
+    /// Width and height of a character cell of `style`, in dots.
+    fn cell(&self, style: Style) -> (usize, usize) {
+        let chars = match self.profile.chars_per_line(style.font) {
+            Some(chars) => chars,
+            None => self.profile.chars_per_line(TextFont::A).unwrap_or(48),
+        };
+        let width = (self.profile.dots_per_line / chars.max(1)).max(1) as usize;
+
+        (width * style.width, width * 2 * style.height)
+    }
+
+    /// Height of a line of the current style, or the line spacing if it is
+    /// more.
+    fn line_height(&self) -> usize {
+        let default = (self.profile.dots_per_mm * 25.4 / 6.0).round() as usize;
+        let tallest = self
+            .line
+            .iter()
+            .map(|glyph| self.cell(glyph.style).1)
+            .max()
+            .unwrap_or(0);
+
+        tallest.max(self.line_spacing.unwrap_or(default))
+    }
+
+    fn character(&mut self, character: char) {
+        let (width, _) = self.cell(self.style);
+        if self.cursor + width > self.area_width && !self.line.is_empty() {
+            let advance = self.line_height();
+            self.flush(Some(advance));
+        }
+
+        self.line.push(Glyph {
+            character,
+            x: self.cursor,
+            style: self.style,
+        });
+        self.cursor += width;
+    }
+
+    /// Prints the line buffer and moves down by `advance` dots, or by the
+    /// height of the line if there is one and `advance` is `None`.
+    fn flush(&mut self, advance: Option<usize>) {
+        let line = std::mem::take(&mut self.line);
+        let height = line
+            .iter()
+            .map(|glyph| self.cell(glyph.style).1)
+            .max()
+            .unwrap_or(0);
+        let left = self.left_margin + self.offset(self.cursor);
+
+        for glyph in &line {
+            let (width, cell_height) = self.cell(glyph.style);
+            let (x, y) = (left + glyph.x, self.y + height - cell_height);
+            let style = glyph.style;
+
+            let mut dark = vec![false; width * cell_height];
+            font::draw(glyph.character, width, cell_height, |dx, dy| {
+                dark[dy * width + dx] = true;
+                if style.bold && dx + 1 < width {
+                    dark[dy * width + dx + 1] = true;
+                }
+            });
+            if style.underline > 0 {
+                let top = cell_height * GLYPH_HEIGHT / (GLYPH_HEIGHT + 1);
+                let bottom = (top + style.underline as usize * style.height).min(cell_height);
+                dark[top * width..bottom * width].fill(true);
+            }
+
+            for dy in 0..cell_height {
+                for dx in 0..width {
+                    if dark[dy * width + dx] != style.invert {
+                        self.dot(x + dx, y + dy);
+                    }
+                }
+            }
+        }
+
+        self.y += match advance {
+            Some(advance) => advance,
+            None if line.is_empty() => 0,
+            None => height,
+        };
+        self.cursor = 0;
+    }
+
+    /// Left of something `width` dots wide in the print area, as the
+    /// alignment says.
+    fn offset(&self, width: usize) -> usize {
+        let space = self.area_width.saturating_sub(width);
+        match self.align {
+            Align::Left => 0,
+            Align::Center => space / 2,
+            Align::Right => space,
+        }
+    }
+
+    /// Prints the line buffer, then something `width` by `height` dots,
+    /// aligned, drawn by `draw` from its top left corner.
+    fn block(&mut self, width: usize, height: usize, draw: impl FnOnce(&mut Self, usize, usize)) {
+        self.flush(None);
+        let left = self.left_margin + self.offset(width);
+        let top = self.y;
+        draw(self, left, top);
+        self.y += height;
+    }
+
+    fn image(&mut self, image: &Image) {
+        let pixels = image.get_pixels();
+        self.block(
+            image.width() as usize,
+            image.height() as usize,
+            |paper, left, top| {
+                for (y, line) in pixels.iter().enumerate() {
+                    for (x, pixel) in line.iter().enumerate() {
+                        if pixel.is_gray() {
+                            paper.dot(left + x, top + y);
+                        }
+                    }
+                }
+            },
+        );
+    }
+
+    /// A box with `label` in it, for what can't be drawn.
+    fn placeholder(&mut self, label: &str) {
+        let (cell_width, cell_height) = self.cell(Style::default());
+        let width = (label.chars().count() + 2) * cell_width;
+        let height = cell_height * 3;
+
+        self.block(width, height, |paper, left, top| {
+            for x in left..left + width {
+                paper.dot(x, top);
+                paper.dot(x, top + height - 1);
+            }
+            for y in top..top + height {
+                paper.dot(left, y);
+                paper.dot(left + width - 1, y);
+            }
+            for (i, character) in label.chars().enumerate() {
+                let x = left + (i + 1) * cell_width;
+                font::draw(character, cell_width, cell_height, |dx, dy| {
+                    paper.dot(x + dx, top + cell_height + dy)
+                });
+            }
+        });
+    }
+
+    fn symbol(&mut self, cn: u8, function: u8, parameters: &[u8]) {
+        let first = parameters.first().copied().unwrap_or(0);
+        match (cn, function) {
+            (49, 65) => {
+                self.qr_code.model = match first {
+                    49 => QRCodeModel::Model1,
+                    51 => QRCodeModel::Micro,
+                    _ => QRCodeModel::Model2,
+                }
+            }
+            (49, 67) => {
+                self.qr_code.cell_size = match first {
+                    0..=1 => QRCodeCellSize::Size1,
+                    2 => QRCodeCellSize::Size2,
+                    3 => QRCodeCellSize::Size3,
+                    4 => QRCodeCellSize::Size4,
+                    5 => QRCodeCellSize::Size5,
+                    6 => QRCodeCellSize::Size6,
+                    7 => QRCodeCellSize::Size7,
+                    _ => QRCodeCellSize::Size8,
+                }
+            }
+            (49, 69) => {
+                self.qr_code.correction = match first {
+                    49 => QRCodeCorrection::M,
+                    50 => QRCodeCorrection::Q,
+                    51 => QRCodeCorrection::H,
+                    _ => QRCodeCorrection::L,
+                }
+            }
+            // The data follows m, which is always 48.
+            (_, 80) => self.symbol = parameters.get(1..).unwrap_or_default().to_vec(),
+            (49, 81) => match render::qr_code(&self.symbol, &self.qr_code) {
+                Ok(image) => self.image(&image),
+                Err(_) => self.placeholder("QR CODE"),
+            },
+            (_, 81) => self.placeholder(match cn {
+                48 => "PDF417",
+                50 => "MAXICODE",
+                53 => "AZTEC",
+                54 => "DATAMATRIX",
+                _ => "2D SYMBOL",
+            }),
+            _ => {}
+        }
+    }
+
+    /// Darkens the dot at `x`, `y`, making the paper longer if needed.
+    fn dot(&mut self, x: usize, y: usize) {
+        if x >= self.width {
+            return;
+        }
+        if y >= self.height {
+            self.height = y + 1;
+            self.dots.resize(self.width * self.height, false);
+        }
+        self.dots[y * self.width + x] = true;
+    }
+}
+
+/// The font of `n` of `ESC M`, `ESC !` and `GS f`.
+fn text_font(n: u8) -> TextFont {
+    match n {
+        1 | 49 => TextFont::B,
+        2 | 50 => TextFont::C,
+        3 | 51 => TextFont::D,
+        4 | 52 => TextFont::E,
+        97 => TextFont::SpecialA,
+        98 => TextFont::SpecialB,
+        _ => TextFont::A,
+    }
+}
@@ -1,17 +1,29 @@
-use crate::{character_set::CharacterSet, command, constants::LF, Image};
-
+use std::fmt;
+
+use crate::{
+    barcode,
+    character_set::CharacterSet,
+    command,
+    constants::LF,
+    error::{BarcodeError, Error},
+    pixel::Pixel,
+    profile::{Buzzer, CodePage, CutFeed, PrinterProfile},
+    render,
+    state::{self, PrinterState},
+    Image,
+};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum QRCodeModel {
     Model1,
+    #[default]
     Model2,
     Micro,
 }
 
-impl Default for QRCodeModel {
-    fn default() -> Self {
-        QRCodeModel::Model2
-    }
-}
-
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum QRCodeCellSize {
     Size1,
     Size2,
@@ -23,6 +35,8 @@ pub enum QRCodeCellSize {
     Size8,
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum QRCodeCorrection {
     L,
     M,
@@ -30,10 +44,20 @@ pub enum QRCodeCorrection {
     H,
 }
 
+#[derive(Clone, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
 pub struct QRCodeOptions {
-    model: QRCodeModel,
-    cell_size: QRCodeCellSize,
-    correction: QRCodeCorrection,
+    pub model: QRCodeModel,
+    pub cell_size: QRCodeCellSize,
+    pub correction: QRCodeCorrection,
+    /// ECI assignment number written before the data, telling scanners how
+    /// to interpret it, such as 26 for UTF-8. Printers can't write an ECI
+    /// header themselves, so these symbols are rendered in software.
+    pub eci: Option<u32>,
 }
 
 impl Default for QRCodeOptions {
@@ -42,10 +66,128 @@ impl Default for QRCodeOptions {
             model: QRCodeModel::Model2,
             cell_size: QRCodeCellSize::Size3,
             correction: QRCodeCorrection::L,
+            eci: None,
+        }
+    }
+}
+
+/// Error correction of a PDF417 symbol, either as a level or as a ratio of
+/// the data codewords.
+pub enum Pdf417Correction {
+    /// Level 0 to 8, each level doubling the error correction codewords.
+    Level(u8),
+    /// Ratio in tens of percent, from 1 (10%) to 40 (400%).
+    Ratio(u8),
+}
+
+pub struct Pdf417Options {
+    /// Number of columns in the data region, from 1 to 30, or 0 to let the
+    /// printer choose.
+    pub columns: u8,
+    /// Number of rows, from 3 to 90, or 0 to let the printer choose.
+    pub rows: u8,
+    /// Width of a module in dots, from 2 to 8.
+    pub module_width: u8,
+    /// Height of a row as a multiple of the module width, from 2 to 8.
+    pub row_height: u8,
+    pub correction: Pdf417Correction,
+    /// Truncated PDF417 leaves out the right row indicators, making the
+    /// symbol narrower.
+    pub truncated: bool,
+}
+
+impl Default for Pdf417Options {
+    fn default() -> Self {
+        Self {
+            columns: 0,
+            rows: 0,
+            module_width: 3,
+            row_height: 3,
+            correction: Pdf417Correction::Ratio(1),
+            truncated: false,
+        }
+    }
+}
+
+pub enum DataMatrixSize {
+    /// Square symbol of the smallest size that fits the data.
+    Auto,
+    /// Square symbol with the given number of columns and rows, one of the
+    /// ECC 200 sizes from 10 to 144, or 0 to fit the data.
+    Square(u8),
+    /// Rectangular symbol with the given number of columns and rows, one of
+    /// 18x8, 32x8, 26x12, 36x12, 36x16 and 48x16, 0 fitting the data.
+    Rectangle(u8, u8),
+}
+
+pub struct DataMatrixOptions {
+    pub size: DataMatrixSize,
+    /// Size of a module in dots, from 2 to 16.
+    pub module_size: u8,
+}
+
+impl Default for DataMatrixOptions {
+    fn default() -> Self {
+        Self {
+            size: DataMatrixSize::Auto,
+            module_size: 3,
         }
     }
 }
 
+#[derive(Default)]
+pub enum AztecMode {
+    #[default]
+    FullRange,
+    Compact,
+}
+
+pub struct AztecOptions {
+    pub mode: AztecMode,
+    /// Number of data layers, up to 32 for full-range and 4 for compact
+    /// symbols, or 0 to fit the data.
+    pub layers: u8,
+    /// Size of a module in dots, from 2 to 16.
+    pub module_size: u8,
+    /// Share of error correction codewords in percent, from 5 to 95.
+    pub correction: u8,
+}
+
+impl Default for AztecOptions {
+    fn default() -> Self {
+        Self {
+            mode: AztecMode::FullRange,
+            layers: 0,
+            module_size: 3,
+            correction: 23,
+        }
+    }
+}
+
+/// MaxiCode modes. Modes 2 and 3 carry a structured carrier message with a
+/// postal code, country code and class of service.
+#[derive(Default)]
+pub enum MaxiCodeMode {
+    /// Structured carrier message with a numeric postal code.
+    #[default]
+    Mode2,
+    /// Structured carrier message with an alphanumeric postal code.
+    Mode3,
+    /// Standard symbol.
+    Mode4,
+    /// Full error correction.
+    Mode5,
+    /// Reader programming.
+    Mode6,
+}
+
+#[derive(Default)]
+pub struct MaxiCodeOptions {
+    pub mode: MaxiCodeMode,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum HriPosition {
     None,
     Top,
@@ -53,6 +195,8 @@ pub enum HriPosition {
     TopBottom,
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum TextFont {
     A,
     B,
@@ -63,6 +207,8 @@ pub enum TextFont {
     SpecialB,
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum BarcodeWidth {
     Width1,
     Width2,
@@ -72,6 +218,12 @@ pub enum BarcodeWidth {
     Width6,
 }
 
+#[derive(Clone, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
 pub struct BarcodeOptions {
     pub hri_position: HriPosition,
     pub hri_font: TextFont,
@@ -90,6 +242,8 @@ impl Default for BarcodeOptions {
     }
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum BarcodeType {
     UPCA,
     UPCE,
@@ -107,29 +261,73 @@ pub enum BarcodeType {
     GS1DataBarExpanded,
 }
 
+impl fmt::Display for BarcodeType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            BarcodeType::UPCA => "UPC-A",
+            BarcodeType::UPCE => "UPC-E",
+            BarcodeType::JAN13 => "JAN-13",
+            BarcodeType::JAN8 => "JAN-8",
+            BarcodeType::CODE39 => "CODE39",
+            BarcodeType::ITF => "ITF",
+            BarcodeType::CODABAR => "CODABAR",
+            BarcodeType::CODE93 => "CODE93",
+            BarcodeType::CODE128 => "CODE128",
+            BarcodeType::GS1128 => "GS1-128",
+            BarcodeType::GS1DataBarOmnidirectional => "GS1 DataBar Omnidirectional",
+            BarcodeType::GS1DataBarTruncated => "GS1 DataBar Truncated",
+            BarcodeType::GS1DataBarLimited => "GS1 DataBar Limited",
+            BarcodeType::GS1DataBarExpanded => "GS1 DataBar Expanded",
+        };
+
+        f.write_str(name)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum CashDrawerPin {
     TwoPin,
     FivePin,
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum TextUnderline {
     OneDotThick,
     TwoDotThick,
     None,
 }
 
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Align {
+    #[default]
     Left,
     Center,
     Right,
 }
 
-impl Default for Align {
-    fn default() -> Self {
-        Align::Left
+/// A horizontal distance, either in printer dots or in millimetres.
+///
+/// Millimetres are converted to dots using the resolution of the printer,
+/// see [`PrinterProfile::dots_per_mm`].
+pub enum Length {
+    Dots(i32),
+    Millimeters(f32),
+}
+
+impl Length {
+    pub fn to_dots(&self, dots_per_mm: f32) -> i32 {
+        match self {
+            Length::Dots(dots) => *dots,
+            Length::Millimeters(mm) => (mm * dots_per_mm).round() as i32,
+        }
     }
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum TextSize {
     Size1,
     Size2,
@@ -141,6 +339,83 @@ pub enum TextSize {
     Size8,
 }
 
+impl TextSize {
+    /// How many times the normal size this is.
+    pub const fn scale(self) -> u16 {
+        self as u16 + 1
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TextColor {
+    #[default]
+    Black,
+    Red,
+}
+
+/// The combined print mode bits selected with `ESC !`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PrintMode {
+    pub font_b: bool,
+    pub emphasized: bool,
+    pub double_height: bool,
+    pub double_width: bool,
+    pub underline: bool,
+}
+
+impl PrintMode {
+    pub const fn bits(&self) -> u8 {
+        (self.font_b as u8)
+            | (self.emphasized as u8) << 3
+            | (self.double_height as u8) << 4
+            | (self.double_width as u8) << 5
+            | (self.underline as u8) << 7
+    }
+}
+
+/// A complete set of text attributes that can be applied in one call with
+/// [`Printer::set_text_style`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct TextStyle {
+    pub font: TextFont,
+    pub bold: bool,
+    pub double_strike: bool,
+    pub underline: TextUnderline,
+    pub width: TextSize,
+    pub height: TextSize,
+    pub invert: bool,
+    pub rotated: bool,
+    pub upside_down: bool,
+    pub character_spacing: u8,
+    pub smoothing: bool,
+    pub color: TextColor,
+}
+
+impl Default for TextStyle {
+    fn default() -> Self {
+        Self {
+            font: TextFont::A,
+            bold: false,
+            double_strike: false,
+            underline: TextUnderline::None,
+            width: TextSize::Size1,
+            height: TextSize::Size1,
+            invert: false,
+            rotated: false,
+            upside_down: false,
+            character_spacing: 0,
+            smoothing: false,
+            color: TextColor::Black,
+        }
+    }
+}
+
 pub trait Printer {
     fn set_character_set(&mut self, set: CharacterSet) -> &mut Self;
     fn set_text_font(&mut self, font: TextFont) -> &mut Self;
@@ -148,19 +423,117 @@ pub trait Printer {
     fn set_text_size(&mut self, width: TextSize, height: TextSize) -> &mut Self;
     fn set_text_underline(&mut self, underline: TextUnderline) -> &mut Self;
     fn set_text_normal(&mut self) -> &mut Self;
+    fn set_print_mode(&mut self, mode: PrintMode) -> &mut Self;
+    fn set_text_double_strike(&mut self, enabled: bool) -> &mut Self;
+    fn set_text_rotation(&mut self, rotated: bool) -> &mut Self;
+    fn set_text_upside_down(&mut self, enabled: bool) -> &mut Self;
+    fn set_character_spacing(&mut self, spacing: u8) -> &mut Self;
+    fn set_text_smoothing(&mut self, enabled: bool) -> &mut Self;
+    fn set_text_color(&mut self, color: TextColor) -> &mut Self;
     fn set_align(&mut self, align: Align) -> &mut Self;
+    fn set_left_margin(&mut self, margin: Length) -> &mut Self;
+    fn set_print_area_width(&mut self, width: Length) -> &mut Self;
+    fn set_absolute_position(&mut self, position: Length) -> &mut Self;
+    fn set_relative_position(&mut self, offset: Length) -> &mut Self;
     fn invert(&mut self, enabled: bool) -> &mut Self;
+    /// Prints `data` in the current character set, with `?` in place of the
+    /// characters it lacks.
     fn text(&mut self, data: &str) -> &mut Self;
     fn raw(&mut self, data: &[u8]) -> &mut Self;
     fn new_line(&mut self) -> &mut Self;
+    /// Characters of the current font and width that fit in the print area.
+    fn chars_per_line(&self) -> u16;
+    /// Cuts the paper, moving it first as the profile's
+    /// [`crate::PrinterProfile::cut_feed`] says.
     fn cut(&mut self, partial: bool) -> &mut Self;
+    /// Cuts the paper, moving it first as `feed` says.
+    fn cut_with_feed(&mut self, partial: bool, feed: CutFeed) -> &mut Self;
     fn image(&mut self, image: Image) -> &mut Self;
-    fn qrcode(&mut self, data: &str, options: QRCodeOptions) -> &mut Self;
-    fn barcode(&mut self, data: &str, r#type: BarcodeType, options: BarcodeOptions) -> &mut Self;
+    /// Prints a QR code of `data`, text or binary data, written as is.
+    /// Returns an error if the data doesn't fit in a symbol of the model and
+    /// correction level of `options`.
+    fn qrcode(
+        &mut self,
+        data: impl AsRef<[u8]>,
+        options: QRCodeOptions,
+    ) -> Result<&mut Self, Error>;
+    /// Prints a PDF417 symbol. Returns an error if the printer doesn't
+    /// support PDF417 or the data doesn't fit in a symbol.
+    fn pdf417(&mut self, data: &str, options: Pdf417Options) -> Result<&mut Self, Error>;
+    /// Prints a DataMatrix symbol. Returns an error if the printer doesn't
+    /// support DataMatrix, the data doesn't fit in a symbol or no symbol has
+    /// the size of `options`.
+    fn data_matrix(&mut self, data: &str, options: DataMatrixOptions) -> Result<&mut Self, Error>;
+    /// Prints an Aztec Code symbol. Returns an error if the printer doesn't
+    /// support Aztec Code or the data doesn't fit in a symbol.
+    fn aztec(&mut self, data: &str, options: AztecOptions) -> Result<&mut Self, Error>;
+    /// Prints a MaxiCode symbol. Returns an error if the printer doesn't
+    /// support MaxiCode or the data doesn't fit in a symbol.
+    fn maxi_code(&mut self, data: &str, options: MaxiCodeOptions) -> Result<&mut Self, Error>;
+    /// Prints a barcode, after checking `data` against the rules of the
+    /// symbology. The check digit of UPC-A, UPC-E, JAN-13 and JAN-8 is added
+    /// when it is left out.
+    fn barcode(
+        &mut self,
+        data: &str,
+        r#type: BarcodeType,
+        options: BarcodeOptions,
+    ) -> Result<&mut Self, Error>;
+    /// Opens the cash drawer on `pin` with the default pulse of the
+    /// printer.
     fn cash_draw(&mut self, pin: CashDrawerPin) -> &mut Self;
+    /// Sounds the buzzer `times` times for `duration_ms` milliseconds
+    /// each, at least once. Printers without a buzzer ignore this.
+    fn beep(&mut self, times: u8, duration_ms: u16) -> &mut Self;
+    /// Opens the cash drawer on `pin` with a pulse of `on_ms` milliseconds
+    /// followed by `off_ms` milliseconds, for drawers that don't open with
+    /// the default pulse.
+    fn cash_draw_pulse(&mut self, pin: CashDrawerPin, on_ms: u16, off_ms: u16) -> &mut Self;
+    /// Opens the cash drawer on `pin` with a pulse of `pulse_ms`
+    /// milliseconds as soon as the printer receives the command, even
+    /// while it is busy with earlier data.
+    fn cash_draw_realtime(&mut self, pin: CashDrawerPin, pulse_ms: u16) -> &mut Self;
+    /// Asks the printer to send back its status, which includes the drawer
+    /// sensor. The response is parsed with [`crate::DrawerStatus`].
+    fn request_drawer_status(&mut self) -> &mut Self;
     fn initialize(&mut self) -> &mut Self;
     fn get_data(&self) -> Vec<u8>;
     fn clear(&mut self) -> &mut Self;
+    /// Saves the current text style and alignment on a stack.
+    fn push_style(&mut self) -> &mut Self;
+    /// Restores the text style and alignment saved by the matching
+    /// [`Printer::push_style`]. Does nothing if the stack is empty.
+    fn pop_style(&mut self) -> &mut Self;
+    /// Runs `f` and restores the text style and alignment afterwards, so
+    /// formatting applied inside `f` doesn't leak into what follows.
+    fn scoped_style<F>(&mut self, f: F) -> &mut Self
+    where
+        F: FnOnce(&mut Self) -> &mut Self,
+    {
+        self.push_style();
+        f(self);
+        self.pop_style()
+    }
+
+    fn set_text_style(&mut self, style: TextStyle) -> &mut Self {
+        self.set_text_font(style.font)
+            .set_text_bold(style.bold)
+            .set_text_double_strike(style.double_strike)
+            .set_text_underline(style.underline)
+            .set_text_size(style.width, style.height)
+            .invert(style.invert)
+            .set_text_rotation(style.rotated)
+            .set_text_upside_down(style.upside_down)
+            .set_character_spacing(style.character_spacing)
+            .set_text_smoothing(style.smoothing)
+            .set_text_color(style.color)
+    }
+
+    /// Restores every attribute of [`TextStyle`] to its power-on default.
+    fn reset_text_style(&mut self) -> &mut Self {
+        self.set_text_style(TextStyle::default())
+    }
+
     fn repeat(&mut self, times: usize, f: fn(&mut Self) -> &mut Self) -> &mut Self {
         for _ in 0..times {
             f(self);
@@ -170,28 +543,208 @@ pub trait Printer {
     }
 }
 
+/// Characters of the font and width of `style` that fit in `width` dots.
+pub(crate) fn chars_per_line(profile: &PrinterProfile, style: TextStyle, width: u16) -> u16 {
+    let chars = profile
+        .chars_per_line(style.font)
+        .or_else(|| profile.fonts.first().map(|(_, chars)| *chars))
+        .unwrap_or(0);
+
+    let chars = chars as u32 * width as u32 / profile.dots_per_line.max(1) as u32;
+
+    (chars / style.width.scale() as u32) as u16
+}
+
+/// Draws `text` with the dot font of the barcodes, in characters of the font
+/// and size of `style`, bold, underlined and inverted as it says. Returns an
+/// image per line of `width` dots, wrapping like the printer does, with the
+/// text placed as `align` says.
+pub(crate) fn rasterize_text(
+    profile: &PrinterProfile,
+    style: TextStyle,
+    align: Align,
+    width: u16,
+    text: &str,
+) -> Vec<Image> {
+    // Characters are about twice as high as they are wide.
+    let chars = profile
+        .chars_per_line(style.font)
+        .or_else(|| profile.fonts.first().map(|(_, chars)| *chars))
+        .unwrap_or(48)
+        .max(1);
+    let base = (profile.dots_per_line / chars).max(1) as usize;
+    let cell_width = base * style.width.scale() as usize;
+    let cell_height = base * 2 * style.height.scale() as usize;
+    let width = (width as usize).max(cell_width);
+    let boldness = if style.bold { cell_width / 8 + 1 } else { 0 };
+    let underline = match style.underline {
+        TextUnderline::None => 0,
+        TextUnderline::OneDotThick => style.height.scale() as usize,
+        TextUnderline::TwoDotThick => 2 * style.height.scale() as usize,
+    };
+    let (ink, paper) = match style.invert {
+        false => (Pixel::new(0, 0, 0, 255), Pixel::new(255, 255, 255, 255)),
+        true => (Pixel::new(255, 255, 255, 255), Pixel::new(0, 0, 0, 255)),
+    };
+
+    let characters: Vec<char> = text.chars().collect();
+    characters
+        .chunks(width / cell_width)
+        .map(|line| {
+            let space = width - line.len() * cell_width;
+            let left = match align {
+                Align::Left => 0,
+                Align::Center => space / 2,
+                Align::Right => space,
+            };
+
+            let mut dark = vec![false; width * cell_height];
+            for (i, &character) in line.iter().enumerate() {
+                let left = left + i * cell_width;
+                let mut set = |x: usize, y: usize| {
+                    for dx in 0..=boldness {
+                        if left + x + dx < width {
+                            dark[y * width + left + x + dx] = true;
+                        }
+                    }
+                };
+                crate::font::draw(character, cell_width, cell_height, &mut set);
+                for y in cell_height - underline..cell_height {
+                    for x in 0..cell_width {
+                        set(x, y);
+                    }
+                }
+            }
+
+            let pixels = dark
+                .into_iter()
+                .map(|dark| if dark { ink } else { paper })
+                .collect();
+            Image::new(pixels, width as u16, cell_height as u16)
+        })
+        .collect()
+}
+
 pub struct BasePrinter {
     cmds: Vec<u8>,
     character_set: CharacterSet,
+    profile: PrinterProfile,
+    left_margin: u16,
+    print_area_width: Option<u16>,
+    state: PrinterState,
+    /// The character size asked for when it is larger than the profile's
+    /// largest, in which case text is drawn in software.
+    software_size: Option<(TextSize, TextSize)>,
+    /// Whether text drawn in software ended the line, so that the next line
+    /// feed isn't sent.
+    line_drawn: bool,
+    style_stack: Vec<(TextStyle, Align)>,
 }
 
 impl Default for BasePrinter {
     fn default() -> Self {
+        Self::new(PrinterProfile::default())
+    }
+}
+
+impl BasePrinter {
+    /// Creates a printer that generates commands for the model described by
+    /// `profile`.
+    pub fn new(profile: PrinterProfile) -> Self {
         Self {
             cmds: vec![],
             character_set: CharacterSet::default(),
+            profile,
+            left_margin: 0,
+            print_area_width: None,
+            state: PrinterState::default(),
+            software_size: None,
+            line_drawn: false,
+            style_stack: vec![],
         }
     }
+
+    pub const fn profile(&self) -> &PrinterProfile {
+        &self.profile
+    }
+
+    pub const fn left_margin(&self) -> u16 {
+        self.left_margin
+    }
+
+    /// Width in dots that is actually printable, taking the left margin and
+    /// the print area width into account.
+    pub fn print_area_width(&self) -> u16 {
+        let available = self.profile.dots_per_line.saturating_sub(self.left_margin);
+
+        match self.print_area_width {
+            Some(width) => width.min(available),
+            None => available,
+        }
+    }
+
+    /// The text style currently in effect. Attributes that are unknown,
+    /// because the printer was never initialized, are reported as defaults.
+    pub fn text_style(&self) -> TextStyle {
+        let mut style = self.state.text_style();
+        if let Some((width, height)) = self.software_size {
+            style.width = width;
+            style.height = height;
+        }
+        style
+    }
+
+    pub fn align(&self) -> Align {
+        self.state.align.unwrap_or_default()
+    }
+
+    fn length_to_dots(&self, length: &Length) -> u16 {
+        length
+            .to_dots(self.profile.dots_per_mm)
+            .clamp(0, u16::MAX as i32) as u16
+    }
 }
 
 impl Printer for BasePrinter {
+    /// Selects `set` for the following text. Character sets missing from the
+    /// profile's code page table fall back to the default PC437, the table 0
+    /// of every printer, which the text is then encoded in.
     fn set_character_set(&mut self, set: CharacterSet) -> &mut Self {
-        self.cmds.extend_from_slice(&command::character_set(&set));
+        let (set, code_page) = match self.profile.code_page(set) {
+            Some(code_page) => (set, code_page),
+            None => {
+                let set = CharacterSet::default();
+                let code_page = self.profile.code_page(set).unwrap_or(CodePage::Table(0));
+                (set, code_page)
+            }
+        };
+
+        if state::update(&mut self.state.character_set, set) {
+            match code_page {
+                CodePage::Table(n) => self
+                    .cmds
+                    .extend_from_slice(&command::character_code_table(n)),
+                CodePage::International(n) => self
+                    .cmds
+                    .extend_from_slice(&command::international_character_set(n)),
+            }
+        }
         self.character_set = set;
         self
     }
 
+    /// Selects `font`, or the first font of the profile if the printer
+    /// doesn't have it.
     fn set_text_font(&mut self, font: TextFont) -> &mut Self {
+        let font = match self.profile.fonts.first() {
+            Some(&(fallback, _)) if !self.profile.supports_font(font) => fallback,
+            _ => font,
+        };
+
+        if !state::update(&mut self.state.font, font) {
+            return self;
+        }
+
         let n = match font {
             TextFont::A => 0,
             TextFont::B => 1,
@@ -208,21 +761,40 @@ impl Printer for BasePrinter {
     }
 
     fn set_text_bold(&mut self, bold: bool) -> &mut Self {
+        if !state::update(&mut self.state.bold, bold) {
+            return self;
+        }
+
         self.cmds
             .extend_from_slice(&command::text_bold(if bold { 1 } else { 0 }));
         self
     }
 
+    /// Sets the character size. Sizes larger than the largest of the profile
+    /// are drawn in software, and the text printed as images.
     fn set_text_size(&mut self, width: TextSize, height: TextSize) -> &mut Self {
+        let max = self.profile.max_text_size;
+        let (width, height) = if width > max || height > max {
+            self.software_size = Some((width, height));
+            (TextSize::Size1, TextSize::Size1)
+        } else {
+            self.software_size = None;
+            (width, height)
+        };
+
+        if !state::update(&mut self.state.size, (width, height)) {
+            return self;
+        }
+
         let w = match width {
-            TextSize::Size1 => 0 * 16,
-            TextSize::Size2 => 1 * 16,
-            TextSize::Size3 => 2 * 16,
-            TextSize::Size4 => 3 * 16,
-            TextSize::Size5 => 4 * 16,
-            TextSize::Size6 => 5 * 16,
-            TextSize::Size7 => 6 * 16,
-            TextSize::Size8 => 7 * 16,
+            TextSize::Size1 => 0,
+            TextSize::Size2 => 16,
+            TextSize::Size3 => 32,
+            TextSize::Size4 => 48,
+            TextSize::Size5 => 64,
+            TextSize::Size6 => 80,
+            TextSize::Size7 => 96,
+            TextSize::Size8 => 112,
         };
 
         let h = match height {
@@ -244,6 +816,10 @@ impl Printer for BasePrinter {
     }
 
     fn set_text_underline(&mut self, underline: TextUnderline) -> &mut Self {
+        if !state::update(&mut self.state.underline, underline) {
+            return self;
+        }
+
         let n = match underline {
             TextUnderline::OneDotThick => 1,
             TextUnderline::TwoDotThick => 2,
@@ -256,11 +832,108 @@ impl Printer for BasePrinter {
     }
 
     fn set_text_normal(&mut self) -> &mut Self {
-        self.cmds.extend_from_slice(&command::text_mode(0));
+        self.set_print_mode(PrintMode::default())
+    }
+
+    fn set_print_mode(&mut self, mode: PrintMode) -> &mut Self {
+        self.cmds
+            .extend_from_slice(&command::text_mode(mode.bits()));
+
+        let size = |double| {
+            if double {
+                TextSize::Size2
+            } else {
+                TextSize::Size1
+            }
+        };
+
+        // `ESC !` overwrites the font, emphasis, size and underline at once.
+        self.state.font = Some(if mode.font_b {
+            TextFont::B
+        } else {
+            TextFont::A
+        });
+        self.state.bold = Some(mode.emphasized);
+        self.state.size = Some((size(mode.double_width), size(mode.double_height)));
+        self.software_size = None;
+        self.state.underline = Some(if mode.underline {
+            TextUnderline::OneDotThick
+        } else {
+            TextUnderline::None
+        });
+
+        self
+    }
+
+    fn set_text_double_strike(&mut self, enabled: bool) -> &mut Self {
+        if !state::update(&mut self.state.double_strike, enabled) {
+            return self;
+        }
+
+        self.cmds
+            .extend_from_slice(&command::text_double_strike(if enabled { 1 } else { 0 }));
+        self
+    }
+
+    fn set_text_rotation(&mut self, rotated: bool) -> &mut Self {
+        if !state::update(&mut self.state.rotated, rotated) {
+            return self;
+        }
+
+        self.cmds
+            .extend_from_slice(&command::text_rotation(if rotated { 1 } else { 0 }));
+        self
+    }
+
+    fn set_text_upside_down(&mut self, enabled: bool) -> &mut Self {
+        if !state::update(&mut self.state.upside_down, enabled) {
+            return self;
+        }
+
+        self.cmds
+            .extend_from_slice(&command::text_upside_down(if enabled { 1 } else { 0 }));
+        self
+    }
+
+    fn set_character_spacing(&mut self, spacing: u8) -> &mut Self {
+        if !state::update(&mut self.state.character_spacing, spacing) {
+            return self;
+        }
+
+        self.cmds.extend_from_slice(&command::text_spacing(spacing));
+        self
+    }
+
+    fn set_text_smoothing(&mut self, enabled: bool) -> &mut Self {
+        if !state::update(&mut self.state.smoothing, enabled) {
+            return self;
+        }
+
+        self.cmds
+            .extend_from_slice(&command::text_smoothing(if enabled { 1 } else { 0 }));
+        self
+    }
+
+    fn set_text_color(&mut self, color: TextColor) -> &mut Self {
+        if !state::update(&mut self.state.color, color) {
+            return self;
+        }
+
+        let n = match color {
+            TextColor::Black => 0,
+            TextColor::Red => 1,
+        };
+
+        self.cmds.extend_from_slice(&command::text_color(n));
+
         self
     }
 
     fn set_align(&mut self, align: Align) -> &mut Self {
+        if !state::update(&mut self.state.align, align) {
+            return self;
+        }
+
         let n = match align {
             Align::Left => 0,
             Align::Center => 1,
@@ -272,15 +945,86 @@ impl Printer for BasePrinter {
         self
     }
 
+    fn set_left_margin(&mut self, margin: Length) -> &mut Self {
+        let margin = self.length_to_dots(&margin);
+        let nl = margin as u8;
+        let nh = (margin >> 8) as u8;
+
+        self.cmds.extend_from_slice(&command::left_margin(nl, nh));
+        self.left_margin = margin;
+
+        self
+    }
+
+    fn set_print_area_width(&mut self, width: Length) -> &mut Self {
+        let width = self.length_to_dots(&width);
+        let nl = width as u8;
+        let nh = (width >> 8) as u8;
+
+        self.cmds
+            .extend_from_slice(&command::print_area_width(nl, nh));
+        self.print_area_width = Some(width);
+
+        self
+    }
+
+    fn set_absolute_position(&mut self, position: Length) -> &mut Self {
+        let position = self.length_to_dots(&position);
+        let nl = position as u8;
+        let nh = (position >> 8) as u8;
+
+        self.cmds
+            .extend_from_slice(&command::absolute_position(nl, nh));
+
+        self
+    }
+
+    fn set_relative_position(&mut self, offset: Length) -> &mut Self {
+        // Negative offsets move to the left and are sent as two's complement.
+        let offset = offset
+            .to_dots(self.profile.dots_per_mm)
+            .clamp(i16::MIN as i32, i16::MAX as i32) as i16 as u16;
+        let nl = offset as u8;
+        let nh = (offset >> 8) as u8;
+
+        self.cmds
+            .extend_from_slice(&command::relative_position(nl, nh));
+
+        self
+    }
+
     fn invert(&mut self, enabled: bool) -> &mut Self {
+        if !state::update(&mut self.state.invert, enabled) {
+            return self;
+        }
+
         self.cmds
             .extend_from_slice(&command::invert(if enabled { 1 } else { 0 }));
         self
     }
 
+    /// Prints `data` in the current character set, with `?` in place of the
+    /// characters it lacks. Text of a size larger than the printer has is
+    /// drawn in software, in the ASCII characters of the barcode font, and
+    /// printed as an image ending the line.
     fn text(&mut self, data: &str) -> &mut Self {
-        self.cmds
-            .extend(iconv::encode(data, (&self.character_set).into()).unwrap());
+        if self.software_size.is_some() && !data.is_empty() {
+            let lines = rasterize_text(
+                &self.profile,
+                self.text_style(),
+                self.align(),
+                self.print_area_width(),
+                data,
+            );
+            for line in lines {
+                self.image(line);
+            }
+            self.line_drawn = true;
+            return self;
+        }
+
+        self.line_drawn = false;
+        self.cmds.extend(self.character_set.encode(data));
         self
     }
 
@@ -290,13 +1034,40 @@ impl Printer for BasePrinter {
     }
 
     fn new_line(&mut self) -> &mut Self {
+        if std::mem::take(&mut self.line_drawn) {
+            return self;
+        }
+
         self.cmds.push(LF);
         self
     }
 
+    fn chars_per_line(&self) -> u16 {
+        chars_per_line(&self.profile, self.text_style(), self.print_area_width())
+    }
+
     fn cut(&mut self, partial: bool) -> &mut Self {
+        self.cut_with_feed(partial, self.profile.cut_feed)
+    }
+
+    /// Cuts the paper with `GS V`, falling back to a full cut if the profile
+    /// has no partial cut. Printers without a cutter ignore this.
+    fn cut_with_feed(&mut self, partial: bool, feed: CutFeed) -> &mut Self {
+        if !self.profile.cutter {
+            return self;
+        }
+
+        let partial = partial && self.profile.partial_cut;
+
+        let (m, n) = match feed {
+            CutFeed::None => (48, None),
+            CutFeed::Feed(n) => (65, Some(n)),
+            CutFeed::Reserved(n) => (97, Some(n)),
+            CutFeed::FeedAndReverse(n) => (103, Some(n)),
+        };
+
         self.cmds
-            .extend_from_slice(&command::cut(if partial { 49 } else { 48 }, None));
+            .extend_from_slice(&command::cut(if partial { m + 1 } else { m }, n));
         self
     }
 
@@ -315,7 +1086,22 @@ impl Printer for BasePrinter {
         self
     }
 
-    fn qrcode(&mut self, data: &str, options: QRCodeOptions) -> &mut Self {
+    /// Prints a QR code with `GS ( k`, or as a raster image rendered in
+    /// software if the profile has no native QR code support or an ECI
+    /// header is asked for.
+    fn qrcode(
+        &mut self,
+        data: impl AsRef<[u8]>,
+        options: QRCodeOptions,
+    ) -> Result<&mut Self, Error> {
+        let data = data.as_ref();
+
+        if !self.profile.qr_code || options.eci.is_some() {
+            let image = render::qr_code(data, &options)?;
+            return Ok(self.image(image));
+        }
+        render::qr_symbol(data, &options)?;
+
         let model_value = match options.model {
             QRCodeModel::Model1 => 49,
             QRCodeModel::Model2 => 50,
@@ -349,21 +1135,210 @@ impl Printer for BasePrinter {
         self.cmds
             .extend_from_slice(&command::qr_code_correction_level(correction_value));
 
-        let encoded = iconv::encode(data, (&CharacterSet::Pc437Usa).into()).unwrap();
+        let length = (data.len() + 3) as u16;
+        let pl = length as u8;
+        let ph = (length >> 8) as u8;
+
+        self.cmds
+            .extend_from_slice(&command::qr_code_store(pl, ph, data));
+
+        self.cmds.extend_from_slice(&command::qr_code_print());
+
+        Ok(self)
+    }
+
+    fn pdf417(&mut self, data: &str, options: Pdf417Options) -> Result<&mut Self, Error> {
+        if !self.profile.pdf417 {
+            return Err(Error::UnsupportedSymbol("PDF417"));
+        }
+        barcode::check_capacity(data, &barcode::PDF417_CAPACITY).map_err(|reason| {
+            Error::InvalidSymbol {
+                symbology: "PDF417",
+                reason,
+            }
+        })?;
+
+        let columns = match options.columns {
+            0 => 0,
+            columns => columns.min(30),
+        };
+        self.cmds
+            .extend_from_slice(&command::pdf417_columns(columns));
+
+        let rows = match options.rows {
+            0 => 0,
+            rows => rows.clamp(3, 90),
+        };
+        self.cmds.extend_from_slice(&command::pdf417_rows(rows));
+
+        self.cmds.extend_from_slice(&command::pdf417_module_width(
+            options.module_width.clamp(2, 8),
+        ));
+        self.cmds
+            .extend_from_slice(&command::pdf417_row_height(options.row_height.clamp(2, 8)));
+
+        let (m, n) = match options.correction {
+            Pdf417Correction::Level(level) => (48, 48 + level.min(8)),
+            Pdf417Correction::Ratio(ratio) => (49, ratio.clamp(1, 40)),
+        };
+        self.cmds
+            .extend_from_slice(&command::pdf417_correction_level(m, n));
+
+        self.cmds
+            .extend_from_slice(&command::pdf417_options(if options.truncated {
+                1
+            } else {
+                0
+            }));
 
+        let encoded = data.as_bytes();
         let encoded_length = (encoded.len() + 3) as u16;
         let pl = encoded_length as u8;
         let ph = (encoded_length >> 8) as u8;
 
         self.cmds
-            .extend_from_slice(&command::qr_code_store(pl, ph, &encoded));
+            .extend_from_slice(&command::pdf417_store(pl, ph, encoded));
 
-        self.cmds.extend_from_slice(&command::qr_code_print());
+        self.cmds.extend_from_slice(&command::pdf417_print());
 
-        self
+        Ok(self)
     }
 
-    fn barcode(&mut self, data: &str, r#type: BarcodeType, options: BarcodeOptions) -> &mut Self {
+    fn data_matrix(&mut self, data: &str, options: DataMatrixOptions) -> Result<&mut Self, Error> {
+        if !self.profile.data_matrix {
+            return Err(Error::UnsupportedSymbol("DataMatrix"));
+        }
+        barcode::check_capacity(data, &barcode::DATA_MATRIX_CAPACITY)
+            .and_then(|()| barcode::check_data_matrix_size(&options.size))
+            .map_err(|reason| Error::InvalidSymbol {
+                symbology: "DataMatrix",
+                reason,
+            })?;
+
+        let (m, d1, d2) = match options.size {
+            DataMatrixSize::Auto => (0, 0, 0),
+            DataMatrixSize::Square(size) => (0, size, 0),
+            DataMatrixSize::Rectangle(columns, rows) => (1, columns, rows),
+        };
+        self.cmds
+            .extend_from_slice(&command::data_matrix_type(m, d1, d2));
+
+        self.cmds
+            .extend_from_slice(&command::data_matrix_module_size(
+                options.module_size.clamp(2, 16),
+            ));
+
+        let encoded = data.as_bytes();
+        let encoded_length = (encoded.len() + 3) as u16;
+        let pl = encoded_length as u8;
+        let ph = (encoded_length >> 8) as u8;
+
+        self.cmds
+            .extend_from_slice(&command::data_matrix_store(pl, ph, encoded));
+
+        self.cmds.extend_from_slice(&command::data_matrix_print());
+
+        Ok(self)
+    }
+
+    fn aztec(&mut self, data: &str, options: AztecOptions) -> Result<&mut Self, Error> {
+        if !self.profile.aztec {
+            return Err(Error::UnsupportedSymbol("Aztec Code"));
+        }
+        barcode::check_capacity(data, &barcode::AZTEC_CAPACITY).map_err(|reason| {
+            Error::InvalidSymbol {
+                symbology: "Aztec Code",
+                reason,
+            }
+        })?;
+
+        let (mode, max_layers) = match options.mode {
+            AztecMode::FullRange => (0, 32),
+            AztecMode::Compact => (1, 4),
+        };
+        self.cmds
+            .extend_from_slice(&command::aztec_mode(mode, options.layers.min(max_layers)));
+
+        self.cmds.extend_from_slice(&command::aztec_module_size(
+            options.module_size.clamp(2, 16),
+        ));
+
+        self.cmds
+            .extend_from_slice(&command::aztec_correction_level(
+                options.correction.clamp(5, 95),
+            ));
+
+        let encoded = data.as_bytes();
+        let encoded_length = (encoded.len() + 3) as u16;
+        let pl = encoded_length as u8;
+        let ph = (encoded_length >> 8) as u8;
+
+        self.cmds
+            .extend_from_slice(&command::aztec_store(pl, ph, encoded));
+
+        self.cmds.extend_from_slice(&command::aztec_print());
+
+        Ok(self)
+    }
+
+    fn maxi_code(&mut self, data: &str, options: MaxiCodeOptions) -> Result<&mut Self, Error> {
+        if !self.profile.maxi_code {
+            return Err(Error::UnsupportedSymbol("MaxiCode"));
+        }
+        barcode::check_capacity(data, &barcode::MAXI_CODE_CAPACITY).map_err(|reason| {
+            Error::InvalidSymbol {
+                symbology: "MaxiCode",
+                reason,
+            }
+        })?;
+
+        let mode_value = match options.mode {
+            MaxiCodeMode::Mode2 => 50,
+            MaxiCodeMode::Mode3 => 51,
+            MaxiCodeMode::Mode4 => 52,
+            MaxiCodeMode::Mode5 => 53,
+            MaxiCodeMode::Mode6 => 54,
+        };
+
+        self.cmds
+            .extend_from_slice(&command::maxi_code_mode(mode_value));
+
+        let encoded = data.as_bytes();
+        let encoded_length = (encoded.len() + 3) as u16;
+        let pl = encoded_length as u8;
+        let ph = (encoded_length >> 8) as u8;
+
+        self.cmds
+            .extend_from_slice(&command::maxi_code_store(pl, ph, encoded));
+
+        self.cmds.extend_from_slice(&command::maxi_code_print());
+
+        Ok(self)
+    }
+
+    /// Prints a barcode with `GS k`. Symbologies the profile doesn't support
+    /// are rendered in software and printed as a raster image instead, or
+    /// refused if the data can't be rendered.
+    fn barcode(
+        &mut self,
+        data: &str,
+        r#type: BarcodeType,
+        options: BarcodeOptions,
+    ) -> Result<&mut Self, Error> {
+        let data = barcode::validate(data, r#type)?;
+        let encoded = barcode::encode(&data, r#type);
+
+        if !self.profile.supports_barcode(r#type) {
+            let max_width = self.print_area_width();
+            let image = render::barcode(&data, r#type, &options, max_width).ok_or(
+                Error::InvalidBarcode {
+                    r#type,
+                    reason: BarcodeError::TooLong,
+                },
+            )?;
+            return Ok(self.image(image));
+        }
+
         let hri_position_value = match options.hri_position {
             HriPosition::None => 0,
             HriPosition::Top => 1,
@@ -419,40 +1394,162 @@ impl Printer for BasePrinter {
             BarcodeType::GS1DataBarExpanded => 78,
         };
 
-        let encoded = iconv::encode(data, (&CharacterSet::Pc437Usa).into()).unwrap();
-
         self.cmds.extend_from_slice(&command::barcode_print(
             type_value,
             encoded.len() as u8,
             &encoded,
         ));
 
-        self
+        Ok(self)
     }
 
     fn cash_draw(&mut self, pin: CashDrawerPin) -> &mut Self {
+        self.cash_draw_pulse(pin, 50, 240)
+    }
+
+    /// Sounds the buzzer with `ESC ( A` or `ESC B n t`, as the profile
+    /// says. Epson printers time beeps in steps of 100 ms and clones in
+    /// steps of 50 ms, up to 9 beeps of 450 ms.
+    fn beep(&mut self, times: u8, duration_ms: u16) -> &mut Self {
+        match self.profile.buzzer {
+            Some(Buzzer::Epson) => {
+                let n = times.clamp(1, 63);
+                let t = duration_ms.div_ceil(100).clamp(1, 255) as u8;
+                self.cmds
+                    .extend_from_slice(&command::beep_pattern(49, n, t));
+            }
+            Some(Buzzer::Clone) => {
+                let n = times.clamp(1, 9);
+                let t = duration_ms.div_ceil(50).clamp(1, 9) as u8;
+                self.cmds.extend_from_slice(&command::beep(n, t));
+            }
+            None => {}
+        }
+
+        self
+    }
+
+    /// Opens the cash drawer with `ESC p`, which times the pulse in steps
+    /// of 2 ms up to 510 ms.
+    fn cash_draw_pulse(&mut self, pin: CashDrawerPin, on_ms: u16, off_ms: u16) -> &mut Self {
         let m = match pin {
             CashDrawerPin::TwoPin => 0,
             CashDrawerPin::FivePin => 1,
         };
+        let t1 = (on_ms / 2).clamp(1, 255) as u8;
+        let t2 = (off_ms / 2).clamp(1, 255) as u8;
+
+        self.cmds.extend_from_slice(&command::cash_draw(m, t1, t2));
+
+        self
+    }
+
+    /// Opens the cash drawer with `DLE DC4 1`, which times the pulse in
+    /// steps of 100 ms up to 800 ms.
+    fn cash_draw_realtime(&mut self, pin: CashDrawerPin, pulse_ms: u16) -> &mut Self {
+        let m = match pin {
+            CashDrawerPin::TwoPin => 0,
+            CashDrawerPin::FivePin => 1,
+        };
+        let t = pulse_ms.div_ceil(100).clamp(1, 8) as u8;
 
         self.cmds
-            .extend_from_slice(&command::cash_draw(m, 0x19, 0x78));
+            .extend_from_slice(&command::cash_draw_realtime(m, t));
+
+        self
+    }
 
+    /// Asks for the printer status with `DLE EOT 1`, a single byte parsed
+    /// with [`crate::DrawerStatus::from_escpos`].
+    fn request_drawer_status(&mut self) -> &mut Self {
+        self.cmds.extend_from_slice(&command::transmit_status(1));
         self
     }
 
     fn initialize(&mut self) -> &mut Self {
         self.cmds.extend_from_slice(&command::initialize());
+        self.left_margin = 0;
+        self.print_area_width = None;
+        self.state = PrinterState::power_on();
+        self.software_size = None;
+        self.line_drawn = false;
         self
     }
 
+    /// Discards the buffered commands. The tracked style is forgotten as
+    /// well, since there is no telling whether the commands ever reached the
+    /// printer, so the next style commands are always emitted.
     fn clear(&mut self) -> &mut Self {
         self.cmds.clear();
+        self.state = PrinterState::default();
+        self.software_size = None;
+        self.line_drawn = false;
         self
     }
 
+    fn push_style(&mut self) -> &mut Self {
+        self.style_stack.push((self.text_style(), self.align()));
+        self
+    }
+
+    fn pop_style(&mut self) -> &mut Self {
+        match self.style_stack.pop() {
+            Some((style, align)) => self.set_text_style(style).set_align(align),
+            None => self,
+        }
+    }
+
     fn get_data(&self) -> Vec<u8> {
         self.cmds.clone()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prints_supported_barcodes_with_gs_k() {
+        let data = BasePrinter::new(PrinterProfile::GENERIC)
+            .barcode(
+                "400638133393",
+                BarcodeType::JAN13,
+                BarcodeOptions::default(),
+            )
+            .unwrap()
+            .get_data();
+        assert!(data.ends_with(b"\x1dk\x43\x0d4006381333931"));
+    }
+
+    #[test]
+    fn renders_unsupported_symbols_in_software() {
+        let mut printer = BasePrinter::new(PrinterProfile::GENERIC_58MM);
+        printer
+            .barcode(
+                "0950110153000",
+                BarcodeType::GS1DataBarOmnidirectional,
+                BarcodeOptions::default(),
+            )
+            .unwrap();
+        assert!(printer.get_data().starts_with(b"\x1dv0\x00"));
+
+        let data = printer
+            .clear()
+            .qrcode("abc", QRCodeOptions::default())
+            .unwrap()
+            .get_data();
+        assert!(data.starts_with(b"\x1dv0\x00"));
+        assert!(!data.windows(3).any(|window| window == b"\x1d(k"));
+    }
+
+    #[test]
+    fn beeps_as_the_profile_says() {
+        let beep = |profile, times| BasePrinter::new(profile).beep(times, 200).get_data();
+        assert_eq!(
+            beep(PrinterProfile::EPSON_TM_T20, 0),
+            b"\x1b(A\x04\x000\x31\x01\x02"
+        );
+        assert_eq!(beep(PrinterProfile::GENERIC, 3), b"\x1bB\x03\x04");
+        assert_eq!(beep(PrinterProfile::GENERIC_58MM, 3), b"");
+    }
+}
@@ -0,0 +1,408 @@
+use crate::{
+    character_set::CharacterSet,
+    printer::{BarcodeType, TextFont, TextSize},
+};
+
+/// Printable dots per line of an 80mm printer at 203 dpi.
+pub const DEFAULT_DOTS_PER_LINE: u16 = 576;
+
+/// Dots per millimetre of a 203 dpi printer.
+pub const DEFAULT_DOTS_PER_MM: f32 = 8.0;
+
+/// How a printer selects a character set.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CodePage {
+    /// Character code table selected with `ESC t n`.
+    Table(u8),
+    /// International character set selected with `ESC R n`.
+    International(u8),
+}
+
+/// How a printer sounds its buzzer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Buzzer {
+    /// The beeper of Epson printers, sounded with `ESC ( A`.
+    Epson,
+    /// The buzzer of common Epson clones, sounded with `ESC B n t`.
+    Clone,
+}
+
+/// How the paper is moved before it is cut, for the cutter sits a few
+/// millimetres above the print head.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum CutFeed {
+    /// Cut where the paper is, leaving the last printed lines under the
+    /// blade (function A, `GS V 48/49`).
+    None,
+    /// Feed the paper to the cutter position plus `n` dots, then cut
+    /// (function B, `GS V 65/66 n`).
+    Feed(u8),
+    /// Reserve the cut at the cutter position plus `n` dots. The cut happens
+    /// once later printing brings the paper there, so no paper is wasted
+    /// between receipts (function C, `GS V 97/98 n`).
+    Reserved(u8),
+    /// Feed the paper to the cutter position plus `n` dots, cut, then feed
+    /// it back to the print head (function D, `GS V 103/104 n`).
+    FeedAndReverse(u8),
+}
+
+/// Capabilities of a printer model.
+///
+/// The built-in profiles describe common models in their default
+/// configuration. Custom models can be described by starting from one of them
+/// with struct update syntax:
+///
+/// ```ignore
+/// const MY_PRINTER: PrinterProfile = PrinterProfile {
+///     name: "My printer",
+///     cutter: false,
+///     ..PrinterProfile::EPSON_TM_T20
+/// };
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct PrinterProfile {
+    /// Short identifier used to look the profile up, see [`PrinterProfile::find`].
+    pub id: &'static str,
+    pub name: &'static str,
+    /// Printable dots per line, which is also the widest raster image.
+    pub dots_per_line: u16,
+    pub dots_per_mm: f32,
+    /// Supported fonts and how many characters of each fit on a line.
+    pub fonts: &'static [(TextFont, u16)],
+    /// Largest character width and height accepted by `GS !`.
+    pub max_text_size: TextSize,
+    pub code_pages: &'static [(CharacterSet, CodePage)],
+    pub barcodes: &'static [BarcodeType],
+    /// Whether QR codes can be printed with `GS ( k`.
+    pub qr_code: bool,
+    /// Whether PDF417 symbols can be printed with `GS ( k`.
+    pub pdf417: bool,
+    /// Whether DataMatrix symbols can be printed with `GS ( k`, which needs
+    /// recent firmware. The same goes for `aztec` and `maxi_code`.
+    pub data_matrix: bool,
+    pub aztec: bool,
+    pub maxi_code: bool,
+    pub cutter: bool,
+    pub partial_cut: bool,
+    /// How [`crate::Printer::cut`] moves the paper before cutting.
+    pub cut_feed: CutFeed,
+    /// The buzzer of the printer, if it has one.
+    pub buzzer: Option<Buzzer>,
+}
+
+impl PrinterProfile {
+    /// A generic Epson compatible 80mm printer. This is the default profile.
+    pub const GENERIC: PrinterProfile = PrinterProfile {
+        id: "generic",
+        name: "Generic ESC/POS",
+        dots_per_line: DEFAULT_DOTS_PER_LINE,
+        dots_per_mm: DEFAULT_DOTS_PER_MM,
+        fonts: &[(TextFont::A, 48), (TextFont::B, 64)],
+        max_text_size: TextSize::Size8,
+        code_pages: EPSON_CODE_PAGES,
+        barcodes: ALL_BARCODES,
+        qr_code: true,
+        pdf417: true,
+        data_matrix: false,
+        aztec: false,
+        maxi_code: false,
+        cutter: true,
+        partial_cut: true,
+        cut_feed: CutFeed::Feed(0),
+        buzzer: Some(Buzzer::Clone),
+    };
+
+    pub const EPSON_TM_T20: PrinterProfile = PrinterProfile {
+        id: "epson-tm-t20",
+        name: "Epson TM-T20",
+        dots_per_line: 576,
+        dots_per_mm: 8.0,
+        fonts: &[(TextFont::A, 48), (TextFont::B, 64)],
+        max_text_size: TextSize::Size8,
+        code_pages: EPSON_CODE_PAGES,
+        barcodes: ALL_BARCODES,
+        qr_code: true,
+        pdf417: true,
+        data_matrix: false,
+        aztec: false,
+        maxi_code: false,
+        cutter: true,
+        partial_cut: true,
+        cut_feed: CutFeed::Feed(0),
+        buzzer: Some(Buzzer::Epson),
+    };
+
+    pub const EPSON_TM_T88: PrinterProfile = PrinterProfile {
+        id: "epson-tm-t88",
+        name: "Epson TM-T88",
+        dots_per_line: 512,
+        dots_per_mm: 180.0 / 25.4,
+        fonts: &[(TextFont::A, 42), (TextFont::B, 56)],
+        max_text_size: TextSize::Size8,
+        code_pages: EPSON_CODE_PAGES,
+        barcodes: ALL_BARCODES,
+        qr_code: true,
+        pdf417: true,
+        data_matrix: true,
+        aztec: true,
+        maxi_code: true,
+        cutter: true,
+        partial_cut: true,
+        cut_feed: CutFeed::Feed(0),
+        buzzer: Some(Buzzer::Epson),
+    };
+
+    pub const STAR_TSP100: PrinterProfile = PrinterProfile {
+        id: "star-tsp100",
+        name: "Star TSP100 (ESC/POS mode)",
+        dots_per_line: 576,
+        dots_per_mm: 8.0,
+        fonts: &[(TextFont::A, 48), (TextFont::B, 64)],
+        max_text_size: TextSize::Size8,
+        code_pages: COMMON_CODE_PAGES,
+        barcodes: BASIC_BARCODES,
+        qr_code: false,
+        pdf417: false,
+        data_matrix: false,
+        aztec: false,
+        maxi_code: false,
+        cutter: true,
+        partial_cut: true,
+        cut_feed: CutFeed::Feed(0),
+        buzzer: None,
+    };
+
+    pub const STAR_TSP650: PrinterProfile = PrinterProfile {
+        id: "star-tsp650",
+        name: "Star TSP650 (ESC/POS mode)",
+        dots_per_line: 576,
+        dots_per_mm: 8.0,
+        fonts: &[(TextFont::A, 48), (TextFont::B, 64)],
+        max_text_size: TextSize::Size8,
+        code_pages: COMMON_CODE_PAGES,
+        barcodes: BASIC_BARCODES,
+        qr_code: true,
+        pdf417: true,
+        data_matrix: false,
+        aztec: false,
+        maxi_code: false,
+        cutter: true,
+        partial_cut: true,
+        cut_feed: CutFeed::Feed(0),
+        buzzer: None,
+    };
+
+    pub const XPRINTER_XP80: PrinterProfile = PrinterProfile {
+        id: "xprinter-xp80",
+        name: "Xprinter XP-80",
+        dots_per_line: 576,
+        dots_per_mm: 8.0,
+        fonts: &[(TextFont::A, 48), (TextFont::B, 64)],
+        max_text_size: TextSize::Size8,
+        code_pages: COMMON_CODE_PAGES,
+        barcodes: BASIC_BARCODES,
+        qr_code: true,
+        pdf417: false,
+        data_matrix: false,
+        aztec: false,
+        maxi_code: false,
+        cutter: true,
+        partial_cut: true,
+        cut_feed: CutFeed::Feed(0),
+        buzzer: Some(Buzzer::Clone),
+    };
+
+    pub const XPRINTER_XP58: PrinterProfile = PrinterProfile {
+        id: "xprinter-xp58",
+        name: "Xprinter XP-58",
+        dots_per_line: 384,
+        dots_per_mm: 8.0,
+        fonts: &[(TextFont::A, 32), (TextFont::B, 42)],
+        max_text_size: TextSize::Size8,
+        code_pages: COMMON_CODE_PAGES,
+        barcodes: BASIC_BARCODES,
+        qr_code: false,
+        pdf417: false,
+        data_matrix: false,
+        aztec: false,
+        maxi_code: false,
+        cutter: false,
+        partial_cut: false,
+        cut_feed: CutFeed::None,
+        buzzer: Some(Buzzer::Clone),
+    };
+
+    pub const BIXOLON_SRP350: PrinterProfile = PrinterProfile {
+        id: "bixolon-srp350",
+        name: "Bixolon SRP-350",
+        dots_per_line: 512,
+        dots_per_mm: 180.0 / 25.4,
+        fonts: &[(TextFont::A, 42), (TextFont::B, 56)],
+        max_text_size: TextSize::Size8,
+        code_pages: COMMON_CODE_PAGES,
+        barcodes: BASIC_BARCODES,
+        qr_code: true,
+        pdf417: true,
+        data_matrix: false,
+        aztec: false,
+        maxi_code: false,
+        cutter: true,
+        partial_cut: true,
+        cut_feed: CutFeed::Feed(0),
+        buzzer: None,
+    };
+
+    /// The cheap unbranded 58mm printers sold as "POS-58".
+    pub const GENERIC_58MM: PrinterProfile = PrinterProfile {
+        id: "generic-58mm",
+        name: "Generic 58mm",
+        dots_per_line: 384,
+        dots_per_mm: 8.0,
+        fonts: &[(TextFont::A, 32), (TextFont::B, 42)],
+        max_text_size: TextSize::Size4,
+        code_pages: COMMON_CODE_PAGES,
+        barcodes: BASIC_BARCODES,
+        qr_code: false,
+        pdf417: false,
+        data_matrix: false,
+        aztec: false,
+        maxi_code: false,
+        cutter: false,
+        partial_cut: false,
+        cut_feed: CutFeed::None,
+        buzzer: None,
+    };
+
+    /// Every built-in profile.
+    pub const ALL: &'static [PrinterProfile] = &[
+        Self::GENERIC,
+        Self::EPSON_TM_T20,
+        Self::EPSON_TM_T88,
+        Self::STAR_TSP100,
+        Self::STAR_TSP650,
+        Self::XPRINTER_XP80,
+        Self::XPRINTER_XP58,
+        Self::BIXOLON_SRP350,
+        Self::GENERIC_58MM,
+    ];
+
+    /// Looks a built-in profile up by its [`PrinterProfile::id`].
+    pub fn find(id: &str) -> Option<PrinterProfile> {
+        Self::ALL
+            .iter()
+            .find(|profile| profile.id.eq_ignore_ascii_case(id))
+            .copied()
+    }
+
+    pub fn code_page(&self, set: CharacterSet) -> Option<CodePage> {
+        self.code_pages
+            .iter()
+            .find(|(candidate, _)| *candidate == set)
+            .map(|(_, code_page)| *code_page)
+    }
+
+    pub fn supports_font(&self, font: TextFont) -> bool {
+        self.chars_per_line(font).is_some()
+    }
+
+    /// Characters per line of `font` at normal width.
+    pub fn chars_per_line(&self, font: TextFont) -> Option<u16> {
+        self.fonts
+            .iter()
+            .find(|(candidate, _)| *candidate == font)
+            .map(|(_, chars)| *chars)
+    }
+
+    pub fn supports_barcode(&self, r#type: BarcodeType) -> bool {
+        self.barcodes.contains(&r#type)
+    }
+}
+
+impl Default for PrinterProfile {
+    fn default() -> Self {
+        Self::GENERIC
+    }
+}
+
+const ALL_BARCODES: &[BarcodeType] = &[
+    BarcodeType::UPCA,
+    BarcodeType::UPCE,
+    BarcodeType::JAN13,
+    BarcodeType::JAN8,
+    BarcodeType::CODE39,
+    BarcodeType::ITF,
+    BarcodeType::CODABAR,
+    BarcodeType::CODE93,
+    BarcodeType::CODE128,
+    BarcodeType::GS1128,
+    BarcodeType::GS1DataBarOmnidirectional,
+    BarcodeType::GS1DataBarTruncated,
+    BarcodeType::GS1DataBarLimited,
+    BarcodeType::GS1DataBarExpanded,
+];
+
+const BASIC_BARCODES: &[BarcodeType] = &[
+    BarcodeType::UPCA,
+    BarcodeType::UPCE,
+    BarcodeType::JAN13,
+    BarcodeType::JAN8,
+    BarcodeType::CODE39,
+    BarcodeType::ITF,
+    BarcodeType::CODABAR,
+    BarcodeType::CODE93,
+    BarcodeType::CODE128,
+];
+
+const EPSON_CODE_PAGES: &[(CharacterSet, CodePage)] = &[
+    (CharacterSet::Pc437Usa, CodePage::Table(0)),
+    (CharacterSet::Pc850Multilingual, CodePage::Table(2)),
+    (CharacterSet::Pc860Portuguese, CodePage::Table(3)),
+    (CharacterSet::Pc863CanadianFrench, CodePage::Table(4)),
+    (CharacterSet::Pc865Nordic, CodePage::Table(5)),
+    (CharacterSet::Pc851Greek, CodePage::Table(11)),
+    (CharacterSet::Pc857Turkish, CodePage::Table(12)),
+    (CharacterSet::Pc737Greek, CodePage::Table(14)),
+    (CharacterSet::Iso8859_7Greek, CodePage::Table(15)),
+    (CharacterSet::Wpc1252, CodePage::Table(16)),
+    (CharacterSet::Pc866Cyrillic2, CodePage::Table(17)),
+    (CharacterSet::Pc852Latin2, CodePage::Table(18)),
+    (CharacterSet::Slovenia, CodePage::Table(18)),
+    (CharacterSet::Pc858Euro, CodePage::Table(19)),
+    (CharacterSet::Wpc775BalticRim, CodePage::Table(33)),
+    (CharacterSet::Pc855Cyrillic, CodePage::Table(34)),
+    (CharacterSet::Pc861Icelandic, CodePage::Table(35)),
+    (CharacterSet::Pc862Hebrew, CodePage::Table(36)),
+    (CharacterSet::Pc864Arabic, CodePage::Table(37)),
+    (CharacterSet::Pc869Greek, CodePage::Table(38)),
+    (CharacterSet::Iso8859_2Latin2, CodePage::Table(39)),
+    (CharacterSet::Iso8859_15Latin9, CodePage::Table(40)),
+    (CharacterSet::Pc1125Ukranian, CodePage::Table(44)),
+    (CharacterSet::Wpc1250Latin2, CodePage::Table(45)),
+    (CharacterSet::Wpc1251Cyrillic, CodePage::Table(46)),
+    (CharacterSet::Wpc1253Greek, CodePage::Table(47)),
+    (CharacterSet::Wpc1254Turkish, CodePage::Table(48)),
+    (CharacterSet::Wpc1255Hebrew, CodePage::Table(49)),
+    (CharacterSet::Wpc1256Arabic, CodePage::Table(50)),
+    (CharacterSet::Wpc1257BalticRim, CodePage::Table(51)),
+    (CharacterSet::Wpc1258Vietnamese, CodePage::Table(52)),
+    (CharacterSet::Kz1048Kazakhstan, CodePage::Table(53)),
+    (CharacterSet::Japan, CodePage::International(0x08)),
+    (CharacterSet::Korea, CodePage::International(0x0d)),
+    (CharacterSet::China, CodePage::International(0x0f)),
+    (CharacterSet::HkTw, CodePage::International(0x00)),
+];
+
+/// The tables that Star, Bixolon and most clones number the same way as
+/// Epson.
+const COMMON_CODE_PAGES: &[(CharacterSet, CodePage)] = &[
+    (CharacterSet::Pc437Usa, CodePage::Table(0)),
+    (CharacterSet::Pc850Multilingual, CodePage::Table(2)),
+    (CharacterSet::Pc860Portuguese, CodePage::Table(3)),
+    (CharacterSet::Pc863CanadianFrench, CodePage::Table(4)),
+    (CharacterSet::Pc865Nordic, CodePage::Table(5)),
+    (CharacterSet::Wpc1252, CodePage::Table(16)),
+    (CharacterSet::Pc866Cyrillic2, CodePage::Table(17)),
+    (CharacterSet::Pc852Latin2, CodePage::Table(18)),
+    (CharacterSet::Pc858Euro, CodePage::Table(19)),
+];
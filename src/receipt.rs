@@ -0,0 +1,334 @@
+//! A declarative receipt, built in one place and printed in another. With
+//! the `serde` feature, receipts can be serialized to JSON, CBOR or any
+//! other serde format.
+
+use crate::{
+    error::Error,
+    printer::{
+        Align, BarcodeOptions, BarcodeType, CashDrawerPin, Printer, QRCodeOptions, TextStyle,
+    },
+    wrap::wrap,
+    Image,
+};
+
+/// A column of a [`ReceiptItem::Table`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct Column {
+    /// Width in characters, or 0 to share the characters left by the other
+    /// columns with the other columns of width 0.
+    pub width: u16,
+    pub align: Align,
+}
+
+/// An element of a [`Receipt`], printed below the previous one.
+#[derive(Clone, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum ReceiptItem {
+    /// A paragraph of text, wrapped by the printer.
+    Text {
+        text: String,
+        #[cfg_attr(feature = "serde", serde(default))]
+        style: TextStyle,
+        #[cfg_attr(feature = "serde", serde(default))]
+        align: Align,
+    },
+    Image {
+        image: Image,
+        #[cfg_attr(feature = "serde", serde(default))]
+        align: Align,
+    },
+    Barcode {
+        data: String,
+        r#type: BarcodeType,
+        #[cfg_attr(feature = "serde", serde(default))]
+        options: BarcodeOptions,
+        #[cfg_attr(feature = "serde", serde(default))]
+        align: Align,
+    },
+    #[cfg_attr(feature = "serde", serde(rename = "qr_code"))]
+    QRCode {
+        data: String,
+        #[cfg_attr(feature = "serde", serde(default))]
+        options: QRCodeOptions,
+        #[cfg_attr(feature = "serde", serde(default))]
+        align: Align,
+    },
+    /// Rows of cells laid out in columns separated by a space. Cells that
+    /// don't fit in their column are wrapped.
+    Table {
+        columns: Vec<Column>,
+        rows: Vec<Vec<String>>,
+        #[cfg_attr(feature = "serde", serde(default))]
+        style: TextStyle,
+    },
+    /// A line of `character` across the print area.
+    Separator {
+        character: char,
+    },
+    /// Empty lines.
+    Feed {
+        lines: u8,
+    },
+    Cut {
+        partial: bool,
+    },
+    CashDrawer {
+        pin: CashDrawerPin,
+    },
+}
+
+/// The content of a receipt, which can be printed by any [`Printer`].
+///
+/// # Example
+///
+/// ```ignore
+/// let bold = TextStyle { bold: true, ..TextStyle::default() };
+///
+/// let mut receipt = Receipt::new();
+/// receipt
+///     .text("ACME Coffee", bold, Align::Center)
+///     .separator('-')
+///     .table(
+///         vec![Column { width: 0, align: Align::Left }, Column { width: 8, align: Align::Right }],
+///         vec![vec!["Espresso".into(), "2.50".into()]],
+///         TextStyle::default(),
+///     )
+///     .cut(false);
+///
+/// let json = serde_json::to_string(&receipt)?;
+///
+/// // In the printing service:
+/// let receipt: Receipt = serde_json::from_str(&json)?;
+/// receipt.print(&mut printer)?;
+/// ```
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Receipt {
+    pub items: Vec<ReceiptItem>,
+}
+
+impl Receipt {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn text(&mut self, text: &str, style: TextStyle, align: Align) -> &mut Self {
+        self.items.push(ReceiptItem::Text {
+            text: text.to_string(),
+            style,
+            align,
+        });
+        self
+    }
+
+    pub fn image(&mut self, image: Image, align: Align) -> &mut Self {
+        self.items.push(ReceiptItem::Image { image, align });
+        self
+    }
+
+    pub fn barcode(
+        &mut self,
+        data: &str,
+        r#type: BarcodeType,
+        options: BarcodeOptions,
+        align: Align,
+    ) -> &mut Self {
+        self.items.push(ReceiptItem::Barcode {
+            data: data.to_string(),
+            r#type,
+            options,
+            align,
+        });
+        self
+    }
+
+    pub fn qrcode(&mut self, data: &str, options: QRCodeOptions, align: Align) -> &mut Self {
+        self.items.push(ReceiptItem::QRCode {
+            data: data.to_string(),
+            options,
+            align,
+        });
+        self
+    }
+
+    pub fn table(
+        &mut self,
+        columns: Vec<Column>,
+        rows: Vec<Vec<String>>,
+        style: TextStyle,
+    ) -> &mut Self {
+        self.items.push(ReceiptItem::Table {
+            columns,
+            rows,
+            style,
+        });
+        self
+    }
+
+    pub fn separator(&mut self, character: char) -> &mut Self {
+        self.items.push(ReceiptItem::Separator { character });
+        self
+    }
+
+    pub fn feed(&mut self, lines: u8) -> &mut Self {
+        self.items.push(ReceiptItem::Feed { lines });
+        self
+    }
+
+    pub fn cut(&mut self, partial: bool) -> &mut Self {
+        self.items.push(ReceiptItem::Cut { partial });
+        self
+    }
+
+    pub fn cash_draw(&mut self, pin: CashDrawerPin) -> &mut Self {
+        self.items.push(ReceiptItem::CashDrawer { pin });
+        self
+    }
+
+    /// Prints the items in order. The text style and alignment of
+    /// `printer` are restored after each item.
+    pub fn print<P: Printer>(&self, printer: &mut P) -> Result<(), Error> {
+        for item in &self.items {
+            printer.push_style();
+            let result = print_item(printer, item);
+            printer.pop_style();
+            result?;
+        }
+
+        Ok(())
+    }
+}
+
+fn print_item<P: Printer>(printer: &mut P, item: &ReceiptItem) -> Result<(), Error> {
+    match item {
+        ReceiptItem::Text { text, style, align } => {
+            printer
+                .set_align(*align)
+                .set_text_style(*style)
+                .text(text)
+                .new_line();
+        }
+        ReceiptItem::Image { image, align } => {
+            printer.set_align(*align).image(image.clone());
+        }
+        ReceiptItem::Barcode {
+            data,
+            r#type,
+            options,
+            align,
+        } => {
+            printer
+                .set_align(*align)
+                .barcode(data, *r#type, options.clone())?;
+        }
+        ReceiptItem::QRCode {
+            data,
+            options,
+            align,
+        } => {
+            printer.set_align(*align).qrcode(data, options.clone())?;
+        }
+        ReceiptItem::Table {
+            columns,
+            rows,
+            style,
+        } => {
+            printer.set_align(Align::Left).set_text_style(*style);
+
+            let widths = column_widths(columns, printer.chars_per_line() as usize);
+            for row in rows {
+                for line in table_row(columns, &widths, row) {
+                    printer.text(&line).new_line();
+                }
+            }
+        }
+        ReceiptItem::Separator { character } => {
+            let line: String =
+                std::iter::repeat_n(*character, printer.chars_per_line() as usize).collect();
+            printer.set_align(Align::Left).text(&line).new_line();
+        }
+        ReceiptItem::Feed { lines } => {
+            for _ in 0..*lines {
+                printer.new_line();
+            }
+        }
+        ReceiptItem::Cut { partial } => {
+            printer.cut(*partial);
+        }
+        ReceiptItem::CashDrawer { pin } => {
+            printer.cash_draw(*pin);
+        }
+    }
+
+    Ok(())
+}
+
+/// Widths of the columns in characters, sharing what is left of `chars`
+/// between the columns of width 0.
+pub(crate) fn column_widths(columns: &[Column], chars: usize) -> Vec<usize> {
+    let gaps = columns.len().saturating_sub(1);
+    let fixed: usize = columns.iter().map(|column| column.width as usize).sum();
+    let shared = columns.iter().filter(|column| column.width == 0).count();
+    let rest = chars.saturating_sub(gaps + fixed);
+
+    let mut extra = 0;
+    columns
+        .iter()
+        .map(|column| match column.width {
+            0 => {
+                extra += 1;
+                rest / shared + usize::from(extra <= rest % shared)
+            }
+            width => width as usize,
+        })
+        .collect()
+}
+
+/// Lays out the cells of a table row, wrapping them in their column.
+pub(crate) fn table_row(columns: &[Column], widths: &[usize], cells: &[String]) -> Vec<String> {
+    let wrapped: Vec<Vec<String>> = widths
+        .iter()
+        .enumerate()
+        .map(|(i, &width)| wrap(cells.get(i).map(String::as_str).unwrap_or(""), width))
+        .collect();
+    let height = wrapped.iter().map(Vec::len).max().unwrap_or(0);
+
+    (0..height)
+        .map(|line| {
+            let cells: Vec<String> = columns
+                .iter()
+                .zip(widths)
+                .zip(&wrapped)
+                .map(|((column, &width), lines)| {
+                    pad(
+                        lines.get(line).map(String::as_str).unwrap_or(""),
+                        width,
+                        column.align,
+                    )
+                })
+                .collect();
+            cells.join(" ").trim_end().to_string()
+        })
+        .collect()
+}
+
+/// Pads `text` with spaces to `width` characters, as `align` says.
+pub(crate) fn pad(text: &str, width: usize, align: Align) -> String {
+    let space = width.saturating_sub(text.chars().count());
+    let (left, right) = match align {
+        Align::Left => (0, space),
+        Align::Center => (space / 2, space - space / 2),
+        Align::Right => (space, 0),
+    };
+
+    format!("{}{}{}", " ".repeat(left), text, " ".repeat(right))
+}
@@ -0,0 +1,864 @@
+//! Software rendering of barcodes and QR codes into raster images, used when
+//! a printer can't draw a symbol itself.
+
+use qrcode::{bits::Bits, types::Color, EcLevel, QrCode, Version};
+
+use crate::{
+    barcode::{self, FNC1, FNC2, FNC3, FNC4},
+    databar,
+    error::{Error, QRCodeError},
+    font::{self, GLYPH_HEIGHT, GLYPH_WIDTH},
+    gs1,
+    pixel::Pixel,
+    printer::{
+        BarcodeOptions, BarcodeType, BarcodeWidth, HriPosition, QRCodeCellSize, QRCodeCorrection,
+        QRCodeModel, QRCodeOptions, TextFont,
+    },
+    Image,
+};
+
+const BLACK: Pixel = Pixel::new(0, 0, 0, 255);
+const WHITE: Pixel = Pixel::new(255, 255, 255, 255);
+
+/// Quiet zone on each side of a linear barcode, in modules.
+const BARCODE_QUIET_ZONE: usize = 10;
+
+/// Data codewords of the model 1 versions 1 to 14, at the levels L, M, Q
+/// and H.
+const MODEL1_DATA_CODEWORDS: [[usize; 4]; 14] = [
+    [19, 16, 13, 9],
+    [36, 30, 24, 16],
+    [57, 44, 36, 24],
+    [80, 60, 50, 34],
+    [108, 82, 68, 46],
+    [136, 106, 86, 58],
+    [170, 132, 108, 72],
+    [208, 160, 128, 87],
+    [246, 186, 156, 102],
+    [290, 222, 183, 124],
+    [336, 256, 208, 145],
+    [384, 292, 244, 165],
+    [432, 332, 276, 192],
+    [489, 368, 310, 210],
+];
+
+/// Encodes a QR code in the smallest symbol of the model that holds `data`
+/// at the error correction level of `options`.
+///
+/// Model 1 symbols are encoded as model 2 symbols of the same sizes, the
+/// versions 1 to 14, so they only hold the data that fits in both the model
+/// 1 and the model 2 symbol of a version.
+pub(crate) fn qr_symbol(data: &[u8], options: &QRCodeOptions) -> Result<QrCode, Error> {
+    let level = match options.correction {
+        QRCodeCorrection::L => EcLevel::L,
+        QRCodeCorrection::M => EcLevel::M,
+        QRCodeCorrection::Q => EcLevel::Q,
+        QRCodeCorrection::H => EcLevel::H,
+    };
+
+    let versions: Vec<Version> = match options.model {
+        QRCodeModel::Model1 => (1..=14).map(Version::Normal).collect(),
+        QRCodeModel::Model2 => (1..=40).map(Version::Normal).collect(),
+        QRCodeModel::Micro => {
+            if level == EcLevel::H {
+                return Err(Error::InvalidQRCode(QRCodeError::UnsupportedCorrection));
+            }
+            if options.eci.is_some() {
+                return Err(Error::InvalidQRCode(QRCodeError::UnsupportedEci));
+            }
+            (1..=4).map(Version::Micro).collect()
+        }
+    };
+
+    if let Some(eci) = options.eci.filter(|&eci| eci > 999_999) {
+        return Err(Error::InvalidQRCode(QRCodeError::InvalidEci(eci)));
+    }
+
+    versions
+        .into_iter()
+        .find_map(|version| {
+            let mut bits = Bits::new(version);
+            if let Some(eci) = options.eci {
+                bits.push_eci_designator(eci).ok()?;
+            }
+            bits.push_optimal_data(data).ok()?;
+            if let (QRCodeModel::Model1, Version::Normal(number)) = (options.model, version) {
+                let capacity = MODEL1_DATA_CODEWORDS[number as usize - 1][level as usize];
+                if bits.len() > capacity * 8 {
+                    return None;
+                }
+            }
+            bits.push_terminator(level).ok()?;
+            QrCode::with_bits(bits, level).ok()
+        })
+        .ok_or(Error::InvalidQRCode(QRCodeError::TooLong {
+            length: data.len(),
+        }))
+}
+
+/// Renders a QR code as [`qr_symbol`] encodes it.
+pub(crate) fn qr_code(data: &[u8], options: &QRCodeOptions) -> Result<Image, Error> {
+    let code = qr_symbol(data, options)?;
+    let quiet_zone = match options.model {
+        QRCodeModel::Micro => 2,
+        QRCodeModel::Model1 | QRCodeModel::Model2 => 4,
+    };
+
+    let cell = match options.cell_size {
+        QRCodeCellSize::Size1 => 1,
+        QRCodeCellSize::Size2 => 2,
+        QRCodeCellSize::Size3 => 3,
+        QRCodeCellSize::Size4 => 4,
+        QRCodeCellSize::Size5 => 5,
+        QRCodeCellSize::Size6 => 6,
+        QRCodeCellSize::Size7 => 7,
+        QRCodeCellSize::Size8 => 8,
+    };
+
+    let modules = code.width();
+    let colors = code.to_colors();
+    let size = (modules + 2 * quiet_zone) * cell;
+
+    Ok(bitmap(size, size, |x, y| {
+        let (x, y) = (x / cell, y / cell);
+        if x < quiet_zone
+            || y < quiet_zone
+            || x >= modules + quiet_zone
+            || y >= modules + quiet_zone
+        {
+            return false;
+        }
+
+        colors[(y - quiet_zone) * modules + x - quiet_zone] == Color::Dark
+    }))
+}
+
+/// Renders a linear barcode from a payload checked by
+/// [`barcode::validate`], with the human readable interpretation placed as
+/// `options.hri_position` asks. Returns `None` if the data can't be encoded.
+///
+/// The module width is reduced when the symbol would be wider than
+/// `max_width` dots.
+pub(crate) fn barcode(
+    data: &str,
+    r#type: BarcodeType,
+    options: &BarcodeOptions,
+    max_width: u16,
+) -> Option<Image> {
+    let encoded = barcode::encode(data, r#type);
+    let encoded = encoded.as_slice();
+
+    let bars = match r#type {
+        BarcodeType::UPCA => upc_a(encoded)?,
+        BarcodeType::UPCE => upc_e(encoded)?,
+        BarcodeType::JAN13 => ean13(encoded)?,
+        BarcodeType::JAN8 => ean8(encoded)?,
+        BarcodeType::CODE39 => code39(encoded)?,
+        BarcodeType::ITF => itf(encoded)?,
+        BarcodeType::CODABAR => codabar(encoded)?,
+        BarcodeType::CODE93 => code93(encoded)?,
+        BarcodeType::CODE128 => code128(encoded, false)?,
+        BarcodeType::GS1128 => code128(encoded, true)?,
+        BarcodeType::GS1DataBarOmnidirectional | BarcodeType::GS1DataBarTruncated => {
+            databar::omnidirectional(&gtin(data)?)
+        }
+        BarcodeType::GS1DataBarLimited => databar::limited(&gtin(data)?),
+        BarcodeType::GS1DataBarExpanded => databar::expanded(data)?,
+    };
+
+    let modules = bars.iter().map(|&w| w as usize).sum::<usize>() + 2 * BARCODE_QUIET_ZONE;
+
+    let mut module = match options.width {
+        BarcodeWidth::Width1 => 1,
+        BarcodeWidth::Width2 => 2,
+        BarcodeWidth::Width3 => 3,
+        BarcodeWidth::Width4 => 4,
+        BarcodeWidth::Width5 => 5,
+        BarcodeWidth::Width6 => 6,
+    };
+    while module > 1 && modules * module > max_width as usize {
+        module -= 1;
+    }
+
+    // Expand the run lengths into one flag per module.
+    let mut dark = vec![false; BARCODE_QUIET_ZONE];
+    for (i, &width) in bars.iter().enumerate() {
+        dark.extend(std::iter::repeat_n(i % 2 == 0, width as usize));
+    }
+    dark.extend(std::iter::repeat_n(false, BARCODE_QUIET_ZONE));
+
+    let bars_width = modules * module;
+    let bars_height = options.height.max(1) as usize;
+
+    let text = hri(data, r#type);
+    let (scale_x, scale_y) = match options.hri_font {
+        TextFont::A | TextFont::SpecialA => (2, 3),
+        _ => (1, 2),
+    };
+    let text_width = text.chars().count() * (GLYPH_WIDTH + 1) * scale_x;
+    // The text and the gap between the text and the bars.
+    let text_height = (GLYPH_HEIGHT + 1) * scale_y;
+
+    let (top, bottom) = match options.hri_position {
+        HriPosition::None => (false, false),
+        HriPosition::Top => (true, false),
+        HriPosition::Bottom => (false, true),
+        HriPosition::TopBottom => (true, true),
+    };
+
+    let width = if top || bottom {
+        bars_width.max(text_width)
+    } else {
+        bars_width
+    };
+    let bars_top = if top { text_height } else { 0 };
+    let height = bars_top + bars_height + if bottom { text_height } else { 0 };
+
+    let mut canvas = Canvas::new(width, height);
+    let bars_left = (width - bars_width) / 2;
+    for y in bars_top..bars_top + bars_height {
+        for x in 0..bars_width {
+            if dark[x / module] {
+                canvas.set(bars_left + x, y);
+            }
+        }
+    }
+
+    let text_left = (width - text_width) / 2;
+    if top {
+        canvas.text(text_left, 0, &text, scale_x, scale_y);
+    }
+    if bottom {
+        let y = bars_top + bars_height + scale_y;
+        canvas.text(text_left, y, &text, scale_x, scale_y);
+    }
+
+    Some(canvas.into_image())
+}
+
+fn gtin(data: &str) -> Option<Vec<u8>> {
+    digits(data.as_bytes()).filter(|digits| digits.len() == 13)
+}
+
+/// Returns the human readable interpretation of a barcode, as printers show
+/// it: with the check digits and the Code 39 start and stop characters, and
+/// with the GS1 Application Identifiers in parentheses.
+fn hri(data: &str, r#type: BarcodeType) -> String {
+    match r#type {
+        BarcodeType::CODE39 => format!("*{}*", data.trim_matches('*')),
+        BarcodeType::CODE128 => data.replace([FNC1, FNC2, FNC3, FNC4], ""),
+        BarcodeType::GS1128 | BarcodeType::GS1DataBarExpanded => {
+            gs1::parse_hri(data).unwrap_or_else(|| data.replace(FNC1, ""))
+        }
+        BarcodeType::GS1DataBarOmnidirectional
+        | BarcodeType::GS1DataBarTruncated
+        | BarcodeType::GS1DataBarLimited => {
+            let check_digit = ean_check_digit(&gtin(data).unwrap_or_default());
+            format!("(01){}{}", data, check_digit)
+        }
+        _ => data.to_string(),
+    }
+}
+
+/// A monochrome drawing surface.
+struct Canvas {
+    width: usize,
+    height: usize,
+    dark: Vec<bool>,
+}
+
+impl Canvas {
+    fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            dark: vec![false; width * height],
+        }
+    }
+
+    fn set(&mut self, x: usize, y: usize) {
+        if x < self.width && y < self.height {
+            self.dark[y * self.width + x] = true;
+        }
+    }
+
+    /// Draws `text` with its top left corner at `x`, `y`, each dot of the
+    /// font being `scale_x` by `scale_y` pixels.
+    fn text(&mut self, x: usize, y: usize, text: &str, scale_x: usize, scale_y: usize) {
+        for (i, c) in text.chars().enumerate() {
+            let left = x + i * (GLYPH_WIDTH + 1) * scale_x;
+            for (column, bits) in font::glyph(c).into_iter().enumerate() {
+                for row in (0..GLYPH_HEIGHT).filter(|row| bits >> row & 1 == 1) {
+                    for dy in 0..scale_y {
+                        for dx in 0..scale_x {
+                            self.set(left + column * scale_x + dx, y + row * scale_y + dy);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn into_image(self) -> Image {
+        bitmap(self.width, self.height, |x, y| {
+            self.dark[y * self.width + x]
+        })
+    }
+}
+
+fn bitmap(width: usize, height: usize, dark: impl Fn(usize, usize) -> bool) -> Image {
+    let mut pixels = Vec::with_capacity(width * height);
+    for y in 0..height {
+        for x in 0..width {
+            pixels.push(if dark(x, y) { BLACK } else { WHITE });
+        }
+    }
+
+    Image::new(pixels, width as u16, height as u16)
+}
+
+/// Bar and space widths in modules, starting with a bar.
+type Bars = Vec<u8>;
+
+/// Appends a pattern of modules (`1` dark, `0` light) as run lengths. The
+/// first pattern has to start with a dark module.
+fn push_modules(bars: &mut Bars, modules: &str) {
+    for module in modules.bytes() {
+        let dark = module == b'1';
+        let last_dark = bars.len() % 2 == 1;
+
+        if !bars.is_empty() && dark == last_dark {
+            *bars.last_mut().unwrap() += 1;
+        } else {
+            bars.push(1);
+        }
+    }
+}
+
+/// Appends element widths given as ASCII digits, continuing the alternation of
+/// bars and spaces.
+fn push_widths(bars: &mut Bars, widths: &str) {
+    bars.extend(widths.bytes().map(|w| w - b'0'));
+}
+
+fn digits(data: &[u8]) -> Option<Vec<u8>> {
+    data.iter()
+        .map(|&c| c.is_ascii_digit().then(|| c - b'0'))
+        .collect()
+}
+
+/// Check digit of EAN/UPC symbols, computed over the data digits.
+pub(crate) fn ean_check_digit(digits: &[u8]) -> u8 {
+    let sum: u32 = digits
+        .iter()
+        .rev()
+        .enumerate()
+        .map(|(i, &d)| d as u32 * if i % 2 == 0 { 3 } else { 1 })
+        .sum();
+
+    ((10 - sum % 10) % 10) as u8
+}
+
+/// Appends the check digit if only the data digits were given, or verifies
+/// it otherwise.
+fn with_check_digit(data: &[u8], length: usize) -> Option<Vec<u8>> {
+    let mut digits = digits(data)?;
+
+    if digits.len() == length - 1 {
+        digits.push(ean_check_digit(&digits));
+    } else if digits.len() != length || ean_check_digit(&digits[..length - 1]) != digits[length - 1]
+    {
+        return None;
+    }
+
+    Some(digits)
+}
+
+const EAN_L: [&str; 10] = [
+    "0001101", "0011001", "0010011", "0111101", "0100011", "0110001", "0101111", "0111011",
+    "0110111", "0001011",
+];
+
+const EAN_G: [&str; 10] = [
+    "0100111", "0110011", "0011011", "0100001", "0011101", "0111001", "0000101", "0010001",
+    "0001001", "0010111",
+];
+
+const EAN_R: [&str; 10] = [
+    "1110010", "1100110", "1101100", "1000010", "1011100", "1001110", "1010000", "1000100",
+    "1001000", "1110100",
+];
+
+/// Parity of the left half of EAN-13, selected by the first digit.
+const EAN13_PARITY: [&str; 10] = [
+    "LLLLLL", "LLGLGG", "LLGGLG", "LLGGGL", "LGLLGG", "LGGLLG", "LGGGLL", "LGLGLG", "LGLGGL",
+    "LGGLGL",
+];
+
+/// Parity of UPC-E with number system 0, selected by the check digit.
+const UPCE_PARITY: [&str; 10] = [
+    "GGGLLL", "GGLGLL", "GGLLGL", "GGLLLG", "GLGGLL", "GLLGGL", "GLLLGG", "GLGLGL", "GLGLLG",
+    "GLLGLG",
+];
+
+fn ean13_bars(digits: &[u8]) -> Bars {
+    let mut bars = Bars::new();
+    push_modules(&mut bars, "101");
+    for (i, parity) in EAN13_PARITY[digits[0] as usize].bytes().enumerate() {
+        let table = if parity == b'L' { &EAN_L } else { &EAN_G };
+        push_modules(&mut bars, table[digits[i + 1] as usize]);
+    }
+    push_modules(&mut bars, "01010");
+    for &digit in &digits[7..13] {
+        push_modules(&mut bars, EAN_R[digit as usize]);
+    }
+    push_modules(&mut bars, "101");
+    bars
+}
+
+fn ean13(data: &[u8]) -> Option<Bars> {
+    Some(ean13_bars(&with_check_digit(data, 13)?))
+}
+
+fn upc_a(data: &[u8]) -> Option<Bars> {
+    let mut digits = vec![0];
+    digits.extend(with_check_digit(data, 12)?);
+    Some(ean13_bars(&digits))
+}
+
+fn ean8(data: &[u8]) -> Option<Bars> {
+    let digits = with_check_digit(data, 8)?;
+
+    let mut bars = Bars::new();
+    push_modules(&mut bars, "101");
+    for &digit in &digits[..4] {
+        push_modules(&mut bars, EAN_L[digit as usize]);
+    }
+    push_modules(&mut bars, "01010");
+    for &digit in &digits[4..] {
+        push_modules(&mut bars, EAN_R[digit as usize]);
+    }
+    push_modules(&mut bars, "101");
+    Some(bars)
+}
+
+/// Expands the six UPC-E data digits into the eleven UPC-A data digits the
+/// check digit is computed from.
+pub(crate) fn upc_e_to_upc_a(number_system: u8, d: &[u8]) -> [u8; 11] {
+    let ns = number_system;
+    match d[5] {
+        0..=2 => [ns, d[0], d[1], d[5], 0, 0, 0, 0, d[2], d[3], d[4]],
+        3 => [ns, d[0], d[1], d[2], 0, 0, 0, 0, 0, d[3], d[4]],
+        4 => [ns, d[0], d[1], d[2], d[3], 0, 0, 0, 0, 0, d[4]],
+        _ => [ns, d[0], d[1], d[2], d[3], d[4], 0, 0, 0, 0, d[5]],
+    }
+}
+
+fn upc_e(data: &[u8]) -> Option<Bars> {
+    let digits = digits(data)?;
+
+    let (number_system, body, check) = match digits.len() {
+        6 => (0, &digits[..], None),
+        7 => (digits[0], &digits[1..], None),
+        8 => (digits[0], &digits[1..7], Some(digits[7])),
+        _ => return None,
+    };
+
+    if number_system > 1 {
+        return None;
+    }
+
+    let expected = ean_check_digit(&upc_e_to_upc_a(number_system, body));
+    if check.is_some_and(|check| check != expected) {
+        return None;
+    }
+
+    let mut bars = Bars::new();
+    push_modules(&mut bars, "101");
+    for (i, parity) in UPCE_PARITY[expected as usize].bytes().enumerate() {
+        // Number system 1 uses the inverted parity pattern.
+        let even = (parity == b'G') != (number_system == 1);
+        let table = if even { &EAN_G } else { &EAN_L };
+        push_modules(&mut bars, table[body[i] as usize]);
+    }
+    push_modules(&mut bars, "010101");
+    Some(bars)
+}
+
+/// Code 39 characters and their nine elements, `1` being wide.
+const CODE39: [(u8, &str); 44] = [
+    (b'0', "000110100"),
+    (b'1', "100100001"),
+    (b'2', "001100001"),
+    (b'3', "101100000"),
+    (b'4', "000110001"),
+    (b'5', "100110000"),
+    (b'6', "001110000"),
+    (b'7', "000100101"),
+    (b'8', "100100100"),
+    (b'9', "001100100"),
+    (b'A', "100001001"),
+    (b'B', "001001001"),
+    (b'C', "101001000"),
+    (b'D', "000011001"),
+    (b'E', "100011000"),
+    (b'F', "001011000"),
+    (b'G', "000001101"),
+    (b'H', "100001100"),
+    (b'I', "001001100"),
+    (b'J', "000011100"),
+    (b'K', "100000011"),
+    (b'L', "001000011"),
+    (b'M', "101000010"),
+    (b'N', "000010011"),
+    (b'O', "100010010"),
+    (b'P', "001010010"),
+    (b'Q', "000000111"),
+    (b'R', "100000110"),
+    (b'S', "001000110"),
+    (b'T', "000010110"),
+    (b'U', "110000001"),
+    (b'V', "011000001"),
+    (b'W', "111000000"),
+    (b'X', "010010001"),
+    (b'Y', "110010000"),
+    (b'Z', "011010000"),
+    (b'-', "010000101"),
+    (b'.', "110000100"),
+    (b' ', "011000100"),
+    (b'$', "010101000"),
+    (b'/', "010100010"),
+    (b'+', "010001010"),
+    (b'%', "000101010"),
+    (b'*', "010010100"),
+];
+
+/// Width of a wide element in modules, for the symbologies built from
+/// narrow and wide elements.
+const WIDE: u8 = 3;
+
+fn push_wide_narrow(bars: &mut Bars, elements: &str) {
+    bars.extend(
+        elements
+            .bytes()
+            .map(|element| if element == b'1' { WIDE } else { 1 }),
+    );
+}
+
+fn code39(data: &[u8]) -> Option<Bars> {
+    // The printer adds the start and stop characters unless they are given.
+    let data = data
+        .strip_prefix(b"*")
+        .and_then(|data| data.strip_suffix(b"*"))
+        .unwrap_or(data);
+
+    let mut bars = Bars::new();
+    for &c in std::iter::once(&b'*')
+        .chain(data)
+        .chain(std::iter::once(&b'*'))
+    {
+        let (_, elements) = CODE39.iter().find(|(candidate, _)| *candidate == c)?;
+        if !bars.is_empty() {
+            // Inter-character gap.
+            bars.push(1);
+        }
+        push_wide_narrow(&mut bars, elements);
+    }
+    Some(bars)
+}
+
+const ITF: [&str; 10] = [
+    "00110", "10001", "01001", "11000", "00101", "10100", "01100", "00011", "10010", "01010",
+];
+
+fn itf(data: &[u8]) -> Option<Bars> {
+    let digits = digits(data)?;
+    if digits.is_empty() || digits.len() % 2 != 0 {
+        return None;
+    }
+
+    let mut bars = vec![1, 1, 1, 1];
+    for pair in digits.chunks(2) {
+        let (bar, space) = (
+            ITF[pair[0] as usize].as_bytes(),
+            ITF[pair[1] as usize].as_bytes(),
+        );
+        for i in 0..5 {
+            for element in [bar[i], space[i]] {
+                bars.push(if element == b'1' { WIDE } else { 1 });
+            }
+        }
+    }
+    bars.extend_from_slice(&[WIDE, 1, 1]);
+    Some(bars)
+}
+
+const CODABAR: [(u8, &str); 20] = [
+    (b'0', "0000011"),
+    (b'1', "0000110"),
+    (b'2', "0001001"),
+    (b'3', "1100000"),
+    (b'4', "0010010"),
+    (b'5', "1000010"),
+    (b'6', "0100001"),
+    (b'7', "0100100"),
+    (b'8', "0110000"),
+    (b'9', "1001000"),
+    (b'-', "0001100"),
+    (b'$', "0011000"),
+    (b':', "1000101"),
+    (b'/', "1010001"),
+    (b'.', "1010100"),
+    (b'+', "0010101"),
+    (b'A', "0011010"),
+    (b'B', "0101001"),
+    (b'C', "0001011"),
+    (b'D', "0001110"),
+];
+
+fn codabar(data: &[u8]) -> Option<Bars> {
+    let is_start_stop = |c: &u8| matches!(c.to_ascii_uppercase(), b'A'..=b'D');
+
+    if data.len() < 2 || !is_start_stop(data.first()?) || !is_start_stop(data.last()?) {
+        return None;
+    }
+
+    let mut bars = Bars::new();
+    for &c in data {
+        let c = c.to_ascii_uppercase();
+        let (_, elements) = CODABAR.iter().find(|(candidate, _)| *candidate == c)?;
+        if !bars.is_empty() {
+            bars.push(1);
+        }
+        push_wide_narrow(&mut bars, elements);
+    }
+    Some(bars)
+}
+
+/// Code 93 element widths, indexed by character value.
+const CODE93: [&str; 47] = [
+    "131112", "111213", "111312", "111411", "121113", "121212", "121311", "111114", "131211",
+    "141111", "211113", "211212", "211311", "221112", "221211", "231111", "112113", "112212",
+    "112311", "122112", "132111", "111123", "111222", "111321", "121122", "131121", "212112",
+    "212211", "211122", "211221", "221121", "222111", "112122", "112221", "122121", "123111",
+    "121131", "311112", "311211", "321111", "112131", "113121", "211131", "121221", "312111",
+    "311121", "122211",
+];
+
+const CODE93_START_STOP: &str = "111141";
+
+const CODE93_CHARS: &[u8; 43] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZ-. $/+%";
+
+/// Shift characters of Code 93 full ASCII.
+const CODE93_DOLLAR: u8 = 43;
+const CODE93_PERCENT: u8 = 44;
+const CODE93_SLASH: u8 = 45;
+const CODE93_PLUS: u8 = 46;
+
+/// Converts one ASCII character into Code 93 values, using the shift
+/// characters for the ones outside the basic set.
+fn code93_values(c: u8) -> Option<Vec<u8>> {
+    let value = |c: u8| CODE93_CHARS.iter().position(|&x| x == c).unwrap() as u8;
+
+    let values = match c {
+        b'0'..=b'9' | b'A'..=b'Z' | b'-' | b'.' | b' ' | b'$' | b'/' | b'+' | b'%' => {
+            vec![value(c)]
+        }
+        0 => vec![CODE93_PERCENT, value(b'U')],
+        1..=26 => vec![CODE93_DOLLAR, value(b'A' + c - 1)],
+        27..=31 => vec![CODE93_PERCENT, value(b'A' + c - 27)],
+        b'!'..=b',' => vec![CODE93_SLASH, value(b'A' + c - b'!')],
+        b':' => vec![CODE93_SLASH, value(b'Z')],
+        b';'..=b'?' => vec![CODE93_PERCENT, value(b'F' + c - b';')],
+        b'@' => vec![CODE93_PERCENT, value(b'V')],
+        b'['..=b'_' => vec![CODE93_PERCENT, value(b'K' + c - b'[')],
+        b'`' => vec![CODE93_PERCENT, value(b'W')],
+        b'a'..=b'z' => vec![CODE93_PLUS, value(b'A' + c - b'a')],
+        b'{'..=127 => vec![CODE93_PERCENT, value(b'P' + c - b'{')],
+        _ => return None,
+    };
+
+    Some(values)
+}
+
+fn code93_check(values: &[u8], max_weight: u32) -> u8 {
+    let sum: u32 = values
+        .iter()
+        .rev()
+        .enumerate()
+        .map(|(i, &v)| v as u32 * (i as u32 % max_weight + 1))
+        .sum();
+
+    (sum % 47) as u8
+}
+
+fn code93(data: &[u8]) -> Option<Bars> {
+    let mut values = Vec::new();
+    for &c in data {
+        values.extend(code93_values(c)?);
+    }
+    values.push(code93_check(&values, 20));
+    values.push(code93_check(&values, 15));
+
+    let mut bars = Bars::new();
+    push_widths(&mut bars, CODE93_START_STOP);
+    for value in values {
+        push_widths(&mut bars, CODE93[value as usize]);
+    }
+    push_widths(&mut bars, CODE93_START_STOP);
+    // Termination bar.
+    bars.push(1);
+    Some(bars)
+}
+
+/// Code 128 element widths, indexed by symbol value.
+const CODE128: [&str; 107] = [
+    "212222", "222122", "222221", "121223", "121322", "131222", "122213", "122312", "132212",
+    "221213", "221312", "231212", "112232", "122132", "122231", "113222", "123122", "123221",
+    "223211", "221132", "221231", "213212", "223112", "312131", "311222", "321122", "321221",
+    "312212", "322112", "322211", "212123", "212321", "232121", "111323", "131123", "131321",
+    "112313", "132113", "132311", "211313", "231113", "231311", "112133", "112331", "132131",
+    "113123", "113321", "133121", "313121", "211331", "231131", "213113", "213311", "213131",
+    "311123", "311321", "331121", "312113", "312311", "332111", "314111", "221411", "431111",
+    "111224", "111422", "121124", "121421", "141122", "141221", "112214", "112412", "122114",
+    "122411", "142112", "142211", "241211", "221114", "413111", "241112", "134111", "111242",
+    "121142", "121241", "114212", "124112", "124211", "411212", "421112", "421211", "212141",
+    "214121", "412121", "111143", "111341", "131141", "114113", "114311", "411113", "411311",
+    "113141", "114131", "311141", "411131", "211412", "211214", "211232", "2331112",
+];
+
+const CODE128_START: [u8; 3] = [103, 104, 105];
+const CODE128_STOP: u8 = 106;
+const CODE128_FNC1: u8 = 102;
+
+#[derive(Clone, Copy, PartialEq)]
+enum CodeSet {
+    A,
+    B,
+    C,
+}
+
+impl CodeSet {
+    const fn index(self) -> usize {
+        match self {
+            CodeSet::A => 0,
+            CodeSet::B => 1,
+            CodeSet::C => 2,
+        }
+    }
+}
+
+/// Converts `GS k` CODE128 data, where `{A`, `{B`, `{C` select a code set,
+/// `{1`..`{4` are the function characters, `{S` is SHIFT and `{{` a literal
+/// brace, into symbol values including the start character. In code set C
+/// each byte holds two digits. Data without a leading code set selection is
+/// taken as code set B.
+fn code128_values(data: &[u8], gs1: bool) -> Option<Vec<u8>> {
+    let mut data = data;
+    let mut set = CodeSet::B;
+
+    if let [b'{', c @ (b'A' | b'B' | b'C'), rest @ ..] = data {
+        set = match c {
+            b'A' => CodeSet::A,
+            b'B' => CodeSet::B,
+            _ => CodeSet::C,
+        };
+        data = rest;
+    }
+
+    let mut values = vec![CODE128_START[set.index()]];
+    if gs1 {
+        values.push(CODE128_FNC1);
+    }
+
+    let mut shifted = false;
+    let mut i = 0;
+    while i < data.len() {
+        let c = data[i];
+
+        if c == b'{' && data.get(i + 1) != Some(&b'{') {
+            let function = *data.get(i + 1)?;
+            i += 2;
+
+            let value = match (function, set) {
+                (b'A', CodeSet::A) | (b'B', CodeSet::B) | (b'C', CodeSet::C) => continue,
+                (b'A', _) => 101,
+                (b'B', CodeSet::A) | (b'B', CodeSet::C) => 100,
+                (b'C', _) => 99,
+                (b'1', _) => CODE128_FNC1,
+                (b'2', CodeSet::A | CodeSet::B) => 97,
+                (b'3', CodeSet::A | CodeSet::B) => 96,
+                (b'4', CodeSet::A) => 101,
+                (b'4', CodeSet::B) => 100,
+                (b'S', CodeSet::A | CodeSet::B) => {
+                    shifted = true;
+                    values.push(98);
+                    continue;
+                }
+                _ => return None,
+            };
+
+            match function {
+                b'A' => set = CodeSet::A,
+                b'B' => set = CodeSet::B,
+                b'C' => set = CodeSet::C,
+                _ => {}
+            }
+
+            values.push(value);
+            continue;
+        }
+
+        // `{{` stands for a single brace.
+        if c == b'{' {
+            i += 1;
+        }
+
+        let current = match (set, shifted) {
+            (CodeSet::A, true) => CodeSet::B,
+            (CodeSet::B, true) => CodeSet::A,
+            (set, _) => set,
+        };
+        shifted = false;
+
+        let value = match current {
+            CodeSet::A => match c {
+                0x00..=0x1f => c + 64,
+                0x20..=0x5f => c - 32,
+                _ => return None,
+            },
+            CodeSet::B => match c {
+                0x20..=0x7f => c - 32,
+                _ => return None,
+            },
+            CodeSet::C => match c {
+                0..=99 => c,
+                _ => return None,
+            },
+        };
+
+        values.push(value);
+        i += 1;
+    }
+
+    Some(values)
+}
+
+fn code128(data: &[u8], gs1: bool) -> Option<Bars> {
+    let mut values = code128_values(data, gs1)?;
+
+    let check = values
+        .iter()
+        .enumerate()
+        .map(|(i, &v)| v as u32 * (i as u32).max(1))
+        .sum::<u32>()
+        % 103;
+    values.push(check as u8);
+    values.push(CODE128_STOP);
+
+    let mut bars = Bars::new();
+    for value in values {
+        push_widths(&mut bars, CODE128[value as usize]);
+    }
+    Some(bars)
+}
@@ -0,0 +1,553 @@
+//! An HTTP server that prints jobs sent by browsers and other programs that
+//! can't reach the printers themselves.
+//!
+//! The endpoints are:
+//!
+//! - `POST /printers/{name}/jobs` queues a job on a printer. A body of type
+//!   `application/json` is a serialized [`Receipt`] and a body of type
+//!   `application/octet-stream` is ESC/POS data sent as is, other types are
+//!   refused. Answers `202` with the job.
+//! - `GET /jobs/{id}` returns a job, whose `status` is `queued`, `printing`,
+//!   `done` or `failed`, with the `error` of a failed job.
+//! - `GET /printers` and `GET /printers/{name}` return the printers and
+//!   their queues.
+//! - `GET /printers/{name}/status` asks the printer for its status, after
+//!   the jobs queued before, and returns whether it is online and whether
+//!   its drawer is open.
+//!
+//! Errors are answered with a JSON object with an `error` message.
+//!
+//! Web pages can only call the server from the origins allowed with
+//! [`PrintServer::allow_origin`], requests sent by other pages are refused
+//! with `403`, so that any site a user visits can't print on their printers.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    io::{self, Read, Write},
+    net::{SocketAddr, ToSocketAddrs},
+    panic::{self, AssertUnwindSafe},
+    sync::{mpsc, Arc, Mutex},
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+
+use serde::Serialize;
+use tiny_http::{Header, Method, Request, Response};
+
+use crate::{
+    printer::{BasePrinter, Printer},
+    profile::PrinterProfile,
+    receipt::Receipt,
+    status::DrawerStatus,
+    transport::Destination,
+};
+
+/// The largest job accepted.
+const MAX_JOB_SIZE: u64 = 16 * 1024 * 1024;
+
+/// Finished jobs kept for `GET /jobs/{id}`, the older ones are forgotten.
+const KEPT_JOBS: usize = 1000;
+
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The printers served and how to reach them.
+///
+/// # Example
+///
+/// ```ignore
+/// let mut server = PrintServer::new();
+/// server
+///     .add_printer("kitchen", "tcp:192.168.1.50".parse()?, PrinterProfile::EPSON_TM_T88)
+///     .add_printer("counter", "/dev/usb/lp0".parse()?, PrinterProfile::EPSON_TM_T20);
+///
+/// let running = server.start("127.0.0.1:8080")?;
+/// running.wait();
+/// ```
+///
+/// A job is then printed with:
+///
+/// ```text
+/// curl -H 'Content-Type: application/json' -d @receipt.json \
+///     http://127.0.0.1:8080/printers/kitchen/jobs
+/// ```
+#[derive(Clone, Debug)]
+pub struct PrintServer {
+    printers: Vec<(String, Destination, PrinterProfile)>,
+    origins: Vec<String>,
+    timeout: Duration,
+}
+
+/// A started [`PrintServer`].
+pub struct RunningServer {
+    address: SocketAddr,
+    http: Arc<tiny_http::Server>,
+    thread: JoinHandle<()>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum JobStatus {
+    Queued,
+    Printing,
+    Done,
+    Failed,
+}
+
+#[derive(Clone, Debug, Serialize)]
+struct Job {
+    id: u64,
+    printer: String,
+    status: JobStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, Serialize)]
+struct Queue {
+    name: String,
+    destination: String,
+    profile: &'static str,
+    queued: usize,
+    printing: bool,
+    printed: u64,
+    failed: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    last_error: Option<String>,
+}
+
+#[derive(Clone, Copy, Debug, Serialize)]
+struct Status {
+    online: bool,
+    drawer_open: bool,
+}
+
+enum Task {
+    Print { id: u64, data: Vec<u8> },
+    Status(mpsc::Sender<io::Result<Status>>),
+}
+
+/// A printer, as seen by the HTTP thread.
+struct Served {
+    profile: PrinterProfile,
+    tasks: mpsc::Sender<Task>,
+    queue: Arc<Mutex<Queue>>,
+}
+
+type Jobs = Arc<Mutex<BTreeMap<u64, Job>>>;
+
+impl PrintServer {
+    pub fn new() -> Self {
+        Self {
+            printers: vec![],
+            origins: vec![],
+            timeout: DEFAULT_TIMEOUT,
+        }
+    }
+
+    /// Serves a printer at `/printers/{name}`. Names should be made of
+    /// characters that don't need escaping in URLs.
+    pub fn add_printer(
+        &mut self,
+        name: &str,
+        destination: Destination,
+        profile: PrinterProfile,
+    ) -> &mut Self {
+        self.printers.push((name.to_string(), destination, profile));
+        self
+    }
+
+    /// Allows the web pages of `origin`, such as `https://pos.example.com`,
+    /// to call the server. No origin is allowed by default, and `*` allows
+    /// any.
+    pub fn allow_origin(&mut self, origin: &str) -> &mut Self {
+        self.origins.push(origin.trim_end_matches('/').to_string());
+        self
+    }
+
+    /// Sets how long to wait for a printer to connect, accept data or answer
+    /// a status request, 10 seconds by default.
+    pub fn set_timeout(&mut self, timeout: Duration) -> &mut Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Listens on `address` and serves requests on other threads, with a
+    /// thread per printer printing its queue in order. Port 0 picks a free
+    /// port, returned by [`RunningServer::address`].
+    pub fn start(&self, address: impl ToSocketAddrs) -> io::Result<RunningServer> {
+        let http = Arc::new(tiny_http::Server::http(address).map_err(io::Error::other)?);
+        let address = http
+            .server_addr()
+            .to_ip()
+            .ok_or_else(|| io::Error::other("not listening on an IP address"))?;
+
+        let jobs: Jobs = Arc::default();
+        let mut printers = HashMap::new();
+        for (name, destination, profile) in &self.printers {
+            let queue = Arc::new(Mutex::new(Queue {
+                name: name.clone(),
+                destination: destination.to_string(),
+                profile: profile.id,
+                ..Queue::default()
+            }));
+            let (tasks, receiver) = mpsc::channel();
+            let worker = Worker {
+                destination: destination.clone(),
+                profile: *profile,
+                timeout: self.timeout,
+                queue: queue.clone(),
+                jobs: jobs.clone(),
+            };
+            thread::spawn(move || worker.run(receiver));
+
+            printers.insert(
+                name.clone(),
+                Served {
+                    profile: *profile,
+                    tasks,
+                    queue,
+                },
+            );
+        }
+
+        let names: Vec<String> = self
+            .printers
+            .iter()
+            .map(|(name, ..)| name.clone())
+            .collect();
+        let origins = self.origins.clone();
+        let server = http.clone();
+        let thread = thread::spawn(move || {
+            let mut next_id = 1;
+            for request in server.incoming_requests() {
+                let context = Context {
+                    names: &names,
+                    origins: &origins,
+                    printers: &printers,
+                    jobs: &jobs,
+                    next_id: &mut next_id,
+                };
+                // A request that panics is dropped, which answers 500, and
+                // the others are still served.
+                let _ = panic::catch_unwind(AssertUnwindSafe(|| context.handle(request)));
+            }
+        });
+
+        Ok(RunningServer {
+            address,
+            http,
+            thread,
+        })
+    }
+}
+
+impl Default for PrintServer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RunningServer {
+    /// The address the server listens on.
+    pub fn address(&self) -> SocketAddr {
+        self.address
+    }
+
+    /// Serves requests until the process ends.
+    pub fn wait(self) {
+        let _ = self.thread.join();
+    }
+
+    /// Stops taking requests. The jobs already queued are still printed.
+    pub fn stop(self) {
+        self.http.unblock();
+        let _ = self.thread.join();
+    }
+}
+
+struct Context<'a> {
+    names: &'a [String],
+    origins: &'a [String],
+    printers: &'a HashMap<String, Served>,
+    jobs: &'a Jobs,
+    next_id: &'a mut u64,
+}
+
+impl<'a> Context<'a> {
+    fn handle(self, mut request: Request) {
+        let path = request.url().split('?').next().unwrap_or("").to_string();
+        let segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+        let method = request.method().clone();
+
+        // Browsers send the origin of the page, other clients don't.
+        let allowed = match header(&request, "Origin") {
+            Some(origin) => {
+                if !self
+                    .origins
+                    .iter()
+                    .any(|allowed| allowed == "*" || allowed == origin)
+                {
+                    let message = format!("the origin {} isn't allowed", origin);
+                    return respond(request, None, Err((403, message)));
+                }
+                Some(origin.to_string())
+            }
+            None => None,
+        };
+
+        let response = match (&method, segments.as_slice()) {
+            (Method::Options, _) => Ok((204, None)),
+            (Method::Get, ["printers"]) => {
+                let queues: Vec<Queue> = self
+                    .names
+                    .iter()
+                    .map(|name| self.printers[name].queue.lock().unwrap().clone())
+                    .collect();
+                Ok((200, Some(json(&queues))))
+            }
+            (Method::Get, ["printers", name]) => self
+                .printer(name)
+                .map(|printer| (200, Some(json(&*printer.queue.lock().unwrap())))),
+            (Method::Get, ["printers", name, "status"]) => match self.printer(name) {
+                Ok(printer) => {
+                    let (sender, receiver) = mpsc::channel();
+                    let _ = printer.tasks.send(Task::Status(sender));
+                    // Printing the queue first can take a while, so the
+                    // answer is waited for on its own thread.
+                    thread::spawn(move || {
+                        let response = match receiver.recv() {
+                            Ok(Ok(status)) => Ok((200, Some(json(&status)))),
+                            Ok(Err(error)) => Err((502, error.to_string())),
+                            Err(_) => Err((503, "the server is stopping".to_string())),
+                        };
+                        respond(request, allowed.as_deref(), response);
+                    });
+                    return;
+                }
+                Err(error) => Err(error),
+            },
+            (Method::Post, ["printers", name, "jobs"]) => self.submit(name, &mut request),
+            (Method::Get, ["jobs", id]) => {
+                let job = id
+                    .parse::<u64>()
+                    .ok()
+                    .and_then(|id| self.jobs.lock().unwrap().get(&id).cloned());
+                match job {
+                    Some(job) => Ok((200, Some(json(&job)))),
+                    None => Err((404, format!("no job {}", id))),
+                }
+            }
+            (
+                _,
+                ["printers"] | ["printers", _] | ["printers", _, "status" | "jobs"] | ["jobs", _],
+            ) => Err((405, format!("{} isn't allowed on {}", method, path))),
+            _ => Err((404, format!("nothing at {}", path))),
+        };
+
+        respond(request, allowed.as_deref(), response);
+    }
+
+    fn printer(&self, name: &str) -> Result<&'a Served, (u16, String)> {
+        self.printers
+            .get(name)
+            .ok_or_else(|| (404, format!("no printer {:?}", name)))
+    }
+
+    fn submit(
+        self,
+        name: &str,
+        request: &mut Request,
+    ) -> Result<(u16, Option<String>), (u16, String)> {
+        let printer = self.printer(name)?;
+
+        let mut body = vec![];
+        request
+            .as_reader()
+            .take(MAX_JOB_SIZE + 1)
+            .read_to_end(&mut body)
+            .map_err(|error| (400, format!("can't read the job: {}", error)))?;
+        if body.len() as u64 > MAX_JOB_SIZE {
+            return Err((413, format!("jobs are limited to {} bytes", MAX_JOB_SIZE)));
+        }
+
+        let content_type = header(request, "Content-Type")
+            .and_then(|value| value.split(';').next())
+            .unwrap_or("")
+            .trim()
+            .to_ascii_lowercase();
+        let data = match content_type.as_str() {
+            "application/json" => {
+                let receipt: Receipt = serde_json::from_slice(&body)
+                    .map_err(|error| (400, format!("invalid receipt: {}", error)))?;
+                let mut base = BasePrinter::new(printer.profile);
+                base.initialize();
+                receipt
+                    .print(&mut base)
+                    .map_err(|error| (400, format!("can't print the receipt: {}", error)))?;
+                base.get_data()
+            }
+            "application/octet-stream" => body,
+            _ => {
+                return Err((
+                    415,
+                    "jobs are application/json receipts or application/octet-stream data"
+                        .to_string(),
+                ))
+            }
+        };
+
+        let id = *self.next_id;
+        *self.next_id += 1;
+        let job = Job {
+            id,
+            printer: name.to_string(),
+            status: JobStatus::Queued,
+            error: None,
+        };
+
+        {
+            let mut jobs = self.jobs.lock().unwrap();
+            jobs.insert(id, job.clone());
+            while jobs.len() > KEPT_JOBS {
+                let finished = jobs
+                    .iter()
+                    .find(|(_, job)| matches!(job.status, JobStatus::Done | JobStatus::Failed))
+                    .map(|(id, _)| *id);
+                match finished {
+                    Some(id) => jobs.remove(&id),
+                    None => break,
+                };
+            }
+        }
+        printer.queue.lock().unwrap().queued += 1;
+        let _ = printer.tasks.send(Task::Print { id, data });
+
+        Ok((202, Some(json(&job))))
+    }
+}
+
+/// The value of the header `field` of `request`.
+fn header<'r>(request: &'r Request, field: &'static str) -> Option<&'r str> {
+    request
+        .headers()
+        .iter()
+        .find(|header| header.field.equiv(field))
+        .map(|header| header.value.as_str())
+}
+
+/// Answers with a status code and a JSON body, or a JSON error, allowing
+/// `origin` to read it.
+fn respond(
+    request: Request,
+    origin: Option<&str>,
+    response: Result<(u16, Option<String>), (u16, String)>,
+) {
+    let (code, body) = match response {
+        Ok((code, body)) => (code, body.unwrap_or_default()),
+        Err((code, message)) => (code, json(&HashMap::from([("error", message)]))),
+    };
+
+    let mut response = Response::from_string(body).with_status_code(code);
+    let mut headers = vec![("Content-Type", "application/json")];
+    if let Some(origin) = origin {
+        headers.extend([
+            ("Access-Control-Allow-Origin", origin),
+            ("Access-Control-Allow-Methods", "GET, POST, OPTIONS"),
+            ("Access-Control-Allow-Headers", "Content-Type"),
+            ("Vary", "Origin"),
+        ]);
+    }
+    for (field, value) in headers {
+        response.add_header(Header::from_bytes(field, value).unwrap());
+    }
+
+    // The client may be gone, there is no one else to tell.
+    let _ = request.respond(response);
+}
+
+fn json(value: &impl Serialize) -> String {
+    serde_json::to_string(value).unwrap()
+}
+
+/// Prints the jobs of a printer, one at a time.
+struct Worker {
+    destination: Destination,
+    profile: PrinterProfile,
+    timeout: Duration,
+    queue: Arc<Mutex<Queue>>,
+    jobs: Jobs,
+}
+
+impl Worker {
+    fn run(self, tasks: mpsc::Receiver<Task>) {
+        for task in tasks {
+            match task {
+                Task::Print { id, data } => {
+                    self.update(id, JobStatus::Printing, None);
+                    {
+                        let mut queue = self.queue.lock().unwrap();
+                        queue.queued -= 1;
+                        queue.printing = true;
+                    }
+
+                    let result = self.destination.send(&data, self.timeout);
+
+                    let mut queue = self.queue.lock().unwrap();
+                    queue.printing = false;
+                    match result {
+                        Ok(()) => {
+                            queue.printed += 1;
+                            self.update(id, JobStatus::Done, None);
+                        }
+                        Err(error) => {
+                            queue.failed += 1;
+                            queue.last_error = Some(error.to_string());
+                            self.update(id, JobStatus::Failed, Some(error.to_string()));
+                        }
+                    }
+                }
+                Task::Status(reply) => {
+                    let _ = reply.send(self.status());
+                }
+            }
+        }
+    }
+
+    fn update(&self, id: u64, status: JobStatus, error: Option<String>) {
+        if let Some(job) = self.jobs.lock().unwrap().get_mut(&id) {
+            job.status = status;
+            job.error = error;
+        }
+    }
+
+    /// Asks the printer for its status with `DLE EOT 1`. Files have no
+    /// status, and aren't opened so that the jobs written to them stay.
+    fn status(&self) -> io::Result<Status> {
+        if let Destination::File(path) = &self.destination {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                format!("{} is a file, which has no status", path.display()),
+            ));
+        }
+
+        let mut connection = self.destination.connect(self.timeout)?;
+        let mut printer = BasePrinter::new(self.profile);
+        printer.request_drawer_status();
+        connection.write_all(&printer.get_data())?;
+        connection.flush()?;
+
+        let mut status = [0];
+        connection.read_exact(&mut status)?;
+        let status = DrawerStatus::from_escpos(status[0]).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("unexpected status 0x{:02X}", status[0]),
+            )
+        })?;
+
+        Ok(Status {
+            online: !status.offline,
+            drawer_open: status.open,
+        })
+    }
+}
@@ -0,0 +1,717 @@
+use crate::constants::{ESC, GS};
+
+const SOH: u8 = 0x01;
+const ACK: u8 = 0x06;
+const BEL: u8 = 0x07;
+const SI: u8 = 0x0f;
+const DC2: u8 = 0x12;
+const SUB: u8 = 0x1a;
+const RS: u8 = 0x1e;
+
+/// Set absolute position
+///
+/// # Arguments
+///
+/// * `n1` - Position from the left margin in dots (LSB)
+/// * `n2` - Position from the left margin in dots (MSB)
+///
+/// # Returns
+///
+/// An array representing the command bytes
+///
+/// # Example
+///
+/// ```ignore
+/// let command = absolute_position(0x40, 0x00);
+/// ```
+pub fn absolute_position(n1: u8, n2: u8) -> [u8; 5] {
+    [ESC, GS, 0x41, n1, n2]
+}
+
+/// Specify position alignment
+///
+/// # Arguments
+///
+/// * `n` - Alignment (0: left, 1: center, 2: right)
+///
+/// # Returns
+///
+/// An array representing the command bytes
+///
+/// # Example
+///
+/// ```ignore
+/// let command = alignment(1);
+/// ```
+pub fn alignment(n: u8) -> [u8; 4] {
+    [ESC, GS, 0x61, n]
+}
+
+/// Request the automatic status, which includes the drawer compulsion
+/// switch
+///
+/// # Returns
+///
+/// An array representing the command bytes
+///
+/// # Example
+///
+/// ```ignore
+/// let command = automatic_status();
+/// ```
+pub fn automatic_status() -> [u8; 3] {
+    [ESC, ACK, SOH]
+}
+
+/// Print barcode
+///
+/// # Arguments
+///
+/// * `n1` - Barcode type
+/// * `n2` - HRI characters and line feed selection
+/// * `n3` - Mode, i.e. module width or narrow/wide ratio
+/// * `n4` - Barcode height in dots
+/// * `data` - Barcode data
+///
+/// # Returns
+///
+/// A vector containing the command bytes
+///
+/// # Example
+///
+/// ```ignore
+/// let command = barcode(6, 2, 2, 80, b"12345");
+/// ```
+pub fn barcode(n1: u8, n2: u8, n3: u8, n4: u8, data: &[u8]) -> Vec<u8> {
+    let mut command = vec![ESC, 0x62, n1, n2, n3, n4];
+    command.extend_from_slice(data);
+    command.push(RS);
+    command
+}
+
+/// Sound the buzzer connected as external device 1 or 2
+///
+/// # Arguments
+///
+/// * `m` - External device (1 or 2)
+/// * `t1` - On time, in units of 20 ms
+/// * `t2` - Off time, in units of 20 ms
+///
+/// # Returns
+///
+/// An array representing the command bytes
+///
+/// # Example
+///
+/// ```ignore
+/// let command = buzzer(1, 10, 10);
+/// ```
+pub fn buzzer(m: u8, t1: u8, t2: u8) -> [u8; 6] {
+    [ESC, GS, BEL, m, t1, t2]
+}
+
+/// Drive external device 1 or 2, usually a cash drawer
+///
+/// # Arguments
+///
+/// * `m` - External device (1 or 2)
+///
+/// # Returns
+///
+/// An array representing the command bytes
+///
+/// # Example
+///
+/// ```ignore
+/// let command = cash_draw(1);
+/// ```
+pub fn cash_draw(m: u8) -> [u8; 1] {
+    if m == 2 {
+        [SUB]
+    } else {
+        [BEL]
+    }
+}
+
+/// Set the drive pulse width of external device 1
+///
+/// # Arguments
+///
+/// * `n1` - Energizing time, in units of 10 ms
+/// * `n2` - Delay time, in units of 10 ms
+///
+/// # Returns
+///
+/// An array representing the command bytes
+///
+/// # Example
+///
+/// ```ignore
+/// let command = cash_draw_pulse(20, 20);
+/// ```
+pub fn cash_draw_pulse(n1: u8, n2: u8) -> [u8; 4] {
+    [ESC, BEL, n1, n2]
+}
+
+/// Set right-side character spacing
+///
+/// # Arguments
+///
+/// * `n` - Spacing in dots
+///
+/// # Returns
+///
+/// An array representing the command bytes
+///
+/// # Example
+///
+/// ```ignore
+/// let command = character_spacing(2);
+/// ```
+pub fn character_spacing(n: u8) -> [u8; 3] {
+    [ESC, 0x20, n]
+}
+
+/// Select code page
+///
+/// # Arguments
+///
+/// * `n` - Code page number
+///
+/// # Returns
+///
+/// An array representing the command bytes
+///
+/// # Example
+///
+/// ```ignore
+/// let command = code_page(1);
+/// ```
+pub fn code_page(n: u8) -> [u8; 4] {
+    [ESC, GS, 0x74, n]
+}
+
+/// Cut paper
+///
+/// # Arguments
+///
+/// * `n` - Cut mode (0: full cut, 1: partial cut, 2: feed and full cut, 3: feed and partial cut)
+///
+/// # Returns
+///
+/// An array representing the command bytes
+///
+/// # Example
+///
+/// ```ignore
+/// let command = cut(3);
+/// ```
+pub fn cut(n: u8) -> [u8; 3] {
+    [ESC, 0x64, n]
+}
+
+/// Select emphasized printing
+///
+/// # Arguments
+///
+/// * `n` - Mode (0: off, 1: on)
+///
+/// # Returns
+///
+/// An array representing the command bytes
+///
+/// # Example
+///
+/// ```ignore
+/// let command = emphasized(1);
+/// ```
+pub fn emphasized(n: u8) -> [u8; 2] {
+    [ESC, if n == 0 { 0x46 } else { 0x45 }]
+}
+
+/// Select font
+///
+/// # Arguments
+///
+/// * `n` - Font (0: font A, 1: font B)
+///
+/// # Returns
+///
+/// An array representing the command bytes
+///
+/// # Example
+///
+/// ```ignore
+/// let command = font(1);
+/// ```
+pub fn font(n: u8) -> [u8; 4] {
+    [ESC, 0x1e, 0x46, n]
+}
+
+/// Select highlight printing, which is white/black reverse on monochrome
+/// printers and red on two-color printers
+///
+/// # Arguments
+///
+/// * `n` - Mode (0: off, 1: on)
+///
+/// # Returns
+///
+/// An array representing the command bytes
+///
+/// # Example
+///
+/// ```ignore
+/// let command = highlight(1);
+/// ```
+pub fn highlight(n: u8) -> [u8; 2] {
+    [ESC, if n == 0 { 0x35 } else { 0x34 }]
+}
+
+/// Initialize printer
+///
+/// # Returns
+///
+/// An array representing the command bytes
+///
+/// # Example
+///
+/// ```ignore
+/// let command = initialize();
+/// ```
+pub fn initialize() -> [u8; 2] {
+    [ESC, 0x40]
+}
+
+/// Set left margin
+///
+/// # Arguments
+///
+/// * `n` - Left margin in characters
+///
+/// # Returns
+///
+/// An array representing the command bytes
+///
+/// # Example
+///
+/// ```ignore
+/// let command = left_margin(2);
+/// ```
+pub fn left_margin(n: u8) -> [u8; 3] {
+    [ESC, 0x6c, n]
+}
+
+/// PDF417: Set the error correction level
+///
+/// # Arguments
+///
+/// * `n` - Error correction level (0 to 8)
+///
+/// # Returns
+///
+/// An array containing the command bytes
+///
+/// # Example
+///
+/// ```ignore
+/// let command = pdf417_correction_level(1);
+/// ```
+pub fn pdf417_correction_level(n: u8) -> [u8; 6] {
+    [ESC, GS, 0x78, 0x53, 0x31, n]
+}
+
+/// PDF417: Set the width of the module
+///
+/// # Arguments
+///
+/// * `n` - Width of the module in dots (1 to 10)
+///
+/// # Returns
+///
+/// An array containing the command bytes
+///
+/// # Example
+///
+/// ```ignore
+/// let command = pdf417_module_width(3);
+/// ```
+pub fn pdf417_module_width(n: u8) -> [u8; 6] {
+    [ESC, GS, 0x78, 0x53, 0x32, n]
+}
+
+/// PDF417: Print the stored data
+///
+/// # Returns
+///
+/// An array containing the command bytes
+///
+/// # Example
+///
+/// ```ignore
+/// let command = pdf417_print();
+/// ```
+pub fn pdf417_print() -> [u8; 4] {
+    [ESC, GS, 0x78, 0x50]
+}
+
+/// PDF417: Set the module aspect ratio
+///
+/// # Arguments
+///
+/// * `n` - Height of the module as a multiple of its width (1 to 10)
+///
+/// # Returns
+///
+/// An array containing the command bytes
+///
+/// # Example
+///
+/// ```ignore
+/// let command = pdf417_row_height(3);
+/// ```
+pub fn pdf417_row_height(n: u8) -> [u8; 6] {
+    [ESC, GS, 0x78, 0x53, 0x33, n]
+}
+
+/// PDF417: Set the size as the number of rows and columns
+///
+/// # Arguments
+///
+/// * `p1` - Number of rows (0 for automatic, 3 to 90)
+/// * `p2` - Number of columns (0 for automatic, 1 to 30)
+///
+/// # Returns
+///
+/// An array containing the command bytes
+///
+/// # Example
+///
+/// ```ignore
+/// let command = pdf417_size(0, 0);
+/// ```
+pub fn pdf417_size(p1: u8, p2: u8) -> [u8; 8] {
+    [ESC, GS, 0x78, 0x53, 0x30, 0x01, p1, p2]
+}
+
+/// PDF417: Store the data
+///
+/// # Arguments
+///
+/// * `nl` - Length of data (LSB)
+/// * `nh` - Length of data (MSB)
+/// * `data` - Data to be stored
+///
+/// # Returns
+///
+/// A vector containing the command bytes
+///
+/// # Example
+///
+/// ```ignore
+/// let command = pdf417_store(0x03, 0x00, b"ABC");
+/// ```
+pub fn pdf417_store(nl: u8, nh: u8, data: &[u8]) -> Vec<u8> {
+    let mut command = vec![ESC, GS, 0x78, 0x44, nl, nh];
+    command.extend_from_slice(data);
+    command
+}
+
+/// QR Code: Select the cell size
+///
+/// # Arguments
+///
+/// * `n` - Cell size in dots (1 to 8)
+///
+/// # Returns
+///
+/// An array representing the command bytes
+///
+/// # Example
+///
+/// ```ignore
+/// let command = qr_code_cell_size(3);
+/// ```
+pub fn qr_code_cell_size(n: u8) -> [u8; 6] {
+    [ESC, GS, 0x79, 0x53, 0x32, n]
+}
+
+/// QR Code: Select the error correction level
+///
+/// # Arguments
+///
+/// * `n` - Error correction level (0: L, 1: M, 2: Q, 3: H)
+///
+/// # Returns
+///
+/// An array representing the command bytes
+///
+/// # Example
+///
+/// ```ignore
+/// let command = qr_code_correction_level(0);
+/// ```
+pub fn qr_code_correction_level(n: u8) -> [u8; 6] {
+    [ESC, GS, 0x79, 0x53, 0x31, n]
+}
+
+/// QR Code: Select the model
+///
+/// # Arguments
+///
+/// * `n` - Model (1: model 1, 2: model 2)
+///
+/// # Returns
+///
+/// An array representing the command bytes
+///
+/// # Example
+///
+/// ```ignore
+/// let command = qr_code_model(2);
+/// ```
+pub fn qr_code_model(n: u8) -> [u8; 6] {
+    [ESC, GS, 0x79, 0x53, 0x30, n]
+}
+
+/// QR Code: Print the stored symbol
+///
+/// # Returns
+///
+/// An array representing the command bytes
+///
+/// # Example
+///
+/// ```ignore
+/// let command = qr_code_print();
+/// ```
+pub fn qr_code_print() -> [u8; 4] {
+    [ESC, GS, 0x79, 0x50]
+}
+
+/// QR Code: Store the data with automatic mode analysis
+///
+/// # Arguments
+///
+/// * `nl` - Length of data (LSB)
+/// * `nh` - Length of data (MSB)
+/// * `data` - Data to be stored
+///
+/// # Returns
+///
+/// A vector containing the command bytes
+///
+/// # Example
+///
+/// ```ignore
+/// let command = qr_code_store(0x03, 0x00, b"ABC");
+/// ```
+pub fn qr_code_store(nl: u8, nh: u8, data: &[u8]) -> Vec<u8> {
+    let mut command = vec![ESC, GS, 0x79, 0x44, 0x31, 0x00, nl, nh];
+    command.extend_from_slice(data);
+    command
+}
+
+/// Raster: Enter raster mode, with the page length set to continuous
+///
+/// # Returns
+///
+/// An array representing the command bytes
+///
+/// # Example
+///
+/// ```ignore
+/// let command = raster_enter();
+/// ```
+pub fn raster_enter() -> [u8; 10] {
+    [ESC, 0x2a, 0x72, 0x41, ESC, 0x2a, 0x72, 0x50, 0x30, 0x00]
+}
+
+/// Raster: Quit raster mode
+///
+/// # Returns
+///
+/// An array representing the command bytes
+///
+/// # Example
+///
+/// ```ignore
+/// let command = raster_quit();
+/// ```
+pub fn raster_quit() -> [u8; 4] {
+    [ESC, 0x2a, 0x72, 0x42]
+}
+
+/// Raster: Transfer one line of raster data
+///
+/// # Arguments
+///
+/// * `n1` - Length of data (LSB)
+/// * `n2` - Length of data (MSB)
+/// * `data` - Line data, one bit per dot
+///
+/// # Returns
+///
+/// A vector containing the command bytes
+///
+/// # Example
+///
+/// ```ignore
+/// let command = raster_line(0x02, 0x00, &[0xff, 0x00]);
+/// ```
+pub fn raster_line(n1: u8, n2: u8, data: &[u8]) -> Vec<u8> {
+    let mut command = vec![0x62, n1, n2];
+    command.extend_from_slice(data);
+    command
+}
+
+/// Set relative position
+///
+/// # Arguments
+///
+/// * `n1` - Offset in dots (LSB)
+/// * `n2` - Offset in dots (MSB)
+///
+/// # Returns
+///
+/// An array representing the command bytes
+///
+/// # Example
+///
+/// ```ignore
+/// let command = relative_position(0x10, 0x00);
+/// ```
+pub fn relative_position(n1: u8, n2: u8) -> [u8; 5] {
+    [ESC, GS, 0x52, n1, n2]
+}
+
+/// Set right margin
+///
+/// # Arguments
+///
+/// * `n` - Right margin position in characters from the left edge
+///
+/// # Returns
+///
+/// An array representing the command bytes
+///
+/// # Example
+///
+/// ```ignore
+/// let command = right_margin(40);
+/// ```
+pub fn right_margin(n: u8) -> [u8; 3] {
+    [ESC, 0x51, n]
+}
+
+/// Specify smoothing
+///
+/// # Arguments
+///
+/// * `n` - Mode (0: off, 1: on)
+///
+/// # Returns
+///
+/// An array representing the command bytes
+///
+/// # Example
+///
+/// ```ignore
+/// let command = smoothing(1);
+/// ```
+pub fn smoothing(n: u8) -> [u8; 4] {
+    [ESC, GS, 0x62, n]
+}
+
+/// Set character expansion
+///
+/// # Arguments
+///
+/// * `n1` - Height expansion (0 to 5)
+/// * `n2` - Width expansion (0 to 5)
+///
+/// # Returns
+///
+/// An array representing the command bytes
+///
+/// # Example
+///
+/// ```ignore
+/// let command = text_size(1, 1);
+/// ```
+pub fn text_size(n1: u8, n2: u8) -> [u8; 4] {
+    [ESC, 0x69, n1, n2]
+}
+
+/// Select underline mode
+///
+/// # Arguments
+///
+/// * `n` - Mode (0: off, 1: on)
+///
+/// # Returns
+///
+/// An array representing the command bytes
+///
+/// # Example
+///
+/// ```ignore
+/// let command = underline(1);
+/// ```
+pub fn underline(n: u8) -> [u8; 3] {
+    [ESC, 0x2d, n]
+}
+
+/// Select upside-down printing
+///
+/// # Arguments
+///
+/// * `n` - Mode (0: off, 1: on)
+///
+/// # Returns
+///
+/// An array representing the command bytes
+///
+/// # Example
+///
+/// ```ignore
+/// let command = upside_down(1);
+/// ```
+pub fn upside_down(n: u8) -> [u8; 1] {
+    [if n == 0 { DC2 } else { SI }]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ends_barcode_data_with_rs() {
+        assert_eq!(barcode(4, 2, 2, 80, b"AB"), b"\x1bb\x04\x02\x02\x50AB\x1e");
+    }
+
+    #[test]
+    fn stores_qr_code_data_with_its_length() {
+        assert_eq!(qr_code_store(3, 0, b"abc"), b"\x1b\x1dyD1\x00\x03\x00abc");
+        assert_eq!(pdf417_store(3, 0, b"abc"), b"\x1b\x1dxD\x03\x00abc");
+    }
+
+    #[test]
+    fn selects_commands_by_argument() {
+        assert_eq!(cash_draw(1), [0x07]);
+        assert_eq!(cash_draw(2), [0x1a]);
+        assert_eq!(emphasized(1), *b"\x1bE");
+        assert_eq!(emphasized(0), *b"\x1bF");
+        assert_eq!(highlight(1), *b"\x1b4");
+        assert_eq!(highlight(0), *b"\x1b5");
+        assert_eq!(upside_down(1), [0x0f]);
+        assert_eq!(upside_down(0), [0x12]);
+    }
+
+    #[test]
+    fn prefixes_raster_lines() {
+        assert_eq!(raster_line(2, 0, &[0xff, 0x00]), b"b\x02\x00\xff\x00");
+        assert_eq!(raster_enter(), *b"\x1b*rA\x1b*rP0\x00");
+        assert_eq!(raster_quit(), *b"\x1b*rB");
+    }
+}
@@ -0,0 +1,4 @@
+mod command;
+mod printer;
+
+pub use printer::StarPrinter;
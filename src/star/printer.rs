@@ -0,0 +1,837 @@
+use crate::{
+    barcode,
+    character_set::CharacterSet,
+    constants::LF,
+    error::{BarcodeError, Error},
+    printer::{
+        self, Align, AztecOptions, BarcodeOptions, BarcodeType, BarcodeWidth, CashDrawerPin,
+        DataMatrixOptions, HriPosition, Length, MaxiCodeOptions, Pdf417Correction, Pdf417Options,
+        PrintMode, Printer, QRCodeCellSize, QRCodeCorrection, QRCodeModel, QRCodeOptions,
+        TextColor, TextFont, TextSize, TextStyle, TextUnderline,
+    },
+    profile::{CutFeed, PrinterProfile},
+    render,
+    state::{self, PrinterState},
+    Image,
+};
+
+use super::command;
+
+/// Code pages of Star Line Mode, selected with `ESC GS t`.
+const STAR_CODE_PAGES: &[(CharacterSet, u8)] = &[
+    (CharacterSet::Pc437Usa, 1),
+    (CharacterSet::Pc850Multilingual, 4),
+    (CharacterSet::Pc858Euro, 4),
+    (CharacterSet::Pc852Latin2, 5),
+    (CharacterSet::Pc860Portuguese, 6),
+    (CharacterSet::Pc861Icelandic, 7),
+    (CharacterSet::Pc863CanadianFrench, 8),
+    (CharacterSet::Pc865Nordic, 9),
+    (CharacterSet::Pc866Cyrillic2, 10),
+    (CharacterSet::Pc855Cyrillic, 11),
+    (CharacterSet::Pc857Turkish, 12),
+    (CharacterSet::Pc862Hebrew, 13),
+    (CharacterSet::Pc864Arabic, 14),
+    (CharacterSet::Pc737Greek, 15),
+    (CharacterSet::Pc851Greek, 16),
+    (CharacterSet::Pc869Greek, 17),
+    (CharacterSet::Wpc1252, 32),
+    (CharacterSet::Wpc1250Latin2, 33),
+    (CharacterSet::Wpc1251Cyrillic, 34),
+];
+
+/// A printer speaking the Star Line Mode command set (also used by StarPRNT
+/// printers such as the TSP650 and TSP100 in their native mode).
+///
+/// Features without a Star Line Mode equivalent, such as 90° rotation and
+/// double-strike, are ignored. Images are sent in raster mode.
+pub struct StarPrinter {
+    cmds: Vec<u8>,
+    character_set: CharacterSet,
+    profile: PrinterProfile,
+    left_margin: u16,
+    print_area_width: Option<u16>,
+    state: PrinterState,
+    /// Whether highlight mode is on, which prints both inverted and red
+    /// text, or `None` if unknown.
+    highlight: Option<bool>,
+    /// The character size asked for when it is larger than the printer's
+    /// largest, in which case text is drawn in software.
+    software_size: Option<(TextSize, TextSize)>,
+    /// Whether text drawn in software ended the line, so that the next line
+    /// feed isn't sent.
+    line_drawn: bool,
+    style_stack: Vec<(TextStyle, Align)>,
+}
+
+impl Default for StarPrinter {
+    fn default() -> Self {
+        Self::new(PrinterProfile::STAR_TSP650)
+    }
+}
+
+impl StarPrinter {
+    /// Creates a printer for the model described by `profile`. Only the
+    /// physical properties of the profile are used, since the code page
+    /// numbers of Star Line Mode are fixed.
+    pub fn new(profile: PrinterProfile) -> Self {
+        Self {
+            cmds: vec![],
+            character_set: CharacterSet::default(),
+            profile,
+            left_margin: 0,
+            print_area_width: None,
+            state: PrinterState::default(),
+            highlight: None,
+            software_size: None,
+            line_drawn: false,
+            style_stack: vec![],
+        }
+    }
+
+    pub const fn profile(&self) -> &PrinterProfile {
+        &self.profile
+    }
+
+    /// Width in dots that is actually printable, taking the left margin and
+    /// the print area width into account.
+    pub fn print_area_width(&self) -> u16 {
+        let available = self.profile.dots_per_line.saturating_sub(self.left_margin);
+
+        match self.print_area_width {
+            Some(width) => width.min(available),
+            None => available,
+        }
+    }
+
+    /// The text style currently in effect. Attributes that are unknown,
+    /// because the printer was never initialized, are reported as defaults.
+    pub fn text_style(&self) -> TextStyle {
+        let mut style = self.state.text_style();
+        if let Some((width, height)) = self.software_size {
+            style.width = width;
+            style.height = height;
+        }
+        style
+    }
+
+    pub fn align(&self) -> Align {
+        self.state.align.unwrap_or_default()
+    }
+
+    /// Turns highlight mode on for inverted or red text and off otherwise.
+    fn update_highlight(&mut self) -> &mut Self {
+        let style = self.text_style();
+        let enabled = style.invert || style.color == TextColor::Red;
+        if state::update(&mut self.highlight, enabled) {
+            self.cmds
+                .extend_from_slice(&command::highlight(if enabled { 1 } else { 0 }));
+        }
+
+        self
+    }
+
+    fn length_to_dots(&self, length: &Length) -> u16 {
+        length
+            .to_dots(self.profile.dots_per_mm)
+            .clamp(0, u16::MAX as i32) as u16
+    }
+
+    /// Width of a character of the current font, since Star Line Mode sets
+    /// margins in characters.
+    fn char_width(&self) -> u16 {
+        let font = self.text_style().font;
+        let chars = self
+            .profile
+            .chars_per_line(font)
+            .or_else(|| self.profile.fonts.first().map(|(_, chars)| *chars))
+            .unwrap_or(1);
+
+        (self.profile.dots_per_line / chars.max(1)).max(1)
+    }
+}
+
+impl Printer for StarPrinter {
+    /// Selects `set` for the following text. Character sets without a Star
+    /// code page fall back to the default PC437, which the text is then
+    /// encoded in.
+    fn set_character_set(&mut self, set: CharacterSet) -> &mut Self {
+        let code_page = |set: CharacterSet| {
+            STAR_CODE_PAGES
+                .iter()
+                .find(|(candidate, _)| *candidate == set)
+                .copied()
+        };
+        let Some((set, n)) = code_page(set).or_else(|| code_page(CharacterSet::default())) else {
+            return self;
+        };
+
+        if state::update(&mut self.state.character_set, set) {
+            self.cmds.extend_from_slice(&command::code_page(n));
+        }
+        self.character_set = set;
+        self
+    }
+
+    fn set_text_font(&mut self, font: TextFont) -> &mut Self {
+        let font = match font {
+            TextFont::B => TextFont::B,
+            _ => TextFont::A,
+        };
+
+        if !state::update(&mut self.state.font, font) {
+            return self;
+        }
+
+        let n = if font == TextFont::B { 1 } else { 0 };
+
+        self.cmds.extend_from_slice(&command::font(n));
+
+        self
+    }
+
+    fn set_text_bold(&mut self, bold: bool) -> &mut Self {
+        if !state::update(&mut self.state.bold, bold) {
+            return self;
+        }
+
+        self.cmds
+            .extend_from_slice(&command::emphasized(if bold { 1 } else { 0 }));
+        self
+    }
+
+    /// Sets the character expansion. Star printers expand at most six times,
+    /// so larger sizes are drawn in software, and the text printed as images.
+    fn set_text_size(&mut self, width: TextSize, height: TextSize) -> &mut Self {
+        let max = self.profile.max_text_size.min(TextSize::Size6);
+        let (width, height) = if width > max || height > max {
+            self.software_size = Some((width, height));
+            (TextSize::Size1, TextSize::Size1)
+        } else {
+            self.software_size = None;
+            (width, height)
+        };
+
+        if !state::update(&mut self.state.size, (width, height)) {
+            return self;
+        }
+
+        let expansion = |size| match size {
+            TextSize::Size1 => 0,
+            TextSize::Size2 => 1,
+            TextSize::Size3 => 2,
+            TextSize::Size4 => 3,
+            TextSize::Size5 => 4,
+            _ => 5,
+        };
+
+        self.cmds
+            .extend_from_slice(&command::text_size(expansion(height), expansion(width)));
+
+        self
+    }
+
+    fn set_text_underline(&mut self, underline: TextUnderline) -> &mut Self {
+        if !state::update(&mut self.state.underline, underline) {
+            return self;
+        }
+
+        let n = match underline {
+            TextUnderline::OneDotThick | TextUnderline::TwoDotThick => 1,
+            TextUnderline::None => 0,
+        };
+
+        self.cmds.extend_from_slice(&command::underline(n));
+
+        self
+    }
+
+    fn set_text_normal(&mut self) -> &mut Self {
+        self.set_print_mode(PrintMode::default())
+    }
+
+    /// Star Line Mode has no combined print mode command, so the individual
+    /// attributes are set instead.
+    fn set_print_mode(&mut self, mode: PrintMode) -> &mut Self {
+        let size = |double| {
+            if double {
+                TextSize::Size2
+            } else {
+                TextSize::Size1
+            }
+        };
+
+        self.set_text_font(if mode.font_b {
+            TextFont::B
+        } else {
+            TextFont::A
+        })
+        .set_text_bold(mode.emphasized)
+        .set_text_size(size(mode.double_width), size(mode.double_height))
+        .set_text_underline(if mode.underline {
+            TextUnderline::OneDotThick
+        } else {
+            TextUnderline::None
+        })
+    }
+
+    fn set_text_double_strike(&mut self, enabled: bool) -> &mut Self {
+        self.state.double_strike = Some(enabled);
+        self
+    }
+
+    fn set_text_rotation(&mut self, rotated: bool) -> &mut Self {
+        self.state.rotated = Some(rotated);
+        self
+    }
+
+    fn set_text_upside_down(&mut self, enabled: bool) -> &mut Self {
+        if !state::update(&mut self.state.upside_down, enabled) {
+            return self;
+        }
+
+        self.cmds
+            .extend_from_slice(&command::upside_down(if enabled { 1 } else { 0 }));
+        self
+    }
+
+    fn set_character_spacing(&mut self, spacing: u8) -> &mut Self {
+        if !state::update(&mut self.state.character_spacing, spacing) {
+            return self;
+        }
+
+        self.cmds
+            .extend_from_slice(&command::character_spacing(spacing));
+        self
+    }
+
+    fn set_text_smoothing(&mut self, enabled: bool) -> &mut Self {
+        if !state::update(&mut self.state.smoothing, enabled) {
+            return self;
+        }
+
+        self.cmds
+            .extend_from_slice(&command::smoothing(if enabled { 1 } else { 0 }));
+        self
+    }
+
+    /// Red is printed with highlight mode, which two-color Star printers
+    /// print in red. Highlight mode stays on while the text is inverted.
+    fn set_text_color(&mut self, color: TextColor) -> &mut Self {
+        self.state.color = Some(color);
+        self.update_highlight()
+    }
+
+    fn set_align(&mut self, align: Align) -> &mut Self {
+        if !state::update(&mut self.state.align, align) {
+            return self;
+        }
+
+        let n = match align {
+            Align::Left => 0,
+            Align::Center => 1,
+            Align::Right => 2,
+        };
+
+        self.cmds.extend_from_slice(&command::alignment(n));
+
+        self
+    }
+
+    /// Sets the left margin, rounded down to whole characters of the current
+    /// font.
+    fn set_left_margin(&mut self, margin: Length) -> &mut Self {
+        let char_width = self.char_width();
+        let chars = (self.length_to_dots(&margin) / char_width).min(u8::MAX as u16);
+
+        self.cmds
+            .extend_from_slice(&command::left_margin(chars as u8));
+        self.left_margin = chars * char_width;
+
+        self
+    }
+
+    /// Sets the print area width through the right margin, rounded down to
+    /// whole characters of the current font.
+    fn set_print_area_width(&mut self, width: Length) -> &mut Self {
+        let char_width = self.char_width();
+        let chars = self.length_to_dots(&width) / char_width;
+        let right = (self.left_margin / char_width + chars).min(u8::MAX as u16);
+
+        self.cmds
+            .extend_from_slice(&command::right_margin(right as u8));
+        self.print_area_width = Some(chars * char_width);
+
+        self
+    }
+
+    fn set_absolute_position(&mut self, position: Length) -> &mut Self {
+        let position = self.length_to_dots(&position);
+        let n1 = position as u8;
+        let n2 = (position >> 8) as u8;
+
+        self.cmds
+            .extend_from_slice(&command::absolute_position(n1, n2));
+
+        self
+    }
+
+    fn set_relative_position(&mut self, offset: Length) -> &mut Self {
+        // Negative offsets move to the left and are sent as two's complement.
+        let offset = offset
+            .to_dots(self.profile.dots_per_mm)
+            .clamp(i16::MIN as i32, i16::MAX as i32) as i16 as u16;
+        let n1 = offset as u8;
+        let n2 = (offset >> 8) as u8;
+
+        self.cmds
+            .extend_from_slice(&command::relative_position(n1, n2));
+
+        self
+    }
+
+    /// Inverts with highlight mode, which stays on while the text is red.
+    fn invert(&mut self, enabled: bool) -> &mut Self {
+        self.state.invert = Some(enabled);
+        self.update_highlight()
+    }
+
+    /// Prints `data` like [`crate::BasePrinter`] does, drawing text larger
+    /// than the printer has in software.
+    fn text(&mut self, data: &str) -> &mut Self {
+        if self.software_size.is_some() && !data.is_empty() {
+            let lines = printer::rasterize_text(
+                &self.profile,
+                self.text_style(),
+                self.align(),
+                self.print_area_width(),
+                data,
+            );
+            for line in lines {
+                self.image(line);
+            }
+            self.line_drawn = true;
+            return self;
+        }
+
+        self.line_drawn = false;
+        self.cmds.extend(self.character_set.encode(data));
+        self
+    }
+
+    fn raw(&mut self, data: &[u8]) -> &mut Self {
+        self.cmds.extend_from_slice(data);
+        self
+    }
+
+    fn new_line(&mut self) -> &mut Self {
+        if std::mem::take(&mut self.line_drawn) {
+            return self;
+        }
+
+        self.cmds.push(LF);
+        self
+    }
+
+    /// Characters of the current font, A or B, and width that fit in the
+    /// print area.
+    fn chars_per_line(&self) -> u16 {
+        printer::chars_per_line(&self.profile, self.text_style(), self.print_area_width())
+    }
+
+    fn cut(&mut self, partial: bool) -> &mut Self {
+        self.cut_with_feed(partial, self.profile.cut_feed)
+    }
+
+    /// Cuts the paper with `ESC d`, falling back to a full cut if the
+    /// profile has no partial cut. Printers without a cutter ignore this.
+    /// Star printers feed to the cutter position only, so the extra feed is
+    /// ignored, and a reserved cut feeds right away.
+    fn cut_with_feed(&mut self, partial: bool, feed: CutFeed) -> &mut Self {
+        if !self.profile.cutter {
+            return self;
+        }
+
+        let partial = partial && self.profile.partial_cut;
+
+        let n = match feed {
+            CutFeed::None => 0,
+            CutFeed::Feed(_) | CutFeed::Reserved(_) | CutFeed::FeedAndReverse(_) => 2,
+        };
+
+        self.cmds
+            .extend_from_slice(&command::cut(if partial { n + 1 } else { n }));
+        self
+    }
+
+    /// Prints `image` in raster mode. Raster mode ignores the alignment, so
+    /// centered and right aligned images are shifted by whole bytes.
+    fn image(&mut self, image: Image) -> &mut Self {
+        let width = image.width().div_ceil(8);
+        let area = self.print_area_width() / 8;
+        let offset = match self.align() {
+            Align::Left => 0,
+            Align::Center => area.saturating_sub(width) / 2,
+            Align::Right => area.saturating_sub(width),
+        } + self.left_margin / 8;
+
+        let line_length = width + offset;
+        let n1 = line_length as u8;
+        let n2 = (line_length >> 8) as u8;
+
+        self.cmds.extend_from_slice(&command::raster_enter());
+
+        let raster = image.to_raster();
+        for row in raster.chunks(width.max(1) as usize) {
+            let mut line = vec![0; offset as usize];
+            line.extend_from_slice(row);
+            self.cmds
+                .extend_from_slice(&command::raster_line(n1, n2, &line));
+        }
+
+        self.cmds.extend_from_slice(&command::raster_quit());
+
+        self
+    }
+
+    /// Prints a QR code with `ESC GS y`. Micro QR codes, QR codes with an
+    /// ECI header, and every QR code if the profile has no native support,
+    /// are rendered in software.
+    fn qrcode(
+        &mut self,
+        data: impl AsRef<[u8]>,
+        options: QRCodeOptions,
+    ) -> Result<&mut Self, Error> {
+        let data = data.as_ref();
+
+        let model = match options.model {
+            QRCodeModel::Model1 => 1,
+            QRCodeModel::Model2 => 2,
+            QRCodeModel::Micro => 0,
+        };
+
+        if model == 0 || !self.profile.qr_code || options.eci.is_some() {
+            let image = render::qr_code(data, &options)?;
+            return Ok(self.image(image));
+        }
+        render::qr_symbol(data, &options)?;
+
+        self.cmds.extend_from_slice(&command::qr_code_model(model));
+
+        let correction = match options.correction {
+            QRCodeCorrection::L => 0,
+            QRCodeCorrection::M => 1,
+            QRCodeCorrection::Q => 2,
+            QRCodeCorrection::H => 3,
+        };
+
+        self.cmds
+            .extend_from_slice(&command::qr_code_correction_level(correction));
+
+        let cell_size = match options.cell_size {
+            QRCodeCellSize::Size1 => 1,
+            QRCodeCellSize::Size2 => 2,
+            QRCodeCellSize::Size3 => 3,
+            QRCodeCellSize::Size4 => 4,
+            QRCodeCellSize::Size5 => 5,
+            QRCodeCellSize::Size6 => 6,
+            QRCodeCellSize::Size7 => 7,
+            QRCodeCellSize::Size8 => 8,
+        };
+
+        self.cmds
+            .extend_from_slice(&command::qr_code_cell_size(cell_size));
+
+        let length = data.len() as u16;
+        let nl = length as u8;
+        let nh = (length >> 8) as u8;
+
+        self.cmds
+            .extend_from_slice(&command::qr_code_store(nl, nh, data));
+        self.cmds.extend_from_slice(&command::qr_code_print());
+
+        Ok(self)
+    }
+
+    /// Prints a PDF417 symbol with `ESC GS x`. Star printers have no
+    /// truncated PDF417, and an error correction ratio is converted to the
+    /// nearest level for the size of the data.
+    fn pdf417(&mut self, data: &str, options: Pdf417Options) -> Result<&mut Self, Error> {
+        if !self.profile.pdf417 {
+            return Err(Error::UnsupportedSymbol("PDF417"));
+        }
+        barcode::check_capacity(data, &barcode::PDF417_CAPACITY).map_err(|reason| {
+            Error::InvalidSymbol {
+                symbology: "PDF417",
+                reason,
+            }
+        })?;
+
+        self.cmds.extend_from_slice(&command::pdf417_size(
+            match options.rows {
+                0 => 0,
+                rows => rows.clamp(3, 90),
+            },
+            options.columns.min(30),
+        ));
+
+        let level = match options.correction {
+            Pdf417Correction::Level(level) => level.min(8),
+            Pdf417Correction::Ratio(ratio) => {
+                // Byte compaction packs about 6 bytes in 5 codewords, and
+                // level n adds 2^(n + 1) codewords.
+                let codewords = (data.len() * 5).div_ceil(6).max(1);
+                let wanted = codewords * ratio.clamp(1, 40) as usize / 10;
+                (0..8).find(|level| 1 << (level + 1) >= wanted).unwrap_or(8)
+            }
+        };
+        self.cmds
+            .extend_from_slice(&command::pdf417_correction_level(level));
+
+        self.cmds.extend_from_slice(&command::pdf417_module_width(
+            options.module_width.clamp(2, 8),
+        ));
+        self.cmds
+            .extend_from_slice(&command::pdf417_row_height(options.row_height.clamp(2, 8)));
+
+        let encoded = data.as_bytes();
+        let length = encoded.len() as u16;
+        let nl = length as u8;
+        let nh = (length >> 8) as u8;
+
+        self.cmds
+            .extend_from_slice(&command::pdf417_store(nl, nh, encoded));
+        self.cmds.extend_from_slice(&command::pdf417_print());
+
+        Ok(self)
+    }
+
+    /// Star Line Mode has no DataMatrix command, so this always returns an
+    /// error.
+    fn data_matrix(
+        &mut self,
+        _data: &str,
+        _options: DataMatrixOptions,
+    ) -> Result<&mut Self, Error> {
+        Err(Error::UnsupportedSymbol("DataMatrix"))
+    }
+
+    /// Star Line Mode has no Aztec Code command, so this always returns an
+    /// error.
+    fn aztec(&mut self, _data: &str, _options: AztecOptions) -> Result<&mut Self, Error> {
+        Err(Error::UnsupportedSymbol("Aztec Code"))
+    }
+
+    /// Star Line Mode has no MaxiCode command, so this always returns an
+    /// error.
+    fn maxi_code(&mut self, _data: &str, _options: MaxiCodeOptions) -> Result<&mut Self, Error> {
+        Err(Error::UnsupportedSymbol("MaxiCode"))
+    }
+
+    /// Prints a barcode with `ESC b`. Star printers can only print the HRI
+    /// characters below the barcode, and symbologies without a Star
+    /// equivalent are rendered in software.
+    fn barcode(
+        &mut self,
+        data: &str,
+        r#type: BarcodeType,
+        options: BarcodeOptions,
+    ) -> Result<&mut Self, Error> {
+        let data = barcode::validate(data, r#type)?;
+
+        let type_value = match r#type {
+            BarcodeType::UPCE => Some(0),
+            BarcodeType::UPCA => Some(1),
+            BarcodeType::JAN8 => Some(2),
+            BarcodeType::JAN13 => Some(3),
+            BarcodeType::CODE39 => Some(4),
+            BarcodeType::ITF => Some(5),
+            BarcodeType::CODE128 => Some(6),
+            BarcodeType::CODE93 => Some(7),
+            BarcodeType::CODABAR => Some(8),
+            _ => None,
+        };
+
+        // Star printers choose the CODE128 code sets themselves, but have no
+        // way to print the function characters.
+        let functions = data.contains([barcode::FNC1, barcode::FNC2, barcode::FNC3, barcode::FNC4]);
+
+        let type_value = match type_value {
+            Some(value) if self.profile.supports_barcode(r#type) && !functions => value,
+            _ => {
+                let max_width = self.print_area_width();
+                let image = render::barcode(&data, r#type, &options, max_width).ok_or(
+                    Error::InvalidBarcode {
+                        r#type,
+                        reason: BarcodeError::TooLong,
+                    },
+                )?;
+                return Ok(self.image(image));
+            }
+        };
+
+        let hri = match options.hri_position {
+            HriPosition::None => 1,
+            HriPosition::Top | HriPosition::Bottom | HriPosition::TopBottom => 2,
+        };
+
+        let mode = match options.width {
+            BarcodeWidth::Width1 | BarcodeWidth::Width2 => 1,
+            BarcodeWidth::Width3 => 2,
+            BarcodeWidth::Width4 | BarcodeWidth::Width5 | BarcodeWidth::Width6 => 3,
+        };
+
+        self.cmds.extend_from_slice(&command::barcode(
+            type_value,
+            hri,
+            mode,
+            options.height,
+            data.as_bytes(),
+        ));
+
+        Ok(self)
+    }
+
+    fn cash_draw(&mut self, pin: CashDrawerPin) -> &mut Self {
+        let m = match pin {
+            CashDrawerPin::TwoPin => 1,
+            CashDrawerPin::FivePin => 2,
+        };
+
+        self.cmds.extend_from_slice(&command::cash_draw(m));
+
+        self
+    }
+
+    /// Sounds the buzzer connected as external device 1 with `ESC GS BEL`,
+    /// which times beeps in steps of 20 ms up to 5100 ms.
+    fn beep(&mut self, times: u8, duration_ms: u16) -> &mut Self {
+        let t = duration_ms.div_ceil(20).clamp(1, 255) as u8;
+        for _ in 0..times.max(1) {
+            self.cmds.extend_from_slice(&command::buzzer(1, t, t));
+        }
+
+        self
+    }
+
+    /// Sets the pulse with `ESC BEL`, in steps of 10 ms up to 2550 ms, before
+    /// opening the drawer. Star printers time the pulse of external device 1
+    /// only, the five pin drawer keeps its default pulse.
+    fn cash_draw_pulse(&mut self, pin: CashDrawerPin, on_ms: u16, off_ms: u16) -> &mut Self {
+        if let CashDrawerPin::TwoPin = pin {
+            let n1 = (on_ms / 10).clamp(1, 255) as u8;
+            let n2 = (off_ms / 10).clamp(1, 255) as u8;
+            self.cmds
+                .extend_from_slice(&command::cash_draw_pulse(n1, n2));
+        }
+
+        self.cash_draw(pin)
+    }
+
+    /// Star printers have no real-time pulse, the drawer opens once the
+    /// data before it is printed.
+    fn cash_draw_realtime(&mut self, pin: CashDrawerPin, pulse_ms: u16) -> &mut Self {
+        self.cash_draw_pulse(pin, pulse_ms, pulse_ms)
+    }
+
+    /// Asks for the automatic status with `ESC ACK SOH`, parsed with
+    /// [`crate::DrawerStatus::from_star`].
+    fn request_drawer_status(&mut self) -> &mut Self {
+        self.cmds.extend_from_slice(&command::automatic_status());
+        self
+    }
+
+    fn initialize(&mut self) -> &mut Self {
+        self.cmds.extend_from_slice(&command::initialize());
+        self.left_margin = 0;
+        self.print_area_width = None;
+        self.state = PrinterState::power_on();
+        self.highlight = Some(false);
+        self.software_size = None;
+        self.line_drawn = false;
+        self
+    }
+
+    fn get_data(&self) -> Vec<u8> {
+        self.cmds.clone()
+    }
+
+    /// Discards the buffered commands and forgets the tracked style, see
+    /// [`crate::BasePrinter`].
+    fn clear(&mut self) -> &mut Self {
+        self.cmds.clear();
+        self.state = PrinterState::default();
+        self.highlight = None;
+        self.software_size = None;
+        self.line_drawn = false;
+        self
+    }
+
+    fn push_style(&mut self) -> &mut Self {
+        self.style_stack.push((self.text_style(), self.align()));
+        self
+    }
+
+    fn pop_style(&mut self) -> &mut Self {
+        match self.style_stack.pop() {
+            Some((style, align)) => self.set_text_style(style).set_align(align),
+            None => self,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn beeps_at_least_once() {
+        let beep = |times| StarPrinter::default().beep(times, 200).get_data();
+        assert_eq!(beep(0), b"\x1b\x1d\x07\x01\x0a\x0a");
+        assert_eq!(beep(0), beep(1));
+        assert_eq!(beep(2), b"\x1b\x1d\x07\x01\x0a\x0a".repeat(2));
+    }
+
+    #[test]
+    fn prints_barcodes_with_esc_b() {
+        let options = BarcodeOptions {
+            hri_position: HriPosition::Bottom,
+            width: BarcodeWidth::Width3,
+            height: 80,
+            ..BarcodeOptions::default()
+        };
+        let data = StarPrinter::default()
+            .barcode("CODE 39", BarcodeType::CODE39, options)
+            .unwrap()
+            .get_data();
+        assert_eq!(data, b"\x1bb\x04\x02\x02\x50CODE 39\x1e");
+    }
+
+    #[test]
+    fn renders_function_characters_in_software() {
+        let data = StarPrinter::default()
+            .barcode(
+                "\u{e001}0101",
+                BarcodeType::CODE128,
+                BarcodeOptions::default(),
+            )
+            .unwrap()
+            .get_data();
+        assert!(data.starts_with(&command::raster_enter()));
+        assert!(data.ends_with(&command::raster_quit()));
+    }
+
+    #[test]
+    fn prints_qr_codes_with_esc_gs_y() {
+        let data = StarPrinter::default()
+            .qrcode("abc", QRCodeOptions::default())
+            .unwrap()
+            .get_data();
+        let mut expected = vec![];
+        expected.extend_from_slice(&command::qr_code_model(2));
+        expected.extend_from_slice(&command::qr_code_correction_level(0));
+        expected.extend_from_slice(&command::qr_code_cell_size(3));
+        expected.extend_from_slice(b"\x1b\x1dyD1\x00\x03\x00abc\x1b\x1dyP");
+        assert_eq!(data, expected);
+    }
+}
@@ -0,0 +1,79 @@
+use crate::{
+    character_set::CharacterSet,
+    printer::{Align, TextColor, TextFont, TextSize, TextStyle, TextUnderline},
+};
+
+/// Effective printer state as far as it is known from the commands emitted
+/// so far. `None` means the state is unknown, e.g. before the printer was
+/// initialized or after the buffer was cleared.
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct PrinterState {
+    pub character_set: Option<CharacterSet>,
+    pub font: Option<TextFont>,
+    pub bold: Option<bool>,
+    pub double_strike: Option<bool>,
+    pub underline: Option<TextUnderline>,
+    pub size: Option<(TextSize, TextSize)>,
+    pub invert: Option<bool>,
+    pub rotated: Option<bool>,
+    pub upside_down: Option<bool>,
+    pub character_spacing: Option<u8>,
+    pub smoothing: Option<bool>,
+    pub color: Option<TextColor>,
+    pub align: Option<Align>,
+}
+
+impl PrinterState {
+    /// The state right after `ESC @`.
+    pub fn power_on() -> Self {
+        let style = TextStyle::default();
+
+        Self {
+            character_set: Some(CharacterSet::default()),
+            font: Some(style.font),
+            bold: Some(style.bold),
+            double_strike: Some(style.double_strike),
+            underline: Some(style.underline),
+            size: Some((style.width, style.height)),
+            invert: Some(style.invert),
+            rotated: Some(style.rotated),
+            upside_down: Some(style.upside_down),
+            character_spacing: Some(style.character_spacing),
+            smoothing: Some(style.smoothing),
+            color: Some(style.color),
+            align: Some(Align::default()),
+        }
+    }
+
+    /// The effective text style, assuming defaults for unknown attributes.
+    pub fn text_style(&self) -> TextStyle {
+        let default = TextStyle::default();
+        let (width, height) = self.size.unwrap_or((default.width, default.height));
+
+        TextStyle {
+            font: self.font.unwrap_or(default.font),
+            bold: self.bold.unwrap_or(default.bold),
+            double_strike: self.double_strike.unwrap_or(default.double_strike),
+            underline: self.underline.unwrap_or(default.underline),
+            width,
+            height,
+            invert: self.invert.unwrap_or(default.invert),
+            rotated: self.rotated.unwrap_or(default.rotated),
+            upside_down: self.upside_down.unwrap_or(default.upside_down),
+            character_spacing: self.character_spacing.unwrap_or(default.character_spacing),
+            smoothing: self.smoothing.unwrap_or(default.smoothing),
+            color: self.color.unwrap_or(default.color),
+        }
+    }
+}
+
+/// Records `value` as the current state and returns whether it differs from
+/// what was known before, i.e. whether a command has to be emitted.
+pub(crate) fn update<T: PartialEq>(current: &mut Option<T>, value: T) -> bool {
+    if current.as_ref() == Some(&value) {
+        false
+    } else {
+        *current = Some(value);
+        true
+    }
+}
@@ -0,0 +1,58 @@
+/// State of the cash drawer, read from the status a printer sends back
+/// after [`crate::Printer::request_drawer_status`].
+///
+/// The printer reports the level of the drawer sensor. Most drawers report
+/// being open with a high level, drawers wired the other way report the
+/// opposite.
+///
+/// # Example
+///
+/// ```ignore
+/// printer.request_drawer_status();
+/// port.write_all(&printer.get_data())?;
+///
+/// let mut status = [0];
+/// port.read_exact(&mut status)?;
+/// if DrawerStatus::from_escpos(status[0]).is_some_and(|status| status.open) {
+///     println!("The drawer is open");
+/// }
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DrawerStatus {
+    /// The drawer sensor is high.
+    pub open: bool,
+    /// The printer is offline, e.g. with its cover open or out of paper.
+    pub offline: bool,
+}
+
+impl DrawerStatus {
+    /// Parses the printer status sent by an ESC/POS printer for `DLE EOT 1`,
+    /// or returns `None` if `status` isn't one.
+    pub fn from_escpos(status: u8) -> Option<Self> {
+        // Bits 1 and 4 are always set, bits 0 and 7 always cleared.
+        if status & 0x93 != 0x12 {
+            return None;
+        }
+
+        Some(Self {
+            open: status & 0x04 != 0,
+            offline: status & 0x08 != 0,
+        })
+    }
+
+    /// Parses the automatic status sent by a Star printer for
+    /// `ESC ACK SOH`, or returns `None` if `status` isn't one.
+    pub fn from_star(status: &[u8]) -> Option<Self> {
+        let (&header, &state) = (status.first()?, status.get(2)?);
+        // Bit 0 of the header is always set, and bits 0 and 7 of the status
+        // bytes always cleared.
+        if header & 0x91 != 0x01 || state & 0x81 != 0 {
+            return None;
+        }
+
+        Some(Self {
+            open: state & 0x04 != 0,
+            offline: state & 0x08 != 0,
+        })
+    }
+}
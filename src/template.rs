@@ -0,0 +1,1162 @@
+//! Receipt templates: the layout of a receipt written as text, filled with
+//! data when it is printed.
+//!
+//! Each line of a template is printed as a paragraph, in which
+//! `{{ path }}` is replaced by a value of the data. Paths go through objects
+//! and arrays with dots, such as `{{ store.name }}` or `{{ items.0.name }}`.
+//! Values can be formatted by filters, applied from left to right:
+//!
+//! | Filter                   | Output                                              |
+//! |--------------------------|-----------------------------------------------------|
+//! | `number DECIMALS`        | `1234.50` for `number 2`                            |
+//! | `currency SYMBOL [DEC]`  | `$1,234.50` for `currency "$"`, 2 decimals by default |
+//! | `date FORMAT`            | `%Y %y %m %d %H %M %S %b %%` of a unix timestamp or an ISO 8601 date |
+//! | `upper`, `lower`         | The text in upper or lower case                     |
+//! | `default TEXT`           | `TEXT` if the value is missing, null or empty       |
+//! | `truncate LENGTH`        | The first `LENGTH` characters                       |
+//!
+//! Lines starting with `@`, optionally indented, are directives:
+//!
+//! | Directive                          | Effect                                       |
+//! |------------------------------------|----------------------------------------------|
+//! | `@align left\|center\|right`       | Alignment of the next paragraphs and codes   |
+//! | `@bold on\|off`                    | Bold text                                    |
+//! | `@underline on\|double\|off`       | Underlined text                              |
+//! | `@invert on\|off`                  | White on black text                          |
+//! | `@font a\|b`                       | Font of the text                             |
+//! | `@size W[xH]`                      | Character size, from 1 to 8                  |
+//! | `@reset`                           | Default text style and alignment             |
+//! | `@columns WIDTH[:ALIGN] ...`       | Columns of the next rows, 0 sharing the rest |
+//! | `@row CELL \| CELL ...`            | A row of cells laid out in the columns       |
+//! | `@separator [CHARACTER]`           | A line across the receipt, `-` by default    |
+//! | `@feed [LINES]`                    | Empty lines                                  |
+//! | `@cut [partial]`                   | Cuts the paper                               |
+//! | `@drawer [2\|5]`                   | Opens the cash drawer on pin 2 or 5          |
+//! | `@barcode TYPE [OPTION=...] DATA`  | A barcode, with `hri`, `height` and `width`  |
+//! | `@qrcode [OPTION=...] DATA`        | A QR code, with `size`, `correction`, `model` and `eci` |
+//! | `@image NAME`                      | An image added with [`Template::add_image`]  |
+//! | `@for NAME in PATH` ... `@end`     | Repeats the lines for each element of an array |
+//! | `@if CONDITION` ... `@else` ... `@end` | Prints the lines if the condition holds  |
+//! | `@# ...`                           | A comment                                    |
+//!
+//! Inside a loop, `loop.index`, `loop.first` and `loop.last` tell where the
+//! element is in the array. Conditions are a path, true unless the value is
+//! missing, null, false, 0 or empty, or a comparison of a path to a literal
+//! with `==`, `!=`, `<`, `<=`, `>` or `>=`, both optionally preceded by
+//! `not`. A line starting with `@@` prints a paragraph starting with `@`.
+
+use std::collections::HashMap;
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::{
+    error::Error,
+    printer::{
+        Align, BarcodeOptions, BarcodeType, BarcodeWidth, CashDrawerPin, HriPosition, Printer,
+        QRCodeCellSize, QRCodeCorrection, QRCodeModel, QRCodeOptions, TextFont, TextSize,
+        TextStyle, TextUnderline,
+    },
+    receipt::{Column, Receipt, ReceiptItem},
+    Image,
+};
+
+/// A parsed receipt template, see the [module documentation](self) for the
+/// syntax.
+///
+/// # Example
+///
+/// ```ignore
+/// let mut template = Template::parse(
+///     r#"@align center
+/// @image logo
+/// @bold on
+/// {{ store.name | upper }}
+/// @reset
+/// {{ date | date "%d/%m/%Y %H:%M" }}
+/// @separator
+/// @columns 3:right 0 10:right
+/// @for item in items
+/// @row {{ item.quantity }} | {{ item.name }} | {{ item.price | currency "$" }}
+/// @end
+/// @separator
+/// @if discount > 0
+/// Discount: {{ discount | currency "$" }}
+/// @end
+/// @barcode CODE128 hri=bottom height=60 {{ number }}
+/// @cut"#,
+/// )?;
+/// template.add_image("logo", logo);
+///
+/// template.print(&mut printer, &json!({
+///     "store": { "name": "ACME Coffee" },
+///     "date": "2024-05-01T08:30:00",
+///     "items": [{ "quantity": 2, "name": "Espresso", "price": 2.5 }],
+///     "discount": 0,
+///     "number": "A-1042",
+/// }))?;
+/// ```
+#[derive(Clone, Debug)]
+pub struct Template {
+    lines: Vec<Line>,
+    images: HashMap<String, Image>,
+}
+
+#[derive(Clone, Debug)]
+struct Line {
+    number: usize,
+    node: Node,
+}
+
+#[derive(Clone, Debug)]
+enum Node {
+    Text(Vec<Segment>),
+    Align(Align),
+    Bold(bool),
+    Underline(TextUnderline),
+    Invert(bool),
+    Font(TextFont),
+    Size(TextSize, TextSize),
+    Reset,
+    Columns(Vec<Column>),
+    Row(Vec<Vec<Segment>>),
+    Separator(char),
+    Feed(u8),
+    Cut(bool),
+    Drawer(CashDrawerPin),
+    Barcode {
+        r#type: BarcodeType,
+        options: BarcodeOptions,
+        data: Vec<Segment>,
+    },
+    QRCode {
+        options: QRCodeOptions,
+        data: Vec<Segment>,
+    },
+    Image(String),
+    For {
+        variable: String,
+        path: Vec<String>,
+        body: Vec<Line>,
+    },
+    If {
+        condition: Condition,
+        then: Vec<Line>,
+        otherwise: Vec<Line>,
+    },
+}
+
+#[derive(Clone, Debug)]
+enum Segment {
+    Literal(String),
+    Value {
+        path: Vec<String>,
+        filters: Vec<Filter>,
+    },
+}
+
+#[derive(Clone, Debug)]
+enum Filter {
+    Number(usize),
+    Currency { symbol: String, decimals: usize },
+    Date(String),
+    Upper,
+    Lower,
+    Default(String),
+    Truncate(usize),
+}
+
+#[derive(Clone, Debug)]
+struct Condition {
+    negate: bool,
+    path: Vec<String>,
+    comparison: Option<(Comparison, Value)>,
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Comparison {
+    Equal,
+    NotEqual,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Word(String),
+    Quoted(String),
+    Pipe,
+}
+
+/// Where a block of lines ended.
+enum BlockEnd {
+    Eof,
+    Else(usize),
+    End(usize),
+}
+
+fn error(line: usize, reason: impl Into<String>) -> Error {
+    Error::InvalidTemplate {
+        line,
+        reason: reason.into(),
+    }
+}
+
+impl Template {
+    /// Parses `source`, returning [`Error::InvalidTemplate`] with the line of
+    /// the first syntax error.
+    pub fn parse(source: &str) -> Result<Self, Error> {
+        let mut lines = source.lines().enumerate().map(|(i, line)| (i + 1, line));
+
+        let lines = match parse_block(&mut lines)? {
+            (lines, BlockEnd::Eof) => lines,
+            (_, BlockEnd::Else(line)) => return Err(error(line, "@else outside of @if")),
+            (_, BlockEnd::End(line)) => return Err(error(line, "@end without @for or @if")),
+        };
+
+        Ok(Self {
+            lines,
+            images: HashMap::new(),
+        })
+    }
+
+    /// Adds an image printed by `@image name`.
+    pub fn add_image(&mut self, name: &str, image: Image) -> &mut Self {
+        self.images.insert(name.to_string(), image);
+        self
+    }
+
+    /// Fills the template with `data`, which is usually a serde_json
+    /// [`Value`] or a struct deriving [`Serialize`].
+    pub fn render(&self, data: &impl Serialize) -> Result<Receipt, Error> {
+        let data = serde_json::to_value(data)
+            .map_err(|error| Error::InvalidTemplateData(error.to_string()))?;
+
+        let mut renderer = Renderer {
+            images: &self.images,
+            data: &data,
+            scopes: vec![],
+            receipt: Receipt::new(),
+            style: TextStyle::default(),
+            align: Align::Left,
+            columns: vec![],
+        };
+        renderer.render(&self.lines)?;
+
+        Ok(renderer.receipt)
+    }
+
+    /// Fills the template with `data` and prints it.
+    pub fn print<P: Printer>(&self, printer: &mut P, data: &impl Serialize) -> Result<(), Error> {
+        self.render(data)?.print(printer)
+    }
+}
+
+fn parse_block<'a>(
+    lines: &mut impl Iterator<Item = (usize, &'a str)>,
+) -> Result<(Vec<Line>, BlockEnd), Error> {
+    let mut block = vec![];
+
+    while let Some((number, text)) = lines.next() {
+        let Some(directive) = text.trim_start().strip_prefix('@') else {
+            block.push(Line {
+                number,
+                node: Node::Text(parse_text(text, number)?),
+            });
+            continue;
+        };
+
+        if directive.starts_with('@') {
+            block.push(Line {
+                number,
+                node: Node::Text(parse_text(directive, number)?),
+            });
+            continue;
+        }
+        if directive.starts_with('#') {
+            continue;
+        }
+
+        let (name, args) = next_word(directive);
+        let node = match name {
+            "end" => return Ok((block, BlockEnd::End(number))),
+            "else" => return Ok((block, BlockEnd::Else(number))),
+            "for" => {
+                let tokens = tokenize(args, number)?;
+                let [Token::Word(variable), Token::Word(keyword), Token::Word(path)] =
+                    tokens.as_slice()
+                else {
+                    return Err(error(number, "expected @for NAME in PATH"));
+                };
+                if keyword != "in" {
+                    return Err(error(number, "expected @for NAME in PATH"));
+                }
+
+                let body = match parse_block(lines)? {
+                    (body, BlockEnd::End(_)) => body,
+                    (_, BlockEnd::Else(line)) => return Err(error(line, "@else outside of @if")),
+                    (_, BlockEnd::Eof) => return Err(error(number, "@for without @end")),
+                };
+
+                Node::For {
+                    variable: variable.clone(),
+                    path: parse_path(path),
+                    body,
+                }
+            }
+            "if" => {
+                let condition = parse_condition(args, number)?;
+
+                let (then, otherwise) = match parse_block(lines)? {
+                    (then, BlockEnd::End(_)) => (then, vec![]),
+                    (then, BlockEnd::Else(_)) => match parse_block(lines)? {
+                        (otherwise, BlockEnd::End(_)) => (then, otherwise),
+                        (_, BlockEnd::Else(line)) => return Err(error(line, "@else after @else")),
+                        (_, BlockEnd::Eof) => return Err(error(number, "@if without @end")),
+                    },
+                    (_, BlockEnd::Eof) => return Err(error(number, "@if without @end")),
+                };
+
+                Node::If {
+                    condition,
+                    then,
+                    otherwise,
+                }
+            }
+            _ => parse_directive(name, args, number)?,
+        };
+
+        block.push(Line { number, node });
+    }
+
+    Ok((block, BlockEnd::Eof))
+}
+
+fn parse_directive(name: &str, args: &str, line: usize) -> Result<Node, Error> {
+    let args = args.trim();
+    let switch = |args: &str| match args {
+        "on" => Ok(true),
+        "off" => Ok(false),
+        _ => Err(error(line, format!("expected @{} on or off", name))),
+    };
+
+    let node = match name {
+        "align" => Node::Align(match args {
+            "left" => Align::Left,
+            "center" => Align::Center,
+            "right" => Align::Right,
+            _ => return Err(error(line, "expected @align left, center or right")),
+        }),
+        "bold" => Node::Bold(switch(args)?),
+        "underline" => Node::Underline(match args {
+            "on" => TextUnderline::OneDotThick,
+            "double" => TextUnderline::TwoDotThick,
+            "off" => TextUnderline::None,
+            _ => return Err(error(line, "expected @underline on, double or off")),
+        }),
+        "invert" => Node::Invert(switch(args)?),
+        "font" => Node::Font(match args {
+            "a" | "A" => TextFont::A,
+            "b" | "B" => TextFont::B,
+            _ => return Err(error(line, "expected @font a or b")),
+        }),
+        "size" => {
+            let (width, height) = args.split_once('x').unwrap_or((args, args));
+            match (text_size(width), text_size(height)) {
+                (Some(width), Some(height)) => Node::Size(width, height),
+                _ => return Err(error(line, "expected @size WIDTHxHEIGHT from 1 to 8")),
+            }
+        }
+        "reset" => Node::Reset,
+        "columns" => Node::Columns(
+            args.split_whitespace()
+                .map(|column| parse_column(column, line))
+                .collect::<Result<_, _>>()?,
+        ),
+        "row" => Node::Row(
+            split_cells(args)
+                .into_iter()
+                .map(|cell| parse_text(cell.trim(), line))
+                .collect::<Result<_, _>>()?,
+        ),
+        "separator" => {
+            let mut characters = args.chars();
+            match (characters.next(), characters.next()) {
+                (None, _) => Node::Separator('-'),
+                (Some(character), None) => Node::Separator(character),
+                _ => return Err(error(line, "expected @separator with one character")),
+            }
+        }
+        "feed" => Node::Feed(match args {
+            "" => 1,
+            _ => args
+                .parse()
+                .map_err(|_| error(line, "expected @feed with 0 to 255 lines"))?,
+        }),
+        "cut" => Node::Cut(match args {
+            "" => false,
+            "partial" => true,
+            _ => return Err(error(line, "expected @cut or @cut partial")),
+        }),
+        "drawer" => Node::Drawer(match args {
+            "" | "2" => CashDrawerPin::TwoPin,
+            "5" => CashDrawerPin::FivePin,
+            _ => return Err(error(line, "expected @drawer 2 or 5")),
+        }),
+        "barcode" => {
+            let (name, mut rest) = next_word(args);
+            let r#type = barcode_type(name)
+                .ok_or_else(|| error(line, format!("unknown barcode type {:?}", name)))?;
+
+            let mut options = BarcodeOptions::default();
+            while let Some((key, value, next)) = next_option(rest, &["hri", "height", "width"]) {
+                let invalid = || error(line, format!("invalid barcode option {}={}", key, value));
+                match key {
+                    "hri" => {
+                        options.hri_position = match value {
+                            "none" => HriPosition::None,
+                            "top" => HriPosition::Top,
+                            "bottom" => HriPosition::Bottom,
+                            "both" => HriPosition::TopBottom,
+                            _ => return Err(invalid()),
+                        }
+                    }
+                    "height" => options.height = value.parse().map_err(|_| invalid())?,
+                    _ => {
+                        options.width = match value {
+                            "1" => BarcodeWidth::Width1,
+                            "2" => BarcodeWidth::Width2,
+                            "3" => BarcodeWidth::Width3,
+                            "4" => BarcodeWidth::Width4,
+                            "5" => BarcodeWidth::Width5,
+                            "6" => BarcodeWidth::Width6,
+                            _ => return Err(invalid()),
+                        }
+                    }
+                }
+                rest = next;
+            }
+
+            Node::Barcode {
+                r#type,
+                options,
+                data: parse_text(rest.trim(), line)?,
+            }
+        }
+        "qrcode" => {
+            let mut rest = args;
+            let mut options = QRCodeOptions::default();
+            while let Some((key, value, next)) =
+                next_option(rest, &["size", "correction", "model", "eci"])
+            {
+                let invalid = || error(line, format!("invalid QR code option {}={}", key, value));
+                match key {
+                    "size" => {
+                        options.cell_size = match value {
+                            "1" => QRCodeCellSize::Size1,
+                            "2" => QRCodeCellSize::Size2,
+                            "3" => QRCodeCellSize::Size3,
+                            "4" => QRCodeCellSize::Size4,
+                            "5" => QRCodeCellSize::Size5,
+                            "6" => QRCodeCellSize::Size6,
+                            "7" => QRCodeCellSize::Size7,
+                            "8" => QRCodeCellSize::Size8,
+                            _ => return Err(invalid()),
+                        }
+                    }
+                    "correction" => {
+                        options.correction = match value {
+                            "l" | "L" => QRCodeCorrection::L,
+                            "m" | "M" => QRCodeCorrection::M,
+                            "q" | "Q" => QRCodeCorrection::Q,
+                            "h" | "H" => QRCodeCorrection::H,
+                            _ => return Err(invalid()),
+                        }
+                    }
+                    "model" => {
+                        options.model = match value {
+                            "1" => QRCodeModel::Model1,
+                            "2" => QRCodeModel::Model2,
+                            "micro" => QRCodeModel::Micro,
+                            _ => return Err(invalid()),
+                        }
+                    }
+                    _ => options.eci = Some(value.parse().map_err(|_| invalid())?),
+                }
+                rest = next;
+            }
+
+            Node::QRCode {
+                options,
+                data: parse_text(rest.trim(), line)?,
+            }
+        }
+        "image" if !args.is_empty() => Node::Image(args.to_string()),
+        "image" => return Err(error(line, "expected @image NAME")),
+        _ => return Err(error(line, format!("unknown directive @{}", name))),
+    };
+
+    Ok(node)
+}
+
+/// Splits the first word off `text`.
+fn next_word(text: &str) -> (&str, &str) {
+    let text = text.trim_start();
+    text.split_at(text.find(char::is_whitespace).unwrap_or(text.len()))
+}
+
+/// Splits `key=value` off `text` if `key` is one of `keys`.
+fn next_option<'a>(text: &'a str, keys: &[&str]) -> Option<(&'a str, &'a str, &'a str)> {
+    let (word, rest) = next_word(text);
+    let (key, value) = word.split_once('=')?;
+    keys.contains(&key).then_some((key, value, rest))
+}
+
+fn text_size(size: &str) -> Option<TextSize> {
+    Some(match size {
+        "1" => TextSize::Size1,
+        "2" => TextSize::Size2,
+        "3" => TextSize::Size3,
+        "4" => TextSize::Size4,
+        "5" => TextSize::Size5,
+        "6" => TextSize::Size6,
+        "7" => TextSize::Size7,
+        "8" => TextSize::Size8,
+        _ => return None,
+    })
+}
+
+fn barcode_type(name: &str) -> Option<BarcodeType> {
+    Some(match name.to_ascii_lowercase().as_str() {
+        "upca" | "upc-a" => BarcodeType::UPCA,
+        "upce" | "upc-e" => BarcodeType::UPCE,
+        "jan13" | "ean13" | "jan-13" | "ean-13" => BarcodeType::JAN13,
+        "jan8" | "ean8" | "jan-8" | "ean-8" => BarcodeType::JAN8,
+        "code39" => BarcodeType::CODE39,
+        "itf" => BarcodeType::ITF,
+        "codabar" => BarcodeType::CODABAR,
+        "code93" => BarcodeType::CODE93,
+        "code128" => BarcodeType::CODE128,
+        "gs1128" | "gs1-128" => BarcodeType::GS1128,
+        "databar" => BarcodeType::GS1DataBarOmnidirectional,
+        "databar-truncated" => BarcodeType::GS1DataBarTruncated,
+        "databar-limited" => BarcodeType::GS1DataBarLimited,
+        "databar-expanded" => BarcodeType::GS1DataBarExpanded,
+        _ => return None,
+    })
+}
+
+fn parse_column(column: &str, line: usize) -> Result<Column, Error> {
+    let (width, align) = column.split_once(':').unwrap_or((column, "left"));
+    let invalid = || {
+        error(
+            line,
+            format!("expected a column as WIDTH:ALIGN, got {}", column),
+        )
+    };
+
+    Ok(Column {
+        width: width.parse().map_err(|_| invalid())?,
+        align: match align {
+            "left" => Align::Left,
+            "center" => Align::Center,
+            "right" => Align::Right,
+            _ => return Err(invalid()),
+        },
+    })
+}
+
+/// Splits the cells of `@row` on the `|` outside of `{{ }}`.
+fn split_cells(text: &str) -> Vec<&str> {
+    let mut cells = vec![];
+    let mut start = 0;
+    let mut in_value = false;
+
+    for (i, _) in text.char_indices() {
+        let rest = &text[i..];
+        if rest.starts_with("{{") {
+            in_value = true;
+        } else if rest.starts_with("}}") {
+            in_value = false;
+        } else if rest.starts_with('|') && !in_value {
+            cells.push(&text[start..i]);
+            start = i + 1;
+        }
+    }
+    cells.push(&text[start..]);
+
+    cells
+}
+
+fn parse_path(path: &str) -> Vec<String> {
+    path.split('.').map(str::to_string).collect()
+}
+
+fn parse_text(text: &str, line: usize) -> Result<Vec<Segment>, Error> {
+    let mut segments = vec![];
+    let mut rest = text;
+
+    while let Some(start) = rest.find("{{") {
+        if start > 0 {
+            segments.push(Segment::Literal(rest[..start].to_string()));
+        }
+
+        let value = &rest[start + 2..];
+        let end = value
+            .find("}}")
+            .ok_or_else(|| error(line, "{{ without }}"))?;
+        segments.push(parse_value(&value[..end], line)?);
+        rest = &value[end + 2..];
+    }
+
+    if !rest.is_empty() {
+        segments.push(Segment::Literal(rest.to_string()));
+    }
+
+    Ok(segments)
+}
+
+fn parse_value(text: &str, line: usize) -> Result<Segment, Error> {
+    let tokens = tokenize(text, line)?;
+    let mut groups = tokens.split(|token| *token == Token::Pipe);
+
+    let path = match groups.next() {
+        Some([Token::Word(path)]) => parse_path(path),
+        _ => return Err(error(line, format!("expected a path in {{{{{}}}}}", text))),
+    };
+    let filters = groups
+        .map(|filter| parse_filter(filter, line))
+        .collect::<Result<_, _>>()?;
+
+    Ok(Segment::Value { path, filters })
+}
+
+fn parse_filter(tokens: &[Token], line: usize) -> Result<Filter, Error> {
+    let argument = |token: &Token| match token {
+        Token::Word(argument) | Token::Quoted(argument) => argument.clone(),
+        Token::Pipe => unreachable!(),
+    };
+    let count = |token: &Token| {
+        argument(token)
+            .parse::<usize>()
+            .map_err(|_| error(line, "expected a number of characters or decimals"))
+    };
+
+    let filter = match tokens {
+        [Token::Word(name), decimals] if name == "number" => Filter::Number(count(decimals)?),
+        [Token::Word(name)] if name == "number" => Filter::Number(0),
+        [Token::Word(name), symbol] if name == "currency" => Filter::Currency {
+            symbol: argument(symbol),
+            decimals: 2,
+        },
+        [Token::Word(name), symbol, decimals] if name == "currency" => Filter::Currency {
+            symbol: argument(symbol),
+            decimals: count(decimals)?,
+        },
+        [Token::Word(name), format] if name == "date" => {
+            let format = argument(format);
+            check_date_format(&format, line)?;
+            Filter::Date(format)
+        }
+        [Token::Word(name)] if name == "upper" => Filter::Upper,
+        [Token::Word(name)] if name == "lower" => Filter::Lower,
+        [Token::Word(name), text] if name == "default" => Filter::Default(argument(text)),
+        [Token::Word(name), length] if name == "truncate" => Filter::Truncate(count(length)?),
+        [Token::Word(name), ..] => {
+            return Err(error(line, format!("invalid filter {}", name)));
+        }
+        _ => return Err(error(line, "expected a filter after |")),
+    };
+
+    Ok(filter)
+}
+
+fn parse_condition(text: &str, line: usize) -> Result<Condition, Error> {
+    let tokens = tokenize(text, line)?;
+    let (negate, tokens) = match tokens.split_first() {
+        Some((Token::Word(not), rest)) if not == "not" => (true, rest),
+        _ => (false, tokens.as_slice()),
+    };
+
+    let invalid = || error(line, "expected @if [not] PATH [OPERATOR VALUE]");
+    let (path, comparison) = match tokens {
+        [Token::Word(path)] => (path, None),
+        [Token::Word(path), Token::Word(operator), value] => {
+            let operator = match operator.as_str() {
+                "==" => Comparison::Equal,
+                "!=" => Comparison::NotEqual,
+                "<" => Comparison::Less,
+                "<=" => Comparison::LessOrEqual,
+                ">" => Comparison::Greater,
+                ">=" => Comparison::GreaterOrEqual,
+                _ => return Err(invalid()),
+            };
+            let value = match value {
+                Token::Quoted(text) => Value::String(text.clone()),
+                Token::Word(word) => serde_json::from_str(word).map_err(|_| invalid())?,
+                Token::Pipe => return Err(invalid()),
+            };
+            (path, Some((operator, value)))
+        }
+        _ => return Err(invalid()),
+    };
+
+    Ok(Condition {
+        negate,
+        path: parse_path(path),
+        comparison,
+    })
+}
+
+/// Splits `text` in words, quoted strings and pipes.
+fn tokenize(text: &str, line: usize) -> Result<Vec<Token>, Error> {
+    let mut tokens = vec![];
+    let mut characters = text.chars().peekable();
+
+    while let Some(&character) = characters.peek() {
+        match character {
+            ' ' | '\t' => {
+                characters.next();
+            }
+            '|' => {
+                characters.next();
+                tokens.push(Token::Pipe);
+            }
+            '"' => {
+                characters.next();
+                let mut quoted = String::new();
+                loop {
+                    match characters.next() {
+                        Some('"') => break,
+                        Some('\\') => match characters.next() {
+                            Some(escaped) => quoted.push(escaped),
+                            None => return Err(error(line, "unterminated string")),
+                        },
+                        Some(character) => quoted.push(character),
+                        None => return Err(error(line, "unterminated string")),
+                    }
+                }
+                tokens.push(Token::Quoted(quoted));
+            }
+            _ => {
+                let mut word = String::new();
+                while let Some(&character) = characters.peek() {
+                    if matches!(character, ' ' | '\t' | '|' | '"') {
+                        break;
+                    }
+                    word.push(character);
+                    characters.next();
+                }
+                tokens.push(Token::Word(word));
+            }
+        }
+    }
+
+    Ok(tokens)
+}
+
+fn check_date_format(format: &str, line: usize) -> Result<(), Error> {
+    let mut characters = format.chars();
+    while let Some(character) = characters.next() {
+        if character == '%' {
+            match characters.next() {
+                Some('Y' | 'y' | 'm' | 'd' | 'H' | 'M' | 'S' | 'b' | '%') => {}
+                Some(specifier) => {
+                    return Err(error(line, format!("unknown date format %{}", specifier)))
+                }
+                None => return Err(error(line, "date format ending with %")),
+            }
+        }
+    }
+
+    Ok(())
+}
+
+struct Renderer<'a> {
+    images: &'a HashMap<String, Image>,
+    data: &'a Value,
+    /// Loop variables, innermost last.
+    scopes: Vec<(String, Value)>,
+    receipt: Receipt,
+    style: TextStyle,
+    align: Align,
+    columns: Vec<Column>,
+}
+
+impl Renderer<'_> {
+    fn render(&mut self, lines: &[Line]) -> Result<(), Error> {
+        for line in lines {
+            self.render_line(line)?;
+        }
+
+        Ok(())
+    }
+
+    fn render_line(&mut self, line: &Line) -> Result<(), Error> {
+        let number = line.number;
+
+        match &line.node {
+            Node::Text(segments) => {
+                let text = self.interpolate(segments, number)?;
+                self.receipt.text(&text, self.style, self.align);
+            }
+            Node::Align(align) => self.align = *align,
+            Node::Bold(bold) => self.style.bold = *bold,
+            Node::Underline(underline) => self.style.underline = *underline,
+            Node::Invert(invert) => self.style.invert = *invert,
+            Node::Font(font) => self.style.font = *font,
+            Node::Size(width, height) => {
+                self.style.width = *width;
+                self.style.height = *height;
+            }
+            Node::Reset => {
+                self.style = TextStyle::default();
+                self.align = Align::Left;
+            }
+            Node::Columns(columns) => self.columns = columns.clone(),
+            Node::Row(cells) => {
+                let row = cells
+                    .iter()
+                    .map(|cell| self.interpolate(cell, number))
+                    .collect::<Result<Vec<_>, _>>()?;
+                let columns = match self.columns.is_empty() {
+                    true => vec![Column::default(); row.len()],
+                    false => self.columns.clone(),
+                };
+
+                // Consecutive rows with the same layout make one table.
+                if let Some(ReceiptItem::Table {
+                    columns: last,
+                    rows,
+                    style,
+                }) = self.receipt.items.last_mut()
+                {
+                    if *last == columns && *style == self.style {
+                        rows.push(row);
+                        return Ok(());
+                    }
+                }
+                self.receipt.table(columns, vec![row], self.style);
+            }
+            Node::Separator(character) => {
+                self.receipt.separator(*character);
+            }
+            Node::Feed(lines) => {
+                self.receipt.feed(*lines);
+            }
+            Node::Cut(partial) => {
+                self.receipt.cut(*partial);
+            }
+            Node::Drawer(pin) => {
+                self.receipt.cash_draw(*pin);
+            }
+            Node::Barcode {
+                r#type,
+                options,
+                data,
+            } => {
+                let data = self.interpolate(data, number)?;
+                self.receipt
+                    .barcode(&data, *r#type, options.clone(), self.align);
+            }
+            Node::QRCode { options, data } => {
+                let data = self.interpolate(data, number)?;
+                self.receipt.qrcode(&data, options.clone(), self.align);
+            }
+            Node::Image(name) => {
+                let image = self
+                    .images
+                    .get(name)
+                    .ok_or_else(|| error(number, format!("no image named {}", name)))?;
+                self.receipt.image(image.clone(), self.align);
+            }
+            Node::For {
+                variable,
+                path,
+                body,
+            } => {
+                let elements = match self.lookup(path) {
+                    Value::Array(elements) => elements,
+                    Value::Null => vec![],
+                    _ => return Err(error(number, format!("{} isn't an array", path.join(".")))),
+                };
+
+                let count = elements.len();
+                for (i, element) in elements.into_iter().enumerate() {
+                    let state = serde_json::json!({
+                        "index": i + 1,
+                        "first": i == 0,
+                        "last": i + 1 == count,
+                    });
+                    self.scopes.push(("loop".to_string(), state));
+                    self.scopes.push((variable.clone(), element));
+                    let result = self.render(body);
+                    self.scopes.truncate(self.scopes.len() - 2);
+                    result?;
+                }
+            }
+            Node::If {
+                condition,
+                then,
+                otherwise,
+            } => match self.evaluate(condition) {
+                true => self.render(then)?,
+                false => self.render(otherwise)?,
+            },
+        }
+
+        Ok(())
+    }
+
+    /// The value at `path`, looked up in the loop variables then in the data,
+    /// or null if there is none.
+    fn lookup(&self, path: &[String]) -> Value {
+        let Some((name, fields)) = path.split_first() else {
+            return Value::Null;
+        };
+
+        let mut value = self
+            .scopes
+            .iter()
+            .rev()
+            .find(|(variable, _)| variable == name)
+            .map(|(_, value)| value)
+            .or_else(|| self.data.get(name));
+
+        for field in fields {
+            value = value.and_then(|value| match value {
+                Value::Array(elements) => elements.get(field.parse::<usize>().ok()?),
+                _ => value.get(field),
+            });
+        }
+
+        value.cloned().unwrap_or(Value::Null)
+    }
+
+    fn interpolate(&self, segments: &[Segment], line: usize) -> Result<String, Error> {
+        let mut text = String::new();
+
+        for segment in segments {
+            match segment {
+                Segment::Literal(literal) => text.push_str(literal),
+                Segment::Value { path, filters } => {
+                    let mut value = self.lookup(path);
+                    for filter in filters {
+                        value = apply(filter, value, line)?;
+                    }
+                    text.push_str(&to_text(&value));
+                }
+            }
+        }
+
+        Ok(text)
+    }
+
+    fn evaluate(&self, condition: &Condition) -> bool {
+        let value = self.lookup(&condition.path);
+
+        let holds = match &condition.comparison {
+            None => match &value {
+                Value::Null => false,
+                Value::Bool(value) => *value,
+                Value::Number(number) => number.as_f64() != Some(0.0),
+                Value::String(text) => !text.is_empty(),
+                Value::Array(elements) => !elements.is_empty(),
+                Value::Object(fields) => !fields.is_empty(),
+            },
+            Some((comparison, other)) => {
+                let ordering = match (value.as_f64(), other.as_f64()) {
+                    (Some(value), Some(other)) => value.partial_cmp(&other),
+                    _ => match (value.as_str(), other.as_str()) {
+                        (Some(value), Some(other)) => Some(value.cmp(other)),
+                        _ => None,
+                    },
+                };
+
+                match comparison {
+                    Comparison::Equal => ordering.map_or(value == *other, |o| o.is_eq()),
+                    Comparison::NotEqual => ordering.map_or(value != *other, |o| o.is_ne()),
+                    Comparison::Less => ordering.is_some_and(|o| o.is_lt()),
+                    Comparison::LessOrEqual => ordering.is_some_and(|o| o.is_le()),
+                    Comparison::Greater => ordering.is_some_and(|o| o.is_gt()),
+                    Comparison::GreaterOrEqual => ordering.is_some_and(|o| o.is_ge()),
+                }
+            }
+        };
+
+        holds != condition.negate
+    }
+}
+
+fn to_text(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(text) => text.clone(),
+        value => value.to_string(),
+    }
+}
+
+fn number(value: &Value) -> Option<f64> {
+    match value {
+        Value::Number(number) => number.as_f64(),
+        Value::String(text) => text.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+fn apply(filter: &Filter, value: Value, line: usize) -> Result<Value, Error> {
+    let not_a_number = || error(line, format!("expected a number, got {}", value));
+
+    let text = match filter {
+        Filter::Number(decimals) => {
+            let number = number(&value).ok_or_else(not_a_number)?;
+            format!("{:.*}", decimals, number)
+        }
+        Filter::Currency { symbol, decimals } => {
+            let amount = number(&value).ok_or_else(not_a_number)?;
+            currency(amount, symbol, *decimals)
+        }
+        Filter::Date(format) => {
+            let date = date(&value)
+                .ok_or_else(|| error(line, format!("expected a date, got {}", value)))?;
+            format_date(date, format)
+        }
+        Filter::Upper => to_text(&value).to_uppercase(),
+        Filter::Lower => to_text(&value).to_lowercase(),
+        Filter::Default(default) => match &value {
+            Value::Null => default.clone(),
+            Value::String(text) if text.is_empty() => default.clone(),
+            _ => return Ok(value),
+        },
+        Filter::Truncate(length) => to_text(&value).chars().take(*length).collect(),
+    };
+
+    Ok(Value::String(text))
+}
+
+/// Formats `amount` with `decimals` decimals, a comma between thousands and
+/// `symbol` in front.
+fn currency(amount: f64, symbol: &str, decimals: usize) -> String {
+    let digits = format!("{:.*}", decimals, amount.abs());
+    let (integer, fraction) = match digits.split_once('.') {
+        Some((integer, fraction)) => (integer, Some(fraction)),
+        None => (digits.as_str(), None),
+    };
+
+    let mut text = String::new();
+    if amount < 0.0 && digits.bytes().any(|digit| matches!(digit, b'1'..=b'9')) {
+        text.push('-');
+    }
+    text.push_str(symbol);
+    for (i, digit) in integer.chars().enumerate() {
+        if i > 0 && (integer.len() - i) % 3 == 0 {
+            text.push(',');
+        }
+        text.push(digit);
+    }
+    if let Some(fraction) = fraction {
+        text.push('.');
+        text.push_str(fraction);
+    }
+
+    text
+}
+
+/// Year, month, day, hour, minute and second.
+type Date = (i64, u32, u32, u32, u32, u32);
+
+/// Reads a unix timestamp in seconds, or an ISO 8601 date such as
+/// `2024-05-01` or `2024-05-01T08:30:00Z`, ignoring the time zone.
+fn date(value: &Value) -> Option<Date> {
+    match value {
+        Value::Number(number) => {
+            let seconds = number
+                .as_i64()
+                .or_else(|| Some(number.as_f64()?.floor() as i64))?;
+            let (year, month, day) = civil_from_days(seconds.div_euclid(86400));
+            let time = seconds.rem_euclid(86400) as u32;
+            Some((year, month, day, time / 3600, time / 60 % 60, time % 60))
+        }
+        Value::String(text) => {
+            let field = |range: std::ops::Range<usize>| -> Option<u32> {
+                let digits = text.get(range)?;
+                digits
+                    .bytes()
+                    .all(|digit| digit.is_ascii_digit())
+                    .then(|| digits.parse().ok())?
+            };
+
+            let (year, month, day) = (field(0..4)?, field(5..7)?, field(8..10)?);
+            if text.get(4..5) != Some("-")
+                || text.get(7..8) != Some("-")
+                || !(1..=12).contains(&month)
+                || !(1..=31).contains(&day)
+            {
+                return None;
+            }
+
+            let (hour, minute, second) = match text.get(10..11) {
+                None => (0, 0, 0),
+                Some("T" | " ") => (
+                    field(11..13)?,
+                    field(14..16)?,
+                    match text.get(16..17) {
+                        Some(":") => field(17..19)?,
+                        _ => 0,
+                    },
+                ),
+                Some(_) => return None,
+            };
+
+            Some((year as i64, month, day, hour, minute, second))
+        }
+        _ => None,
+    }
+}
+
+/// The date `days` days after 1970-01-01.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let days = days + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days.rem_euclid(146097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * month + 2) / 5 + 1) as u32;
+    let month = if month < 10 { month + 3 } else { month - 9 } as u32;
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    (year, month, day)
+}
+
+fn format_date((year, month, day, hour, minute, second): Date, format: &str) -> String {
+    const MONTHS: [&str; 12] = [
+        "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+    ];
+
+    let mut text = String::new();
+    let mut characters = format.chars();
+    while let Some(character) = characters.next() {
+        if character != '%' {
+            text.push(character);
+            continue;
+        }
+
+        match characters.next() {
+            Some('Y') => text.push_str(&format!("{:04}", year)),
+            Some('y') => text.push_str(&format!("{:02}", year.rem_euclid(100))),
+            Some('m') => text.push_str(&format!("{:02}", month)),
+            Some('d') => text.push_str(&format!("{:02}", day)),
+            Some('H') => text.push_str(&format!("{:02}", hour)),
+            Some('M') => text.push_str(&format!("{:02}", minute)),
+            Some('S') => text.push_str(&format!("{:02}", second)),
+            Some('b') => text.push_str(MONTHS[month as usize - 1]),
+            _ => text.push('%'),
+        }
+    }
+
+    text
+}
@@ -0,0 +1,319 @@
+//! Sending commands to a printer, over a device file, a TCP socket or a
+//! serial port, or to a file.
+
+use std::{
+    fmt,
+    fs::{File, OpenOptions},
+    io::{self, Read, Write},
+    net::{TcpStream, ToSocketAddrs},
+    path::PathBuf,
+    str::FromStr,
+    sync::mpsc,
+    thread,
+    time::Duration,
+};
+
+/// The port of raw printing on network printers, also known as JetDirect.
+const RAW_PORT: u16 = 9100;
+
+const DEFAULT_BAUD_RATE: u32 = 9600;
+
+/// Where a printer is connected.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Destination {
+    /// A device file, such as `/dev/usb/lp0`, which must exist.
+    Device(PathBuf),
+    /// A regular file, created or replaced by each job, to print it later
+    /// or elsewhere.
+    File(PathBuf),
+    /// A network printer.
+    Tcp { host: String, port: u16 },
+    /// A serial port, such as `/dev/ttyUSB0` or `COM3`. Opening one needs
+    /// the `serial` feature.
+    Serial { path: String, baud_rate: u32 },
+}
+
+/// An open connection to a printer, written to and read from for status.
+pub trait Connection: Read + Write + Send {}
+
+impl<T: Read + Write + Send> Connection for T {}
+
+impl Destination {
+    /// Opens a connection, giving up on reads, writes and connections after
+    /// `timeout`.
+    pub fn connect(&self, timeout: Duration) -> io::Result<Box<dyn Connection>> {
+        match self {
+            Destination::Device(path) => {
+                let metadata = path.metadata().map_err(|error| {
+                    io::Error::new(
+                        error.kind(),
+                        format!("no device at {}: {}", path.display(), error),
+                    )
+                })?;
+                if metadata.is_file() {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        format!(
+                            "{} is a regular file, use file:{0} to write to it",
+                            path.display()
+                        ),
+                    ));
+                }
+
+                Ok(Box::new(Device::open(path.clone(), timeout)?))
+            }
+            Destination::File(path) => Ok(Box::new(File::create(path)?)),
+            Destination::Tcp { host, port } => {
+                let mut last_error = None;
+                for address in (host.as_str(), *port).to_socket_addrs()? {
+                    match TcpStream::connect_timeout(&address, timeout) {
+                        Ok(stream) => {
+                            stream.set_read_timeout(Some(timeout))?;
+                            stream.set_write_timeout(Some(timeout))?;
+                            return Ok(Box::new(stream));
+                        }
+                        Err(error) => last_error = Some(error),
+                    }
+                }
+                Err(last_error.unwrap_or_else(|| {
+                    io::Error::new(io::ErrorKind::NotFound, format!("no address for {}", host))
+                }))
+            }
+            #[cfg(feature = "serial")]
+            Destination::Serial { path, baud_rate } => {
+                let port = serialport::new(path, *baud_rate)
+                    .timeout(timeout)
+                    .open()
+                    .map_err(io::Error::from)?;
+                Ok(Box::new(port))
+            }
+            #[cfg(not(feature = "serial"))]
+            Destination::Serial { .. } => Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "serial ports need the serial feature",
+            )),
+        }
+    }
+
+    /// Opens a connection, writes `data` and closes it.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let destination: Destination = "tcp:192.168.1.50".parse()?;
+    /// destination.send(&printer.get_data(), Duration::from_secs(5))?;
+    /// ```
+    pub fn send(&self, data: &[u8], timeout: Duration) -> io::Result<()> {
+        let mut connection = self.connect(timeout)?;
+        connection.write_all(data)?;
+        connection.flush()
+    }
+}
+
+impl FromStr for Destination {
+    type Err = String;
+
+    /// Parses `tcp:HOST[:PORT]`, `serial:PATH[@BAUD]`, `file:PATH` or the
+    /// path of a device. The port defaults to 9100 and the baud rate to
+    /// 9600.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(address) = s.strip_prefix("tcp:") {
+            let (host, port) = match address.rsplit_once(':') {
+                // An IPv6 address without a port, such as `::1`.
+                Some((host, _)) if host.contains(':') && !host.ends_with(']') => {
+                    (address, RAW_PORT)
+                }
+                Some((host, port)) => (
+                    host,
+                    port.parse()
+                        .map_err(|_| format!("invalid port {:?}", port))?,
+                ),
+                None => (address, RAW_PORT),
+            };
+            let host = host.trim_start_matches('[').trim_end_matches(']');
+            if host.is_empty() {
+                return Err("missing host".to_string());
+            }
+
+            return Ok(Destination::Tcp {
+                host: host.to_string(),
+                port,
+            });
+        }
+
+        if let Some(port) = s.strip_prefix("serial:") {
+            let (path, baud_rate) = match port.rsplit_once('@') {
+                Some((path, baud_rate)) => (
+                    path,
+                    baud_rate
+                        .parse()
+                        .map_err(|_| format!("invalid baud rate {:?}", baud_rate))?,
+                ),
+                None => (port, DEFAULT_BAUD_RATE),
+            };
+            if path.is_empty() {
+                return Err("missing serial port".to_string());
+            }
+
+            return Ok(Destination::Serial {
+                path: path.to_string(),
+                baud_rate,
+            });
+        }
+
+        if let Some(path) = s.strip_prefix("file:") {
+            if path.is_empty() {
+                return Err("missing file".to_string());
+            }
+
+            return Ok(Destination::File(PathBuf::from(path)));
+        }
+
+        if s.is_empty() {
+            return Err("missing device".to_string());
+        }
+
+        Ok(Destination::Device(PathBuf::from(s)))
+    }
+}
+
+impl fmt::Display for Destination {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Destination::Device(path) => write!(f, "{}", path.display()),
+            Destination::File(path) => write!(f, "file:{}", path.display()),
+            Destination::Tcp { host, port } if host.contains(':') => {
+                write!(f, "tcp:[{}]:{}", host, port)
+            }
+            Destination::Tcp { host, port } => write!(f, "tcp:{}:{}", host, port),
+            Destination::Serial { path, baud_rate } => write!(f, "serial:{}@{}", path, baud_rate),
+        }
+    }
+}
+
+enum Operation {
+    Read(usize),
+    Write(Vec<u8>),
+    Flush,
+}
+
+enum Done {
+    Opened,
+    Read(Vec<u8>),
+    Written(usize),
+    Flushed,
+}
+
+/// A device file, opened, read and written on a thread of its own so that
+/// a printer that stopped answering times out like a socket does. The
+/// thread is left to finish the operation that timed out.
+struct Device {
+    operations: mpsc::Sender<Operation>,
+    done: mpsc::Receiver<io::Result<Done>>,
+    timeout: Duration,
+    timed_out: bool,
+}
+
+impl Device {
+    fn open(path: PathBuf, timeout: Duration) -> io::Result<Self> {
+        let (operations, receiver) = mpsc::channel();
+        let (sender, done) = mpsc::channel();
+        thread::spawn(move || {
+            // Printers that can't be read from are only written to.
+            let file = OpenOptions::new()
+                .read(true)
+                .write(true)
+                .open(&path)
+                .or_else(|_| OpenOptions::new().write(true).open(&path));
+            let mut file = match file {
+                Ok(file) => file,
+                Err(error) => {
+                    let _ = sender.send(Err(error));
+                    return;
+                }
+            };
+            let _ = sender.send(Ok(Done::Opened));
+
+            for operation in receiver {
+                let result = match operation {
+                    Operation::Read(length) => {
+                        let mut buffer = vec![0; length];
+                        file.read(&mut buffer).map(|read| {
+                            buffer.truncate(read);
+                            Done::Read(buffer)
+                        })
+                    }
+                    Operation::Write(data) => file.write(&data).map(Done::Written),
+                    Operation::Flush => file.flush().map(|()| Done::Flushed),
+                };
+                if sender.send(result).is_err() {
+                    break;
+                }
+            }
+        });
+
+        let mut device = Self {
+            operations,
+            done,
+            timeout,
+            timed_out: false,
+        };
+        device.wait()?;
+        Ok(device)
+    }
+
+    fn run(&mut self, operation: Operation) -> io::Result<Done> {
+        if self.timed_out {
+            return Err(timed_out());
+        }
+
+        self.operations
+            .send(operation)
+            .map_err(|_| io::Error::from(io::ErrorKind::BrokenPipe))?;
+        self.wait()
+    }
+
+    /// Waits for the thread to be done with the last operation. Once it
+    /// times out, the device is given up on.
+    fn wait(&mut self) -> io::Result<Done> {
+        match self.done.recv_timeout(self.timeout) {
+            Ok(result) => result,
+            Err(mpsc::RecvTimeoutError::Timeout) => {
+                self.timed_out = true;
+                Err(timed_out())
+            }
+            Err(mpsc::RecvTimeoutError::Disconnected) => {
+                Err(io::Error::from(io::ErrorKind::BrokenPipe))
+            }
+        }
+    }
+}
+
+impl Read for Device {
+    fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+        match self.run(Operation::Read(buffer.len()))? {
+            Done::Read(data) => {
+                buffer[..data.len()].copy_from_slice(&data);
+                Ok(data.len())
+            }
+            _ => unreachable!(),
+        }
+    }
+}
+
+impl Write for Device {
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        match self.run(Operation::Write(data.to_vec()))? {
+            Done::Written(written) => Ok(written),
+            _ => unreachable!(),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.run(Operation::Flush).map(|_| ())
+    }
+}
+
+fn timed_out() -> io::Error {
+    io::Error::new(io::ErrorKind::TimedOut, "the device didn't answer in time")
+}
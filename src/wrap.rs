@@ -0,0 +1,97 @@
+//! Wrapping text to the width of the paper, shared by receipts, Markdown and
+//! HTML.
+
+use crate::printer::{Printer, TextUnderline};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) struct Emphasis {
+    pub bold: bool,
+    pub underline: bool,
+}
+
+/// A character and its emphasis.
+pub(crate) type Styled = (char, Emphasis);
+
+/// Prints `text` wrapped to the width of the paper, with `first` in front
+/// of the first line and `hanging` in front of the others.
+pub(crate) fn print_wrapped<P: Printer>(
+    printer: &mut P,
+    text: &[Styled],
+    first: &str,
+    hanging: &str,
+) {
+    let width = (printer.chars_per_line() as usize).saturating_sub(first.chars().count());
+
+    for (i, line) in wrap_styled(text, width).iter().enumerate() {
+        printer
+            .set_text_bold(false)
+            .set_text_underline(TextUnderline::None)
+            .text(if i == 0 { first } else { hanging });
+
+        for run in line.chunk_by(|(_, a), (_, b)| a == b) {
+            let emphasis = run[0].1;
+            let text: String = run.iter().map(|(character, _)| character).collect();
+            printer
+                .set_text_bold(emphasis.bold)
+                .set_text_underline(match emphasis.underline {
+                    true => TextUnderline::OneDotThick,
+                    false => TextUnderline::None,
+                })
+                .text(&text);
+        }
+        printer.new_line();
+    }
+}
+
+/// Wraps `text` on whitespace in lines of at most `width` characters,
+/// breaking the words that are longer.
+pub(crate) fn wrap(text: &str, width: usize) -> Vec<String> {
+    let words = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    let text: Vec<Styled> = words
+        .chars()
+        .map(|character| (character, Emphasis::default()))
+        .collect();
+
+    wrap_styled(&text, width)
+        .into_iter()
+        .map(|line| line.into_iter().map(|(character, _)| character).collect())
+        .collect()
+}
+
+/// Wraps `text` on spaces like [`wrap`], keeping the emphasis of each
+/// character.
+pub(crate) fn wrap_styled(text: &[Styled], width: usize) -> Vec<Vec<Styled>> {
+    let width = width.max(1);
+    let mut lines = vec![];
+    let mut line: Vec<Styled> = vec![];
+    let mut start: usize = 0;
+
+    for word in text.split(|(character, _)| *character == ' ') {
+        // Words are separated by a single space, which keeps its emphasis.
+        let space = start.checked_sub(1).map(|i| text[i]);
+        start += word.len() + 1;
+        let mut word = word.to_vec();
+
+        if !line.is_empty() && line.len() + 1 + word.len() > width {
+            lines.push(std::mem::take(&mut line));
+        }
+
+        while word.len() > width {
+            if !line.is_empty() {
+                lines.push(std::mem::take(&mut line));
+            }
+            lines.push(word.drain(..width).collect());
+        }
+
+        if !line.is_empty() {
+            line.push(space.unwrap_or((' ', Emphasis::default())));
+        }
+        line.extend(word);
+    }
+
+    if !line.is_empty() || lines.is_empty() {
+        lines.push(line);
+    }
+
+    lines
+}
@@ -0,0 +1,80 @@
+#![cfg(feature = "cups")]
+
+use thermal_printer::{CupsRaster, Image, RasterPage};
+
+/// Version 2, big-endian, 8 by 4 dots of 8-bit gray, with compressed lines:
+///
+/// - a line repeated twice, of a run of four black dots and four literal
+///   dots,
+/// - a line cleared to white by its first byte,
+/// - a line of a single run of mid gray.
+const GRAY: &[u8] = include_bytes!("fixtures/gray.ras");
+
+/// Version 3, little-endian, a red and a blue dot of 8-bit sRGB.
+const RGB: &[u8] = include_bytes!("fixtures/rgb.ras");
+
+fn read(raster: &[u8]) -> Vec<RasterPage> {
+    let mut raster = CupsRaster::new(raster).unwrap();
+    let mut pages = vec![];
+    while let Some(page) = raster.next_page().unwrap() {
+        pages.push(page);
+    }
+    pages
+}
+
+fn levels(image: &Image) -> Vec<Vec<u8>> {
+    image
+        .get_pixels()
+        .iter()
+        .map(|row| row.iter().map(|pixel| pixel.red()).collect())
+        .collect()
+}
+
+#[test]
+fn decodes_compressed_lines() {
+    let pages = read(GRAY);
+    assert_eq!(pages.len(), 1);
+
+    let page = &pages[0];
+    assert_eq!((page.width, page.height), (8, 4));
+    assert_eq!(page.resolution, (203, 203));
+    assert_eq!(page.cut_media, 4);
+    assert_eq!(
+        levels(&page.to_image(u16::MAX)),
+        [
+            [0, 0, 0, 0, 0x40, 0x80, 0xc0, 0xff],
+            [0, 0, 0, 0, 0x40, 0x80, 0xc0, 0xff],
+            [0xff; 8],
+            [0x80; 8],
+        ]
+    );
+}
+
+#[test]
+fn decodes_uncompressed_colors() {
+    let pages = read(RGB);
+    assert_eq!(pages.len(), 1);
+
+    let page = &pages[0];
+    assert_eq!((page.width, page.height), (2, 1));
+    assert_eq!(page.resolution, (180, 180));
+    assert_eq!(page.cut_media, 1);
+    assert_eq!(levels(&page.to_image(u16::MAX)), [[76, 29]]);
+}
+
+#[test]
+fn scales_wide_pages_down() {
+    let page = &read(GRAY)[0];
+    assert_eq!(levels(&page.to_image(4)), [[0, 0, 0x60, 0xdf], [0xbf; 4]]);
+}
+
+#[test]
+fn refuses_truncated_pages() {
+    let mut raster = CupsRaster::new(&GRAY[..GRAY.len() - 1]).unwrap();
+    assert!(raster.next_page().is_err());
+
+    let mut raster = CupsRaster::new(&GRAY[..100]).unwrap();
+    assert!(raster.next_page().is_err());
+
+    assert!(CupsRaster::new(&b"RaSx"[..]).is_err());
+}
@@ -0,0 +1,293 @@
+#![cfg(feature = "server")]
+
+use std::{
+    fs,
+    io::{Read, Write},
+    net::{SocketAddr, TcpListener, TcpStream},
+    sync::mpsc,
+    thread,
+    time::{Duration, Instant},
+};
+
+use serde_json::Value;
+use thermal_printer::{Destination, PrintServer, PrinterProfile, RunningServer};
+
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Sends an HTTP request and returns the status code and the JSON body.
+fn request(
+    address: SocketAddr,
+    method: &str,
+    path: &str,
+    headers: &[(&str, &str)],
+    body: &[u8],
+) -> (u16, Value) {
+    let mut stream = TcpStream::connect(address).unwrap();
+    stream.set_read_timeout(Some(TIMEOUT)).unwrap();
+
+    let mut head = format!(
+        "{} {} HTTP/1.1\r\nHost: {}\r\nConnection: close\r\nContent-Length: {}\r\n",
+        method,
+        path,
+        address,
+        body.len()
+    );
+    for (field, value) in headers {
+        head.push_str(&format!("{}: {}\r\n", field, value));
+    }
+    head.push_str("\r\n");
+    stream.write_all(head.as_bytes()).unwrap();
+    stream.write_all(body).unwrap();
+
+    let mut response = vec![];
+    stream.read_to_end(&mut response).unwrap();
+    let response = String::from_utf8(response).unwrap();
+    let (head, body) = response.split_once("\r\n\r\n").unwrap();
+    let code = head.split(' ').nth(1).unwrap().parse().unwrap();
+    let body = if body.is_empty() {
+        Value::Null
+    } else {
+        serde_json::from_str(body).unwrap()
+    };
+
+    (code, body)
+}
+
+fn start(destination: Destination) -> RunningServer {
+    let mut server = PrintServer::new();
+    server
+        .set_timeout(TIMEOUT)
+        .add_printer("test", destination, PrinterProfile::default())
+        .allow_origin("https://pos.example.com");
+    server.start("127.0.0.1:0").unwrap()
+}
+
+fn tcp(listener: &TcpListener) -> Destination {
+    Destination::Tcp {
+        host: "127.0.0.1".to_string(),
+        port: listener.local_addr().unwrap().port(),
+    }
+}
+
+/// Polls the job `id` until its status is `status`.
+fn wait_for(address: SocketAddr, id: u64, status: &str) -> Value {
+    let start = Instant::now();
+    loop {
+        let (code, job) = request(address, "GET", &format!("/jobs/{}", id), &[], b"");
+        assert_eq!(code, 200);
+        if job["status"] == status {
+            return job;
+        }
+        assert!(start.elapsed() < TIMEOUT, "job {} is still {}", id, job);
+        thread::sleep(Duration::from_millis(10));
+    }
+}
+
+#[test]
+fn prints_raw_jobs() {
+    let printer = TcpListener::bind("127.0.0.1:0").unwrap();
+    let server = start(tcp(&printer));
+    let address = server.address();
+
+    // More than the socket buffers hold, so that the job prints until the
+    // printer reads it.
+    let data = vec![b'x'; 8 * 1024 * 1024];
+    let (code, job) = request(
+        address,
+        "POST",
+        "/printers/test/jobs",
+        &[("Content-Type", "application/octet-stream")],
+        &data,
+    );
+    assert_eq!(code, 202);
+    assert_eq!(job["status"], "queued");
+    assert_eq!(job["printer"], "test");
+    let id = job["id"].as_u64().unwrap();
+
+    let (mut connection, _) = printer.accept().unwrap();
+    wait_for(address, id, "printing");
+    let mut received = vec![];
+    connection.read_to_end(&mut received).unwrap();
+    assert_eq!(received, data);
+    wait_for(address, id, "done");
+
+    let (code, queue) = request(address, "GET", "/printers/test", &[], b"");
+    assert_eq!(code, 200);
+    assert_eq!(queue["printed"], 1);
+    assert_eq!(queue["queued"], 0);
+
+    server.stop();
+}
+
+#[test]
+fn prints_receipts() {
+    let printer = TcpListener::bind("127.0.0.1:0").unwrap();
+    let server = start(tcp(&printer));
+    let address = server.address();
+
+    let (code, job) = request(
+        address,
+        "POST",
+        "/printers/test/jobs",
+        &[("Content-Type", "application/json; charset=utf-8")],
+        br#"{"items": [{"text": {"text": "Hello"}}]}"#,
+    );
+    assert_eq!(code, 202, "{}", job);
+
+    let (mut connection, _) = printer.accept().unwrap();
+    let mut received = vec![];
+    connection.read_to_end(&mut received).unwrap();
+    assert!(received.starts_with(b"\x1b@"));
+    assert!(received.windows(5).any(|window| window == b"Hello"));
+    wait_for(address, job["id"].as_u64().unwrap(), "done");
+
+    server.stop();
+}
+
+#[test]
+fn reports_failed_jobs() {
+    // Nothing listens on the port once the listener is dropped.
+    let destination = tcp(&TcpListener::bind("127.0.0.1:0").unwrap());
+    let server = start(destination);
+    let address = server.address();
+
+    let (code, job) = request(
+        address,
+        "POST",
+        "/printers/test/jobs",
+        &[("Content-Type", "application/octet-stream")],
+        b"data",
+    );
+    assert_eq!(code, 202);
+
+    let job = wait_for(address, job["id"].as_u64().unwrap(), "failed");
+    assert!(job["error"].is_string());
+    let (_, queue) = request(address, "GET", "/printers/test", &[], b"");
+    assert_eq!(queue["failed"], 1);
+    assert_eq!(queue["last_error"], job["error"]);
+
+    server.stop();
+}
+
+#[test]
+fn refuses_invalid_jobs() {
+    let printer = TcpListener::bind("127.0.0.1:0").unwrap();
+    let server = start(tcp(&printer));
+    let address = server.address();
+
+    let (code, _) = request(address, "POST", "/printers/test/jobs", &[], b"data");
+    assert_eq!(code, 415);
+
+    let (code, error) = request(
+        address,
+        "POST",
+        "/printers/test/jobs",
+        &[("Content-Type", "application/json")],
+        b"{",
+    );
+    assert_eq!(code, 400);
+    assert!(error["error"].is_string());
+
+    let (code, _) = request(
+        address,
+        "POST",
+        "/printers/other/jobs",
+        &[("Content-Type", "application/octet-stream")],
+        b"data",
+    );
+    assert_eq!(code, 404);
+
+    let (code, _) = request(address, "GET", "/jobs/1", &[], b"");
+    assert_eq!(code, 404);
+
+    server.stop();
+}
+
+#[test]
+fn checks_origins() {
+    let printer = TcpListener::bind("127.0.0.1:0").unwrap();
+    let server = start(tcp(&printer));
+    let address = server.address();
+
+    let (code, _) = request(
+        address,
+        "GET",
+        "/printers",
+        &[("Origin", "https://evil.example.com")],
+        b"",
+    );
+    assert_eq!(code, 403);
+
+    let (code, printers) = request(
+        address,
+        "GET",
+        "/printers",
+        &[("Origin", "https://pos.example.com")],
+        b"",
+    );
+    assert_eq!(code, 200);
+    assert_eq!(printers[0]["name"], "test");
+
+    server.stop();
+}
+
+#[test]
+fn reads_the_status() {
+    let printer = TcpListener::bind("127.0.0.1:0").unwrap();
+    let server = start(tcp(&printer));
+    let address = server.address();
+
+    // Online with the drawer open.
+    let (sender, receiver) = mpsc::channel();
+    let fake = thread::spawn(move || {
+        let (mut connection, _) = printer.accept().unwrap();
+        let mut query = [0; 3];
+        connection.read_exact(&mut query).unwrap();
+        sender.send(query).unwrap();
+        connection.write_all(&[0x16]).unwrap();
+    });
+
+    let (code, status) = request(address, "GET", "/printers/test/status", &[], b"");
+    assert_eq!(receiver.recv().unwrap(), [0x10, 0x04, 0x01]);
+    assert_eq!(code, 200, "{}", status);
+    assert_eq!(status["online"], true);
+    assert_eq!(status["drawer_open"], true);
+    fake.join().unwrap();
+
+    server.stop();
+}
+
+#[test]
+fn refuses_invalid_status() {
+    let printer = TcpListener::bind("127.0.0.1:0").unwrap();
+    let server = start(tcp(&printer));
+    let address = server.address();
+
+    let fake = thread::spawn(move || {
+        let (mut connection, _) = printer.accept().unwrap();
+        let mut query = [0; 3];
+        connection.read_exact(&mut query).unwrap();
+        connection.write_all(&[0xff]).unwrap();
+    });
+
+    let (code, error) = request(address, "GET", "/printers/test/status", &[], b"");
+    assert_eq!(code, 502);
+    assert!(error["error"].as_str().unwrap().contains("0xFF"));
+    fake.join().unwrap();
+
+    server.stop();
+}
+
+#[test]
+fn keeps_files_on_status() {
+    let path = std::env::temp_dir().join(format!("thermal-printer-{}.prn", std::process::id()));
+    fs::write(&path, b"printed").unwrap();
+    let server = start(Destination::File(path.clone()));
+
+    let (code, _) = request(server.address(), "GET", "/printers/test/status", &[], b"");
+    assert_eq!(code, 502);
+    assert_eq!(fs::read(&path).unwrap(), b"printed");
+
+    server.stop();
+    fs::remove_file(path).unwrap();
+}